@@ -0,0 +1,173 @@
+use std::fmt;
+use std::iter::Sum;
+use std::ops::Add;
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::{GameError, MAX_CALL, MIN_CALL, NIL_CALL};
+use crate::rules::RuleSet;
+
+// the highest contract, the calls of the two partners of a team added up
+const MAX_CONTRACT: u8 = MAX_CALL * 2;
+
+/// Bid is the number of rounds a player calls to win in a deal, it's checked
+/// against the rules and the size of the table when it's made, a bid read from
+/// a message or a file only has to be one a player or a team could make
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Serialize, Deserialize)]
+#[serde(try_from = "u8")]
+pub struct Bid(u8);
+
+impl Bid {
+    // the call to take no tricks at all
+    pub const NIL: Bid = Bid(NIL_CALL);
+
+    /// new checks the call against the calls the rules allow at a table of the given size
+    /// ```rust
+    ///  # use callbreak::bid::Bid;
+    ///  # use callbreak::rules::RuleSet;
+    ///  assert!(Bid::new(3, &RuleSet::default(), 4).is_ok());
+    ///  assert!(Bid::new(9, &RuleSet::default(), 4).is_err());
+    /// ```
+    pub fn new(value: u8, rules: &RuleSet, total_players: usize) -> Result<Self, GameError> {
+        let bid = Bid(value);
+        if !Self::get_bids(rules, total_players).contains(&bid) {
+            return Err(GameError::InvalidCall);
+        }
+        Ok(bid)
+    }
+
+    /// get_bids returns every bid allowed at a table of the given size, lowest first
+    /// and so nil first when the rules allow it
+    pub fn get_bids(rules: &RuleSet, total_players: usize) -> Vec<Self> {
+        let (min_call, max_call) = rules.get_call_range(total_players);
+        let nil = rules.get_nil_bid().map(|_| Bid::NIL);
+        nil.into_iter()
+            .chain((min_call..=max_call).map(Bid))
+            .collect()
+    }
+
+    /// lowest returns the lowest bid that isn't nil, the one made when a player runs out of time
+    pub fn lowest(rules: &RuleSet, total_players: usize) -> Self {
+        Bid(rules.get_call_range(total_players).0)
+    }
+
+    pub fn get_value(&self) -> u8 {
+        self.0
+    }
+
+    pub fn is_nil(&self) -> bool {
+        *self == Bid::NIL
+    }
+
+    /// is_made checks if the tricks fulfil the bid, a nil bid is made by taking none
+    pub fn is_made(&self, tricks: u8) -> bool {
+        if self.is_nil() {
+            return tricks == 0;
+        }
+        tricks >= self.0
+    }
+
+    /// get_overtricks returns the tricks taken over the bid
+    pub fn get_overtricks(&self, tricks: u8) -> u8 {
+        tricks.saturating_sub(self.0)
+    }
+}
+
+impl TryFrom<u8> for Bid {
+    type Error = GameError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        if value != NIL_CALL && !(MIN_CALL..=MAX_CONTRACT).contains(&value) {
+            return Err(GameError::InvalidCall);
+        }
+        Ok(Bid(value))
+    }
+}
+
+impl Default for Bid {
+    fn default() -> Self {
+        Bid(MIN_CALL)
+    }
+}
+
+impl fmt::Display for Bid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_nil() {
+            return f.pad("nil");
+        }
+        f.pad(&self.0.to_string())
+    }
+}
+
+// the bids of the partners add up into the contract of the team
+impl Add for Bid {
+    type Output = Bid;
+
+    fn add(self, rhs: Bid) -> Bid {
+        Bid(self.0 + rhs.0)
+    }
+}
+
+impl Sum for Bid {
+    fn sum<I: Iterator<Item = Bid>>(iter: I) -> Bid {
+        iter.fold(Bid(0), |total, bid| total + bid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::score::NilBid;
+
+    #[test]
+    fn test_new() {
+        let rules = RuleSet::default();
+        assert_eq!(Bid::new(5, &rules, 4).map(|b| b.get_value()), Ok(5));
+        assert_eq!(Bid::new(1, &rules, 4), Err(GameError::InvalidCall));
+        assert_eq!(Bid::new(9, &rules, 4), Err(GameError::InvalidCall));
+        assert_eq!(Bid::new(NIL_CALL, &rules, 4), Err(GameError::InvalidCall));
+        assert_eq!(Bid::lowest(&rules, 4), Bid::default());
+
+        let rules = RuleSet {
+            nil_bid: Some(NilBid::default()),
+            max_call: 4,
+            ..RuleSet::default()
+        };
+        assert_eq!(Bid::new(NIL_CALL, &rules, 4), Ok(Bid::NIL));
+        assert_eq!(
+            Bid::get_bids(&rules, 4),
+            vec![Bid::NIL, Bid(2), Bid(3), Bid(4)]
+        );
+    }
+
+    #[test]
+    fn test_deserialize() {
+        assert_eq!(serde_json::from_str::<Bid>("5").unwrap(), Bid(5));
+        assert_eq!(serde_json::from_str::<Bid>("0").unwrap(), Bid::NIL);
+        // the contract of a team is up to the highest calls of both partners
+        assert_eq!(serde_json::from_str::<Bid>("16").unwrap(), Bid(16));
+        assert!(serde_json::from_str::<Bid>("1").is_err());
+        assert!(serde_json::from_str::<Bid>("17").is_err());
+        assert!(serde_json::from_str::<Bid>("255").is_err());
+    }
+
+    #[test]
+    fn test_scoring_helpers() {
+        assert!(Bid(3).is_made(3));
+        assert!(!Bid(3).is_made(2));
+        assert!(Bid::NIL.is_made(0));
+        assert!(!Bid::NIL.is_made(1));
+        assert_eq!(Bid(3).get_overtricks(5), 2);
+        assert_eq!(Bid(3).get_overtricks(1), 0);
+        assert_eq!([Bid(3), Bid::NIL, Bid(4)].into_iter().sum::<Bid>(), Bid(7));
+        assert!(Bid::NIL < Bid(2));
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Bid(4).to_string(), "4");
+        assert_eq!(Bid::NIL.to_string(), "nil");
+        assert_eq!(format!("{:>3}", Bid(4)), "  4");
+        assert_eq!(serde_json::to_string(&Bid(4)).unwrap(), "4");
+    }
+}
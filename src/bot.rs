@@ -0,0 +1,403 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::bid::Bid;
+use crate::card::{Card, Suit, DEFAULT_TRUMP_SUIT};
+use crate::game::{Game, GameState, Move, Round, NIL_CALL};
+
+/// Difficulty decides how well a bot calls and throws the cards
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Difficulty {
+    // random calls and random eligible cards
+    Easy,
+    // counts the high cards for the call and wins the round as cheaply as possible
+    #[default]
+    Normal,
+    // also counts the short suits that can be trumped and keeps the trumps for later
+    Hard,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+        }
+    }
+}
+
+/// Bot makes the calls and throws the cards for a computer player
+pub struct Bot {
+    difficulty: Difficulty,
+    trump_suit: Suit,
+    // the bot called nil and tries not to win any round
+    playing_nil: bool,
+}
+
+impl Bot {
+    pub fn new(difficulty: Difficulty) -> Self {
+        Bot {
+            difficulty,
+            trump_suit: DEFAULT_TRUMP_SUIT,
+            playing_nil: false,
+        }
+    }
+
+    /// with_trump_suit makes the bot play for a game with another trump suit
+    pub fn with_trump_suit(mut self, trump_suit: Suit) -> Self {
+        self.trump_suit = trump_suit;
+        self
+    }
+
+    /// with_nil_call makes the bot play for a nil call, losing every round it can
+    pub fn with_nil_call(mut self, playing_nil: bool) -> Self {
+        self.playing_nil = playing_nil;
+        self
+    }
+
+    pub fn get_difficulty(&self) -> Difficulty {
+        self.difficulty
+    }
+
+    /// choose_move returns the call or the throw for the current player of the game,
+    /// None if no one has to move
+    pub fn choose_move(&self, game: &Game) -> Option<Move> {
+        let player = game.get_current_player()?;
+        // the trump suit comes from the rules of the game the bot plays in
+        let bot = Bot::new(self.difficulty)
+            .with_trump_suit(game.get_rules().trump_suit)
+            .with_nil_call(player.get_call().is_nil());
+        match game.get_state() {
+            GameState::Calling
+                if game.can_redeal(player.get_name())
+                    && bot.wants_redeal(player.get_cards(), game.get_call_range()) =>
+            {
+                Some(Move::Redeal)
+            }
+            GameState::Calling
+                if game.get_allowed_bids().contains(&Bid::NIL)
+                    && bot.wants_nil(player.get_cards()) =>
+            {
+                Some(Move::Call(NIL_CALL))
+            }
+            GameState::Calling => {
+                let call = bot.choose_call(player.get_cards(), game.get_call_range());
+                Some(Move::Call(get_closest_call(call, &game.get_allowed_bids())))
+            }
+            GameState::Playing => {
+                let eligible = game.get_eligible_cards(player.get_name()).ok()?;
+                Some(Move::Throw(bot.choose_card(
+                    player.get_cards(),
+                    &eligible,
+                    game.get_current_round(),
+                    game.get_players().len(),
+                )))
+            }
+            _ => None,
+        }
+    }
+
+    /// choose_call returns the call for the hand within the allowed call range
+    pub fn choose_call(&self, cards: &[Card], call_range: (u8, u8)) -> u8 {
+        let (min_call, max_call) = call_range;
+        let call = match self.difficulty {
+            Difficulty::Easy => rand::thread_rng().gen_range(min_call..=min_call + 2),
+            Difficulty::Normal | Difficulty::Hard => self.count_tricks(cards),
+        };
+        call.clamp(min_call, max_call)
+    }
+
+    /// wants_redeal decides if a hand that allows a redeal is worth throwing in,
+    /// only the hands not expected to make the lowest call are
+    pub fn wants_redeal(&self, cards: &[Card], call_range: (u8, u8)) -> bool {
+        match self.difficulty {
+            Difficulty::Easy => false,
+            Difficulty::Normal | Difficulty::Hard => self.count_tricks(cards) < call_range.0,
+        }
+    }
+
+    /// wants_nil decides if the hand is weak enough to call nil: no card above a ten
+    /// and at most a single low trump, the hard bot also wants no long suit
+    pub fn wants_nil(&self, cards: &[Card]) -> bool {
+        if self.difficulty == Difficulty::Easy || cards.iter().any(|c| c.get_priority() > 10) {
+            return false;
+        }
+        let suit_len = |suit| cards.iter().filter(|c| c.get_suit() == suit).count();
+        let trumps = suit_len(self.trump_suit);
+        match self.difficulty {
+            Difficulty::Hard => {
+                trumps <= 1
+                    && [Suit::Spade, Suit::Hearts, Suit::Club, Suit::Diamonds]
+                        .iter()
+                        .all(|s| suit_len(*s) <= cards.len() / 2)
+            }
+            _ => trumps <= 1,
+        }
+    }
+
+    /// count_tricks estimates the number of rounds the hand can win
+    fn count_tricks(&self, cards: &[Card]) -> u8 {
+        let mut tricks = 0.0;
+        let trumps = cards
+            .iter()
+            .filter(|c| c.get_suit() == self.trump_suit)
+            .count();
+
+        for suit in [Suit::Spade, Suit::Hearts, Suit::Club, Suit::Diamonds] {
+            let suit_cards: Vec<&Card> = cards.iter().filter(|c| c.get_suit() == suit).collect();
+            let total = suit_cards.len();
+            for c in suit_cards.iter() {
+                // an honour only wins if there are enough cards to guard it
+                tricks += match (c.get_priority(), total) {
+                    (14, _) => 1.0,
+                    (13, t) if t >= 2 => 0.8,
+                    (12, t) if t >= 3 && suit == self.trump_suit => 0.7,
+                    _ => 0.0,
+                };
+            }
+
+            if suit == self.trump_suit {
+                // the long trumps win once the others run out of them
+                tricks += total.saturating_sub(3) as f32;
+            } else if self.difficulty == Difficulty::Hard && trumps > total {
+                // short suits can be trumped once they are played
+                tricks += match total {
+                    0 => 1.0,
+                    1 => 0.5,
+                    _ => 0.0,
+                };
+            }
+        }
+
+        tricks.round() as u8
+    }
+
+    /// choose_card returns the index of the card to throw, out of the eligible cards
+    pub fn choose_card(
+        &self,
+        cards: &[Card],
+        eligible: &[usize],
+        round: &[Round],
+        total_players: usize,
+    ) -> usize {
+        if self.difficulty == Difficulty::Easy {
+            return eligible[rand::thread_rng().gen_range(0..eligible.len())];
+        }
+
+        let winning_card = match self.get_winning_card(round) {
+            Some(_) if self.playing_nil => return self.choose_nil_card(cards, eligible, round),
+            Some(c) => c,
+            None if self.playing_nil => return self.lowest_card(cards, eligible),
+            None => return self.choose_lead_card(cards, eligible),
+        };
+
+        let mut winning_cards: Vec<usize> = eligible
+            .iter()
+            .copied()
+            .filter(|i| cards[*i].beats(winning_card, self.trump_suit))
+            .collect();
+        if winning_cards.is_empty() {
+            // can't win the round, so throw away the cheapest card
+            return self.lowest_card(cards, eligible);
+        }
+
+        let is_last = round.len() + 1 == total_players;
+        winning_cards.sort_by_key(|i| self.card_cost(&cards[*i]));
+        if self.difficulty == Difficulty::Hard && !is_last {
+            // the highest card of the suit has the best chance to hold till the end
+            let suit = cards[winning_cards[0]].get_suit();
+            if let Some(i) = winning_cards
+                .iter()
+                .filter(|i| cards[**i].get_suit() == suit)
+                .max_by_key(|i| cards[**i].get_priority())
+            {
+                return *i;
+            }
+        }
+        // the cheapest card that still wins the round
+        winning_cards[0]
+    }
+
+    /// choose_lead_card throws an ace when possible, otherwise the cheapest card
+    fn choose_lead_card(&self, cards: &[Card], eligible: &[usize]) -> usize {
+        eligible
+            .iter()
+            .copied()
+            .find(|i| cards[*i].get_priority() == 14 && cards[*i].get_suit() != self.trump_suit)
+            .unwrap_or_else(|| self.lowest_card(cards, eligible))
+    }
+
+    /// choose_nil_card throws the highest card that still loses the round, or the
+    /// cheapest card if every card wins it
+    fn choose_nil_card(&self, cards: &[Card], eligible: &[usize], round: &[Round]) -> usize {
+        let winning_card = self.get_winning_card(round);
+        eligible
+            .iter()
+            .copied()
+            .filter(|i| !winning_card.is_some_and(|w| cards[*i].beats(w, self.trump_suit)))
+            .max_by_key(|i| self.card_cost(&cards[*i]))
+            .unwrap_or_else(|| self.lowest_card(cards, eligible))
+    }
+
+    fn get_winning_card<'a>(&self, round: &'a [Round]) -> Option<&'a Card> {
+        let mut winning_card = round.first()?.get_card();
+        for r in round.iter().skip(1) {
+            if r.get_card().beats(winning_card, self.trump_suit) {
+                winning_card = r.get_card();
+            }
+        }
+        Some(winning_card)
+    }
+
+    fn lowest_card(&self, cards: &[Card], eligible: &[usize]) -> usize {
+        *eligible
+            .iter()
+            .min_by_key(|i| self.card_cost(&cards[**i]))
+            .expect("no eligible cards to throw")
+    }
+
+    /// card_cost ranks the cards by how much they're worth keeping, trumps are kept the longest
+    fn card_cost(&self, card: &Card) -> i32 {
+        if card.get_suit() == self.trump_suit {
+            card.get_priority() + 100
+        } else {
+            card.get_priority()
+        }
+    }
+}
+
+/// get_closest_call returns the allowed call nearest to the call, the lower one on a
+/// tie, e.g. when the hook takes the call away from the last caller
+fn get_closest_call(call: u8, allowed: &[Bid]) -> u8 {
+    allowed
+        .iter()
+        .filter(|b| !b.is_nil())
+        .min_by_key(|b| b.get_value().abs_diff(call))
+        .map(|b| b.get_value())
+        .unwrap_or(call)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::RuleSet;
+
+    fn new_cards(cards: &[(Suit, &str)]) -> Vec<Card> {
+        cards
+            .iter()
+            .map(|(suit, value)| Card::new(*suit, value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_choose_call() {
+        let bot = Bot::new(Difficulty::Normal);
+        let cards = new_cards(&[
+            (Suit::Spade, "A"),
+            (Suit::Spade, "K"),
+            (Suit::Spade, "Q"),
+            (Suit::Spade, "4"),
+            (Suit::Spade, "3"),
+            (Suit::Hearts, "A"),
+            (Suit::Hearts, "2"),
+            (Suit::Club, "K"),
+            (Suit::Club, "4"),
+            (Suit::Diamonds, "3"),
+        ]);
+        // 2.5 for the spade honours, 2 for the long spades, 1 ace, 0.8 for the guarded king
+        assert_eq!(bot.choose_call(&cards, (2, 8)), 6);
+        assert_eq!(bot.choose_call(&cards, (2, 5)), 5);
+
+        let weak_cards = new_cards(&[(Suit::Hearts, "2"), (Suit::Club, "3")]);
+        assert_eq!(bot.choose_call(&weak_cards, (2, 8)), 2);
+        // only the hands below the lowest call are thrown in
+        assert!(bot.wants_redeal(&weak_cards, (2, 8)));
+        assert!(!bot.wants_redeal(&cards, (2, 8)));
+        assert!(!Bot::new(Difficulty::Easy).wants_redeal(&weak_cards, (2, 8)));
+
+        let easy_bot = Bot::new(Difficulty::Easy);
+        let call = easy_bot.choose_call(&cards, (2, 8));
+        assert!((2..=4).contains(&call));
+
+        // a hand without a high card or a long trump goes nil
+        assert!(bot.wants_nil(&weak_cards));
+        assert!(!bot.wants_nil(&cards));
+        assert!(!easy_bot.wants_nil(&weak_cards));
+    }
+
+    #[test]
+    fn test_get_closest_call() {
+        let rules = RuleSet {
+            hook: true,
+            ..RuleSet::default()
+        };
+        let allowed: Vec<Bid> = Bid::get_bids(&rules, 4)
+            .into_iter()
+            .filter(|b| !rules.get_forbidden_bids(10, 4).contains(b))
+            .collect();
+        assert_eq!(get_closest_call(3, &allowed), 2);
+        assert_eq!(get_closest_call(5, &allowed), 5);
+    }
+
+    #[test]
+    fn test_choose_nil_card() {
+        let bot = Bot::new(Difficulty::Normal).with_nil_call(true);
+        let cards = new_cards(&[
+            (Suit::Hearts, "3"),
+            (Suit::Hearts, "9"),
+            (Suit::Hearts, "A"),
+            (Suit::Spade, "4"),
+        ]);
+        // the lowest card is led
+        assert_eq!(bot.choose_card(&cards, &[0, 1, 2, 3], &[], 4), 0);
+
+        // the highest card below the winning jack
+        let round = vec![Round::new("a", Card::new(Suit::Hearts, "J".to_string()))];
+        assert_eq!(bot.choose_card(&cards, &[0, 1, 2], &round, 4), 1);
+        // the ace has to win, so it's thrown when nothing else is left
+        assert_eq!(bot.choose_card(&cards, &[2], &round, 4), 2);
+    }
+
+    #[test]
+    fn test_choose_lead_card() {
+        let bot = Bot::new(Difficulty::Normal);
+        let cards = new_cards(&[(Suit::Spade, "2"), (Suit::Hearts, "A"), (Suit::Club, "5")]);
+        assert_eq!(bot.choose_card(&cards, &[0, 1, 2], &[], 4), 1);
+
+        let cards = new_cards(&[(Suit::Spade, "2"), (Suit::Hearts, "K"), (Suit::Club, "5")]);
+        assert_eq!(bot.choose_card(&cards, &[0, 1, 2], &[], 4), 2);
+    }
+
+    #[test]
+    fn test_choose_following_card() {
+        let cards = new_cards(&[
+            (Suit::Hearts, "3"),
+            (Suit::Hearts, "Q"),
+            (Suit::Hearts, "A"),
+            (Suit::Spade, "4"),
+        ]);
+        let round = vec![
+            Round::new("a", Card::new(Suit::Hearts, "10".to_string())),
+            Round::new("b", Card::new(Suit::Hearts, "J".to_string())),
+        ];
+
+        // the cheapest winning card
+        let bot = Bot::new(Difficulty::Normal);
+        assert_eq!(bot.choose_card(&cards, &[0, 1, 2], &round, 4), 1);
+        // the highest card as there is still a player left to throw
+        let hard_bot = Bot::new(Difficulty::Hard);
+        assert_eq!(hard_bot.choose_card(&cards, &[0, 1, 2], &round, 4), 2);
+        assert_eq!(hard_bot.choose_card(&cards, &[0, 1, 2], &round, 3), 1);
+
+        // the round is trumped, so the lowest card is thrown
+        let trumped_round = vec![
+            Round::new("a", Card::new(Suit::Hearts, "10".to_string())),
+            Round::new("b", Card::new(Suit::Spade, "2".to_string())),
+        ];
+        assert_eq!(bot.choose_card(&cards, &[0, 1, 2], &trumped_round, 4), 0);
+    }
+}
@@ -1,6 +1,9 @@
 use std::collections::HashMap;
 
-#[derive(Copy, Clone, PartialEq, Debug)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum Suit {
     Club,
     Spade,
@@ -8,9 +11,46 @@ pub enum Suit {
     Diamonds,
 }
 
-// Default trump suit is spade
-pub const TRUMP_SUIT: Suit = Suit::Spade;
+// Default trump suit is spade, the rules of a game may pick another one
+pub const DEFAULT_TRUMP_SUIT: Suit = Suit::Spade;
 
+/// CardStyle is the way the suit of a card is printed
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum CardStyle {
+    // emoji suits with the variation selector, e.g. [ 5 ♣️ ]
+    Emoji,
+    // plain unicode suits, e.g. [ 5 ♣ ]
+    Unicode,
+    // letters for the suits, e.g. [ 5C ]
+    Ascii,
+}
+
+impl Suit {
+    pub fn get_symbol(&self, style: CardStyle) -> &'static str {
+        match style {
+            CardStyle::Emoji => match self {
+                Suit::Club => "♣️",
+                Suit::Spade => "♠️",
+                Suit::Diamonds => "♦️",
+                Suit::Hearts => "♥️",
+            },
+            CardStyle::Unicode => match self {
+                Suit::Club => "♣",
+                Suit::Spade => "♠",
+                Suit::Diamonds => "♦",
+                Suit::Hearts => "♥",
+            },
+            CardStyle::Ascii => match self {
+                Suit::Club => "C",
+                Suit::Spade => "S",
+                Suit::Diamonds => "D",
+                Suit::Hearts => "H",
+            },
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub struct Card {
     value: String,
     suit: Suit,
@@ -52,14 +92,27 @@ impl Card {
     }
 
     pub fn get_print_str(&self) -> String {
-        let parsed_suit = match self.suit {
-            Suit::Club => "♣️",
-            Suit::Spade => "♠️",
-            Suit::Diamonds => "♦️",
-            Suit::Hearts => "♥️"
-        };
+        self.get_display_str(CardStyle::Emoji)
+    }
+
+    /// get_display_str returns the card printed in the given style
+    pub fn get_display_str(&self, style: CardStyle) -> String {
+        let parsed_suit = self.suit.get_symbol(style);
+        match style {
+            CardStyle::Ascii => format!("[ {}{} ]", self.value, parsed_suit),
+            _ => format!("[ {} {} ]", self.value, parsed_suit),
+        }
+    }
 
-        format!("[ {} {} ]", self.value, parsed_suit)
+    /// beats checks if the card wins over the card that is currently winning the round
+    /// - same suit: the higher card wins
+    /// - different suit: only a trump can win
+    pub fn beats(&self, winning_card: &Card, trump_suit: Suit) -> bool {
+        if self.suit == winning_card.suit {
+            self.priority > winning_card.priority
+        } else {
+            self.suit == trump_suit
+        }
     }
 
     pub fn print(&self) {
@@ -122,6 +175,35 @@ mod tests {
         assert_eq!(card.get_print_str(), "[ 5 ♣\u{fe0f} ]");
     }
 
+    #[test]
+    fn test_get_display_str() {
+        let card = Card::new(Suit::Hearts, "10".to_string());
+        assert_eq!(card.get_display_str(CardStyle::Emoji), "[ 10 ♥\u{fe0f} ]");
+        assert_eq!(card.get_display_str(CardStyle::Unicode), "[ 10 ♥ ]");
+        assert_eq!(card.get_display_str(CardStyle::Ascii), "[ 10H ]");
+
+        let card = Card::new(Suit::Spade, "A".to_string());
+        assert_eq!(card.get_display_str(CardStyle::Ascii), "[ AS ]");
+    }
+
+    #[test]
+    fn test_beats() {
+        let winning_card = Card::new(Suit::Hearts, "10".to_string());
+
+        assert!(Card::new(Suit::Hearts, "J".to_string()).beats(&winning_card, Suit::Spade));
+        assert!(!Card::new(Suit::Hearts, "9".to_string()).beats(&winning_card, Suit::Spade));
+        assert!(!Card::new(Suit::Club, "A".to_string()).beats(&winning_card, Suit::Spade));
+        assert!(Card::new(Suit::Spade, "2".to_string()).beats(&winning_card, Suit::Spade));
+
+        let winning_trump = Card::new(Suit::Spade, "5".to_string());
+        assert!(!Card::new(Suit::Hearts, "A".to_string()).beats(&winning_trump, Suit::Spade));
+        assert!(Card::new(Suit::Spade, "6".to_string()).beats(&winning_trump, Suit::Spade));
+
+        // with hearts as the trump the spades lose their power
+        assert!(Card::new(Suit::Hearts, "2".to_string()).beats(&winning_trump, Suit::Hearts));
+        assert!(!Card::new(Suit::Spade, "2".to_string()).beats(&winning_card, Suit::Hearts));
+    }
+
     #[test]
     fn test_get_value() {
         let card = Card::new(Suit::Diamonds, "9".to_string());
@@ -140,4 +222,3 @@ mod tests {
         assert_eq!(card.get_priority(), 11);
     }
 }
-
@@ -1,28 +1,62 @@
-use std::{io, panic::{set_hook, take_hook}};
+use std::{
+    io,
+    panic::{set_hook, take_hook},
+};
 
+pub mod config;
+pub mod profile;
+mod remote;
+mod replay;
 mod state;
+pub mod theme;
 mod tui;
 mod ui;
 
+use config::Config;
+use remote::RemoteSession;
 use state::State;
 
+#[allow(clippy::upper_case_acronyms)]
 pub struct CLI {}
 
-use color_eyre::{
-    eyre::{bail, WrapErr},
-    Result,
-};
-
 use crate::game::Game;
 
 impl CLI {
     pub fn new_cli() -> io::Result<()> {
+        // initialize a new game
+        let game = Game::default();
+        let mut state = State::new(game, Config::default());
+        state.load_config();
+        state.load_profiles();
+        state.load_replays();
+        Self::run(state)
+    }
+
+    /// join_cli joins a game hosted with `callbreak serve`, the name is asked
+    /// in the lobby if it's not given
+    pub fn join_cli(addr: &str, name: Option<String>) -> io::Result<()> {
+        // connect before taking over the terminal, so a wrong address fails right away
+        let session = RemoteSession::connect(addr)?;
+        let mut state = State::new(Game::default(), Config::default());
+        state.load_config();
+        state.start_remote(session, name);
+        Self::run(state)
+    }
+
+    /// watch_cli follows a game hosted with `callbreak serve` as a spectator
+    pub fn watch_cli(addr: &str, name: Option<String>) -> io::Result<()> {
+        let mut session = RemoteSession::connect(addr)?;
+        session.watching = true;
+        let mut state = State::new(Game::default(), Config::default());
+        state.load_config();
+        state.start_remote(session, name);
+        Self::run(state)
+    }
+
+    fn run(mut new_app_state: State) -> io::Result<()> {
         Self::init_panic_hook();
         color_eyre::install().expect("Error Unwrapping color eyre");
         let mut terminal = tui::init()?;
-        // initialize a new game
-        let game = Game::new_game();
-        let mut new_app_state = State::new(game);
         let app_result = new_app_state.run_app(&mut terminal);
         if let Err(err) = app_result {
             println!("{err:?}");
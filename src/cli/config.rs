@@ -0,0 +1,290 @@
+use std::path::{Path, PathBuf};
+use std::{fs, io, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use super::theme::Theme;
+use crate::bot::Difficulty;
+use crate::game::DEFAULT_TOTAL_DEALS;
+use crate::rules::{Reshuffle, RulePreset, RuleSet, DEFAULT_MAX_REDEALS, MAX_REDEALS};
+use crate::score::{NilBid, DEFAULT_NIL_POINTS};
+
+const CONFIG_DIR: &str = "callbreak";
+const CONFIG_FILE: &str = "config.toml";
+
+pub const MAX_TOTAL_DEALS: u8 = 20;
+// a minimum total call above the rounds of a 4 player deal would fail every deal
+pub const MAX_MIN_TOTAL_CALL: u8 = 13;
+// the calls supported by the game
+pub use crate::game::{MAX_CALL, MIN_CALL};
+
+/// AnimationSpeed is the pause before a bot makes its move
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnimationSpeed {
+    Off,
+    Fast,
+    #[default]
+    Normal,
+    Slow,
+}
+
+impl AnimationSpeed {
+    pub const ALL: [AnimationSpeed; 4] = [
+        AnimationSpeed::Off,
+        AnimationSpeed::Fast,
+        AnimationSpeed::Normal,
+        AnimationSpeed::Slow,
+    ];
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            AnimationSpeed::Off => "Off",
+            AnimationSpeed::Fast => "Fast",
+            AnimationSpeed::Normal => "Normal",
+            AnimationSpeed::Slow => "Slow",
+        }
+    }
+
+    pub fn get_delay(&self) -> Duration {
+        match self {
+            AnimationSpeed::Off => Duration::ZERO,
+            AnimationSpeed::Fast => Duration::from_millis(300),
+            AnimationSpeed::Normal => Duration::from_millis(700),
+            AnimationSpeed::Slow => Duration::from_millis(1200),
+        }
+    }
+}
+
+/// TimeLimit is the time a player has for a call or a throw before it's made for them
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeLimit {
+    #[default]
+    Off,
+    Short,
+    Normal,
+    Long,
+}
+
+impl TimeLimit {
+    pub const ALL: [TimeLimit; 4] = [
+        TimeLimit::Off,
+        TimeLimit::Short,
+        TimeLimit::Normal,
+        TimeLimit::Long,
+    ];
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            TimeLimit::Off => "Off",
+            TimeLimit::Short => "15 seconds",
+            TimeLimit::Normal => "30 seconds",
+            TimeLimit::Long => "60 seconds",
+        }
+    }
+
+    pub fn get_limit(&self) -> Option<Duration> {
+        match self {
+            TimeLimit::Off => None,
+            TimeLimit::Short => Some(Duration::from_secs(15)),
+            TimeLimit::Normal => Some(Duration::from_secs(30)),
+            TimeLimit::Long => Some(Duration::from_secs(60)),
+        }
+    }
+}
+
+/// Config holds the user preferences, persisted as TOML in the config directory
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub theme: Theme,
+    pub rules: RulePreset,
+    // the weak hands whose player may ask for a redeal, and how often in a deal
+    pub redeal: Reshuffle,
+    pub max_redeals: u8,
+    // four players play in two teams of the opposite seats
+    pub partnership: bool,
+    // a call to take no tricks, worth the bonus if it's made and the penalty otherwise
+    pub nil_bid: bool,
+    pub nil_bonus: u8,
+    pub nil_penalty: u8,
+    // the last caller can't make the calls add up to the rounds, and the calls have to
+    // add up to at least the minimum total, 0 for none
+    pub hook: bool,
+    pub min_total_call: u8,
+    pub total_deals: u8,
+    pub min_call: u8,
+    pub max_call: u8,
+    pub bot_difficulty: Difficulty,
+    pub animation_speed: AnimationSpeed,
+    pub call_time_limit: TimeLimit,
+    pub throw_time_limit: TimeLimit,
+    // names filled in the new game wizard, in the order of the players
+    pub player_names: Vec<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            theme: Theme::default(),
+            rules: RulePreset::default(),
+            redeal: Reshuffle::default(),
+            max_redeals: DEFAULT_MAX_REDEALS,
+            partnership: false,
+            nil_bid: false,
+            nil_bonus: DEFAULT_NIL_POINTS,
+            nil_penalty: DEFAULT_NIL_POINTS,
+            hook: false,
+            min_total_call: 0,
+            total_deals: DEFAULT_TOTAL_DEALS,
+            min_call: MIN_CALL,
+            max_call: MAX_CALL,
+            bot_difficulty: Difficulty::default(),
+            animation_speed: AnimationSpeed::default(),
+            call_time_limit: TimeLimit::default(),
+            throw_time_limit: TimeLimit::default(),
+            player_names: vec![],
+        }
+    }
+}
+
+impl Config {
+    /// get_rules returns the rules of the preset with the calls and the deals of the config
+    pub fn get_rules(&self) -> RuleSet {
+        RuleSet {
+            min_call: self.min_call,
+            max_call: self.max_call,
+            total_deals: self.total_deals,
+            redeal: self.redeal,
+            max_redeals: self.max_redeals,
+            partnership: self.partnership,
+            nil_bid: self.nil_bid.then_some(NilBid {
+                bonus: self.nil_bonus,
+                penalty: self.nil_penalty,
+            }),
+            hook: self.hook,
+            min_total_call: self.min_total_call,
+            ..self.rules.get_rules()
+        }
+    }
+
+    /// get_path returns the path of the config file, e.g. ~/.config/callbreak/config.toml
+    pub fn get_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join(CONFIG_DIR).join(CONFIG_FILE))
+    }
+
+    /// load_from reads the config file, the default config is used if there is no file yet,
+    /// the missing fields take their default value and a field that can't be parsed is an error
+    pub fn load_from(path: &Path) -> io::Result<Self> {
+        if !path.exists() {
+            return Ok(Config::default());
+        }
+        let content = fs::read_to_string(path)?;
+        toml::from_str::<Config>(&content)
+            .map(Config::validate)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// validate brings the values edited by hand back into the supported range
+    pub fn validate(mut self) -> Self {
+        self.total_deals = self.total_deals.clamp(1, MAX_TOTAL_DEALS);
+        self.min_call = self.min_call.clamp(MIN_CALL, MAX_CALL);
+        self.max_call = self.max_call.clamp(self.min_call, MAX_CALL);
+        self.min_total_call = self.min_total_call.min(MAX_MIN_TOTAL_CALL);
+        self.max_redeals = self.max_redeals.min(MAX_REDEALS);
+        self.player_names = self
+            .player_names
+            .iter()
+            .map(|n| n.trim().to_string())
+            .filter(|n| !n.is_empty())
+            .collect();
+        self
+    }
+
+    /// save_to writes the config to the file, creating the directory if needed
+    pub fn save_to(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let content = toml::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(path, content)
+    }
+
+    /// get_player_name returns the default name of the player at the given position
+    pub fn get_player_name(&self, idx: usize) -> Option<&str> {
+        self.player_names.get(idx).map(|n| n.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_round_trip() {
+        let config = Config {
+            theme: Theme::FourColor,
+            total_deals: 3,
+            player_names: vec!["Ayan".to_string()],
+            ..Config::default()
+        };
+        let content = toml::to_string_pretty(&config).unwrap();
+        assert!(content.contains("theme = \"four_color\""));
+        assert!(content.contains("total_deals = 3"));
+        assert_eq!(toml::from_str::<Config>(&content).unwrap(), config);
+    }
+
+    #[test]
+    fn test_config_missing_fields() {
+        let config: Config = toml::from_str("theme = \"ascii\"").unwrap();
+        assert_eq!(config.theme, Theme::Ascii);
+        assert_eq!(config.total_deals, DEFAULT_TOTAL_DEALS);
+        assert_eq!(config.bot_difficulty, Difficulty::Normal);
+        assert_eq!(config.get_rules().nil_bid, None);
+
+        let config: Config = toml::from_str("nil_bid = true\nnil_bonus = 8").unwrap();
+        assert_eq!(
+            config.get_rules().nil_bid,
+            Some(NilBid {
+                bonus: 8,
+                penalty: DEFAULT_NIL_POINTS
+            })
+        );
+    }
+
+    #[test]
+    fn test_config_load() {
+        let path = std::env::temp_dir().join(format!(
+            "callbreak-config-{}/config.toml",
+            std::process::id()
+        ));
+        assert_eq!(Config::load_from(&path).unwrap(), Config::default());
+
+        let config = Config {
+            total_deals: 3,
+            ..Config::default()
+        };
+        config.save_to(&path).unwrap();
+        assert_eq!(Config::load_from(&path).unwrap(), config);
+
+        // a value that can't be parsed fails the load instead of resetting the config
+        fs::write(&path, "total_deals = 3\ntheme = \"neon\"").unwrap();
+        let err = Config::load_from(&path).unwrap_err();
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_config_validate() {
+        let config: Config = toml::from_str(
+            "total_deals = 0\nmin_call = 7\nmax_call = 3\nmin_total_call = 40\nplayer_names = [\" a \", \"\"]",
+        )
+        .unwrap();
+        let config = config.validate();
+        assert_eq!(config.total_deals, 1);
+        assert_eq!((config.min_call, config.max_call), (7, 7));
+        assert_eq!(config.min_total_call, MAX_MIN_TOTAL_CALL);
+        assert_eq!(config.player_names, vec!["a".to_string()]);
+    }
+}
@@ -0,0 +1,186 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::theme::Theme;
+use crate::pin::{Lockout, PinError, PinHash};
+use crate::rating::Rating;
+use crate::stats::{PlayerStats, Stats};
+
+const PROFILES_DIR: &str = "callbreak";
+const PROFILES_FILE: &str = "profiles.json";
+
+// the glyphs a profile can be shown with, the new profiles take the first free one
+pub const AVATARS: [char; 10] = ['♠', '♥', '♦', '♣', '★', '☀', '☂', '♞', '♛', '☘'];
+
+/// Profile is a regular player of this device, the stats are kept under the name of the profile
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Profile {
+    name: String,
+    pub avatar: char,
+    // the pin is only kept as a salted hash
+    pin: PinHash,
+    // the wrong pins are kept with the profile, so a lockout outlasts the app
+    #[serde(default)]
+    lockout: Lockout,
+    // the theme used while the cards of the player are shown, None for the one of the settings
+    pub theme: Option<Theme>,
+}
+
+impl Profile {
+    pub fn new(name: &str, pin: &str, avatar: char) -> Self {
+        Profile {
+            name: name.to_string(),
+            avatar,
+            pin: PinHash::new(pin),
+            lockout: Lockout::default(),
+            theme: None,
+        }
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// get_label returns the name of the player with their avatar, e.g. "♠ Ayan"
+    pub fn get_label(&self) -> String {
+        format!("{} {}", self.avatar, self.name)
+    }
+
+    /// verify_pin checks the entered pin against the hash of the profile's pin, the profile
+    /// is locked out for a while after too many wrong pins
+    pub fn verify_pin(&mut self, pin_entered: &str) -> Result<(), PinError> {
+        self.lockout.verify(&self.pin, pin_entered)
+    }
+
+    /// get_stats returns the lifetime statistics recorded for the profile
+    pub fn get_stats<'a>(&self, stats: &'a Stats) -> Option<&'a PlayerStats> {
+        stats.get_player(&self.name)
+    }
+
+    pub fn get_rating(&self, stats: &Stats) -> Rating {
+        stats.get_ratings().get_rating(&self.name)
+    }
+}
+
+/// Profiles holds the saved profiles of the device, persisted as JSON in the data directory
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Profiles {
+    profiles: Vec<Profile>,
+}
+
+impl Profiles {
+    /// get_path returns the path of the profiles file, e.g. ~/.local/share/callbreak/profiles.json
+    pub fn get_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join(PROFILES_DIR).join(PROFILES_FILE))
+    }
+
+    /// load_from reads the profiles file, no profiles are used if there is no file yet
+    pub fn load_from(path: &Path) -> io::Result<Self> {
+        if !path.exists() {
+            return Ok(Profiles::default());
+        }
+        let content = fs::read_to_string(path)?;
+        serde_json::from_str(&content).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// save_to writes the profiles to the file, creating the directory if needed
+    pub fn save_to(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let content = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(path, content)
+    }
+
+    pub fn get_profiles(&self) -> &[Profile] {
+        &self.profiles
+    }
+
+    pub fn get_profile(&self, name: &str) -> Option<&Profile> {
+        self.profiles.iter().find(|p| p.name == name)
+    }
+
+    pub fn get_profile_mut(&mut self, name: &str) -> Option<&mut Profile> {
+        self.profiles.iter_mut().find(|p| p.name == name)
+    }
+
+    /// add saves a new profile with the first avatar not taken by another profile,
+    /// false if there is a profile with the name already
+    pub fn add(&mut self, name: &str, pin: &str) -> bool {
+        if self.get_profile(name).is_some() {
+            return false;
+        }
+        let avatar = AVATARS
+            .iter()
+            .find(|a| self.profiles.iter().all(|p| p.avatar != **a))
+            .unwrap_or(&AVATARS[0]);
+        self.profiles.push(Profile::new(name, pin, *avatar));
+        true
+    }
+
+    /// remove deletes the profile, the statistics of the player are kept
+    pub fn remove(&mut self, name: &str) -> Option<Profile> {
+        let idx = self.profiles.iter().position(|p| p.name == name)?;
+        Some(self.profiles.remove(idx))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profile_pin() {
+        let mut profile = Profile::new("Ayan", "1234", '♠');
+        assert_eq!(profile.verify_pin("1234"), Ok(()));
+        assert!(profile.verify_pin("4321").is_err());
+        assert_eq!(profile.get_label(), "♠ Ayan");
+
+        // the same pin is hashed differently for every profile
+        let other = Profile::new("Bo", "1234", '♥');
+        assert_ne!(profile.pin, other.pin);
+        let content = serde_json::to_string(&profile).unwrap();
+        assert!(!content.contains("1234"));
+        // the wrong pin is saved with the profile
+        assert!(content.contains("\"failed_attempts\":1"));
+    }
+
+    #[test]
+    fn test_profiles() {
+        let mut profiles = Profiles::default();
+        assert!(profiles.add("Ayan", "1234"));
+        assert!(profiles.add("Bo", "1111"));
+        assert!(!profiles.add("Ayan", "4321"));
+        assert_eq!(
+            profiles.get_profile_mut("Ayan").unwrap().verify_pin("1234"),
+            Ok(())
+        );
+        assert_eq!(profiles.get_profile("Bo").unwrap().avatar, AVATARS[1]);
+
+        // a removed profile frees its avatar
+        profiles.remove("Ayan").unwrap();
+        assert!(profiles.remove("Ayan").is_none());
+        profiles.add("Cy", "2222");
+        assert_eq!(profiles.get_profile("Cy").unwrap().avatar, AVATARS[0]);
+    }
+
+    #[test]
+    fn test_profiles_round_trip() {
+        let path = std::env::temp_dir().join(format!(
+            "callbreak-profiles-{}/profiles.json",
+            std::process::id()
+        ));
+        assert_eq!(Profiles::load_from(&path).unwrap(), Profiles::default());
+
+        let mut profiles = Profiles::default();
+        profiles.add("Ayan", "1234");
+        profiles.get_profile_mut("Ayan").unwrap().theme = Some(Theme::FourColor);
+        profiles.save_to(&path).unwrap();
+        let loaded = Profiles::load_from(&path).unwrap();
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+        assert_eq!(loaded, profiles);
+    }
+}
@@ -0,0 +1,448 @@
+use std::collections::HashMap;
+use std::io;
+use std::time::{Duration, Instant};
+
+use crate::card::Card;
+use crate::game::GameState;
+use crate::net::client::{ClientEvent, Connection};
+use crate::net::protocol::{ClientMessage, SeatView, ServerMessage, PROTOCOL_VERSION};
+use crate::shuffle::{self, ShuffleProof};
+use crate::view::GameView;
+
+// the time between two attempts to connect again after the connection is lost
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(2);
+// the number of chat lines kept for the lobby
+const MAX_CHAT_LINES: usize = 100;
+
+/// RemoteUpdate tells the front end what has changed after polling the server
+#[derive(Debug, PartialEq)]
+pub enum RemoteUpdate {
+    // the server has given a seat to the player
+    Joined,
+    Lobby,
+    State,
+    Error(String),
+    Disconnected,
+}
+
+/// RemoteSession is a game hosted by `callbreak serve` and joined over the network,
+/// the server runs the game and the session only keeps its latest view
+pub struct RemoteSession {
+    pub addr: String,
+    pub name: String,
+    connection: Option<Connection>,
+    // given by the server to take the same seat back after a disconnect
+    token: Option<String>,
+    last_attempt: Instant,
+    pub seat: Option<usize>,
+    // watching the game without a seat, Some with the delay of the states
+    pub spectator_delay: Option<Duration>,
+    // watches the game instead of taking a seat, also when reconnecting
+    pub watching: bool,
+    pub seats: Vec<SeatView>,
+    pub total_seats: usize,
+    pub ready: bool,
+    // sent with ready and mixed into the shuffles, to check the seeds revealed after every deal
+    entropy: String,
+    // the hashes of the seeds of the server for every deal
+    shuffle_commitment: Vec<String>,
+    // the deals are fixed by a duplicate match, so no entropy is sent
+    fixed_deals: bool,
+    // the seat that got the first card and the hand dealt to the player in every deal
+    dealt_hands: HashMap<u8, (usize, Vec<Card>)>,
+    pub view: Option<GameView>,
+    // the time left for the turn in the view counts down from here
+    view_received: Instant,
+    pub chat: Vec<String>,
+}
+
+impl RemoteSession {
+    /// connect opens the connection to the server, the seat is taken later with join
+    pub fn connect(addr: &str) -> io::Result<Self> {
+        let connection = Connection::connect(addr)?;
+        Ok(RemoteSession {
+            addr: addr.to_string(),
+            name: String::new(),
+            connection: Some(connection),
+            token: None,
+            last_attempt: Instant::now(),
+            seat: None,
+            spectator_delay: None,
+            watching: false,
+            seats: vec![],
+            total_seats: 0,
+            ready: false,
+            entropy: String::new(),
+            shuffle_commitment: vec![],
+            fixed_deals: false,
+            dealt_hands: HashMap::new(),
+            view: None,
+            view_received: Instant::now(),
+            chat: vec![],
+        })
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.connection.is_some()
+    }
+
+    pub fn is_spectating(&self) -> bool {
+        self.spectator_delay.is_some()
+    }
+
+    /// is_finished checks if the hosted game is over, no reconnect is tried after that
+    pub fn is_finished(&self) -> bool {
+        self.view
+            .as_ref()
+            .is_some_and(|v| v.state == GameState::Finished)
+    }
+
+    /// get_view gives the latest view with the time left for the turn as of now
+    pub fn get_view(&self) -> Option<GameView> {
+        let mut view = self.view.clone()?;
+        let elapsed = self.view_received.elapsed().as_millis() as u64;
+        view.time_left_ms = view.time_left_ms.map(|t| t.saturating_sub(elapsed));
+        Some(view)
+    }
+
+    /// join asks the server for a seat under the given name
+    pub fn join(&mut self, name: &str) {
+        self.name = name.to_string();
+        self.send(ClientMessage::Join {
+            version: PROTOCOL_VERSION,
+            name: self.name.clone(),
+            token: self.token.clone(),
+        });
+    }
+
+    /// watch asks the server to follow the game without a seat
+    pub fn watch(&mut self, name: &str) {
+        self.name = name.to_string();
+        self.watching = true;
+        self.send(ClientMessage::Watch {
+            version: PROTOCOL_VERSION,
+            name: self.name.clone(),
+        });
+    }
+
+    pub fn toggle_ready(&mut self) {
+        if self.is_spectating() {
+            return;
+        }
+        self.ready = !self.ready;
+        self.entropy = if self.fixed_deals {
+            String::new()
+        } else {
+            hex::encode(shuffle::new_seed())
+        };
+        self.send(ClientMessage::Ready {
+            ready: self.ready,
+            entropy: Some(self.entropy.clone()).filter(|e| !e.is_empty()),
+        });
+    }
+
+    pub fn send_chat(&mut self, text: &str) {
+        let text = text.trim();
+        if !text.is_empty() {
+            self.send(ClientMessage::Chat {
+                text: text.to_string(),
+            });
+        }
+    }
+
+    pub fn place_call(&mut self, call: u8) {
+        self.send(ClientMessage::Bid { call });
+    }
+
+    pub fn throw(&mut self, card: Card) {
+        self.send(ClientMessage::Play { card });
+    }
+
+    pub fn redeal(&mut self) {
+        self.send(ClientMessage::Redeal);
+    }
+
+    /// send writes the message to the server, a failed write drops the connection
+    /// and the message is lost
+    fn send(&mut self, message: ClientMessage) {
+        if let Some(connection) = self.connection.as_mut() {
+            if connection.send(&message).is_err() {
+                self.drop_connection();
+            }
+        }
+    }
+
+    fn drop_connection(&mut self) {
+        self.connection = None;
+        self.last_attempt = Instant::now();
+        if self.is_finished() {
+            self.push_chat(String::from("* The server has closed the game"));
+        } else {
+            self.push_chat(String::from("* Connection lost, trying to reconnect..."));
+        }
+    }
+
+    fn push_chat(&mut self, line: String) {
+        self.chat.push(line);
+        if self.chat.len() > MAX_CHAT_LINES {
+            self.chat.remove(0);
+        }
+    }
+
+    /// poll applies every message received since the last poll and tries to connect
+    /// again if the connection was lost, it never waits on the network
+    pub fn poll(&mut self) -> Vec<RemoteUpdate> {
+        let mut updates = vec![];
+        if self.connection.is_none() {
+            self.reconnect();
+        }
+
+        while let Some(event) = self.connection.as_ref().and_then(|c| c.try_recv()) {
+            match event {
+                ClientEvent::Message(message) => {
+                    if let Some(update) = self.apply(message) {
+                        updates.push(update);
+                    }
+                }
+                ClientEvent::Disconnected => {
+                    self.drop_connection();
+                    updates.push(RemoteUpdate::Disconnected);
+                }
+            }
+        }
+        updates
+    }
+
+    /// reconnect takes the seat back with the token of the previous connection
+    fn reconnect(&mut self) {
+        // a player without a seat has nothing to take back, a spectator just watches again
+        if self.is_finished()
+            || (self.token.is_none() && !self.is_spectating())
+            || self.last_attempt.elapsed() < RECONNECT_INTERVAL
+        {
+            return;
+        }
+        self.last_attempt = Instant::now();
+        if let Ok(connection) = Connection::connect(&self.addr) {
+            self.connection = Some(connection);
+            let name = self.name.clone();
+            if self.watching {
+                self.watch(&name);
+            } else {
+                self.join(&name);
+            }
+        }
+    }
+
+    /// record_dealt_hand keeps the hand of the player as it was dealt, from the states
+    /// of the deal before anyone has called, so a redealt hand replaces the first one
+    fn record_dealt_hand(&mut self, view: &GameView) {
+        let (deal, _) = view.deal;
+        if view.state != GameState::Calling
+            || view.seat.is_none()
+            || view.players.iter().any(|p| p.call.is_some())
+        {
+            return;
+        }
+        let first_seat = view
+            .players
+            .iter()
+            .position(|p| Some(&p.name) == view.current_player.as_ref());
+        if let Some(first_seat) = first_seat {
+            self.dealt_hands
+                .insert(deal, (first_seat, view.hand.clone()));
+        }
+    }
+
+    /// verify_shuffle checks the revealed seeds of a deal against the commitment and
+    /// the hand the player got, a failed check is shown as an error
+    fn verify_shuffle(&mut self, proof: &ShuffleProof) -> Option<RemoteUpdate> {
+        let seat = self.seat?;
+        let (first_seat, hand) = self.dealt_hands.get(&proof.deal)?;
+        let commitment = self
+            .shuffle_commitment
+            .get((proof.deal as usize).checked_sub(1)?)?;
+        // the deck is dealt again under the rules of the game
+        let rules = &self.view.as_ref()?.rules;
+        match proof.verify(commitment, seat, &self.entropy, *first_seat, hand, rules) {
+            Ok(()) => {
+                self.push_chat(format!("* The shuffle of deal {} is verified", proof.deal));
+                None
+            }
+            Err(e) => Some(RemoteUpdate::Error(format!(
+                "The shuffle of deal {} failed the check: {}",
+                proof.deal, e
+            ))),
+        }
+    }
+
+    fn apply(&mut self, message: ServerMessage) -> Option<RemoteUpdate> {
+        match message {
+            ServerMessage::Welcome { seat, token, .. } => {
+                if self.token.is_some() {
+                    self.push_chat(String::from("* Reconnected"));
+                }
+                self.seat = Some(seat);
+                self.token = Some(token);
+                Some(RemoteUpdate::Joined)
+            }
+            ServerMessage::Watching { delay_ms, .. } => {
+                if self.is_spectating() {
+                    self.push_chat(String::from("* Reconnected"));
+                }
+                self.spectator_delay = Some(Duration::from_millis(delay_ms));
+                Some(RemoteUpdate::Joined)
+            }
+            ServerMessage::Lobby { seats, total_seats } => {
+                self.seats = seats;
+                self.total_seats = total_seats;
+                Some(RemoteUpdate::Lobby)
+            }
+            ServerMessage::Chat { from, text } => {
+                self.push_chat(format!("{}: {}", from, text));
+                None
+            }
+            ServerMessage::Notice { text } => {
+                self.push_chat(format!("* {}", text));
+                None
+            }
+            ServerMessage::State { view } => {
+                self.record_dealt_hand(&view);
+                self.view = Some(*view);
+                self.view_received = Instant::now();
+                Some(RemoteUpdate::State)
+            }
+            ServerMessage::ShuffleCommitment { hashes, fixed } => {
+                self.shuffle_commitment = hashes;
+                self.fixed_deals = fixed;
+                None
+            }
+            ServerMessage::ShuffleReveal { proof } => self.verify_shuffle(&proof),
+            ServerMessage::Error { message } => Some(RemoteUpdate::Error(message)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+    use crate::net::server::{Server, ServerOptions};
+
+    /// wait_for polls the session until the update arrives
+    fn wait_for(session: &mut RemoteSession, expected: RemoteUpdate) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut seen = vec![];
+        while Instant::now() < deadline {
+            let updates = session.poll();
+            if updates.contains(&expected) {
+                return;
+            }
+            seen.extend(updates);
+            thread::sleep(Duration::from_millis(10));
+        }
+        panic!("{:?} not received, got {:?}", expected, seen);
+    }
+
+    #[test]
+    fn test_remote_session() {
+        let options = ServerOptions {
+            total_players: 2,
+            total_deals: 1,
+            ..ServerOptions::default()
+        };
+        let server = Server::bind("127.0.0.1:0", options).unwrap();
+        let addr = server.local_addr().unwrap().to_string();
+        thread::spawn(move || server.run());
+
+        let mut a = RemoteSession::connect(&addr).unwrap();
+        a.join("a");
+        wait_for(&mut a, RemoteUpdate::Joined);
+        assert_eq!(a.seat, Some(0));
+
+        // the name is already taken
+        let mut b = RemoteSession::connect(&addr).unwrap();
+        b.join("a");
+        wait_for(
+            &mut b,
+            RemoteUpdate::Error(String::from("Player already added in the game")),
+        );
+        assert_eq!(b.seat, None);
+        b.join("b");
+        wait_for(&mut b, RemoteUpdate::Joined);
+        assert_eq!(b.seat, Some(1));
+
+        b.send_chat(" hello ");
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !a.chat.contains(&String::from("b: hello")) {
+            assert!(Instant::now() < deadline, "chat not received: {:?}", a.chat);
+            a.poll();
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        a.toggle_ready();
+        b.toggle_ready();
+        wait_for(&mut a, RemoteUpdate::State);
+        let view = a.view.as_ref().unwrap();
+        assert_eq!(view.state, GameState::Calling);
+        assert_eq!(view.seat.as_deref(), Some("a"));
+        assert_eq!(view.hand.len(), 26);
+    }
+
+    /// play_turn makes the lowest call or throws the first eligible card on the turn of the player
+    fn play_turn(session: &mut RemoteSession) {
+        let view = match session.view.clone() {
+            Some(view) if view.is_my_turn() => view,
+            _ => return,
+        };
+        match view.state {
+            GameState::Calling => session.place_call(view.call_range.0),
+            GameState::Playing => session.throw(view.hand[view.eligible_cards[0]].clone()),
+            _ => {}
+        }
+    }
+
+    #[test]
+    fn test_verified_shuffle() {
+        let options = ServerOptions {
+            total_players: 2,
+            total_deals: 1,
+            ..ServerOptions::default()
+        };
+        let server = Server::bind("127.0.0.1:0", options).unwrap();
+        let addr = server.local_addr().unwrap().to_string();
+        thread::spawn(move || server.run());
+
+        let mut sessions = vec![];
+        for name in ["a", "b"] {
+            let mut session = RemoteSession::connect(&addr).unwrap();
+            session.join(name);
+            wait_for(&mut session, RemoteUpdate::Joined);
+            sessions.push(session);
+        }
+        for session in sessions.iter_mut() {
+            session.toggle_ready();
+        }
+
+        // both players check their hands once the seeds are revealed after the deal
+        let verified = String::from("* The shuffle of deal 1 is verified");
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while !sessions.iter().all(|s| s.chat.contains(&verified)) {
+            assert!(Instant::now() < deadline, "the shuffle was not verified");
+            for session in sessions.iter_mut() {
+                let updates = session.poll();
+                assert!(
+                    !updates.iter().any(|u| matches!(u, RemoteUpdate::Error(_))),
+                    "{:?}",
+                    updates
+                );
+                // a move is only made once for every new state
+                if updates.contains(&RemoteUpdate::State) {
+                    play_turn(session);
+                }
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::log::{GameLog, LogEntry, LogEvent};
+
+const REPLAYS_DIR: &str = "callbreak";
+const REPLAYS_SUBDIR: &str = "replays";
+
+/// Replay steps through the saved log of a finished game, one event at a time
+#[derive(Clone, Debug)]
+pub struct Replay {
+    log: GameLog,
+    // the events shown so far, the start of the game is always shown
+    step: usize,
+}
+
+impl Replay {
+    pub fn new(log: GameLog) -> Self {
+        let step = log.get_entries().len().min(1);
+        Replay { log, step }
+    }
+
+    /// get_dir returns the directory of the replays, e.g. ~/.local/share/callbreak/replays
+    pub fn get_dir() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join(REPLAYS_DIR).join(REPLAYS_SUBDIR))
+    }
+
+    /// load_all reads the replays saved in the directory, the latest game first,
+    /// there are no replays if there is no directory yet
+    pub fn load_all(dir: &Path) -> io::Result<Vec<Replay>> {
+        if !dir.exists() {
+            return Ok(vec![]);
+        }
+        let mut replays = vec![];
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            // a file that isn't a game log is left out
+            if path.extension().is_some_and(|e| e == "json") {
+                if let Ok(log) = GameLog::load(&path) {
+                    replays.push(Replay::new(log));
+                }
+            }
+        }
+        replays.sort_by_key(|r| std::cmp::Reverse(r.get_started()));
+        Ok(replays)
+    }
+
+    /// get_started returns the time the game started, in seconds since the UNIX epoch
+    pub fn get_started(&self) -> u64 {
+        self.log.get_entries().first().map_or(0, |e| e.time)
+    }
+
+    /// get_name returns the players of the game and its winner, e.g. "a, b, c, d · a won"
+    pub fn get_name(&self) -> String {
+        let mut players = String::new();
+        let mut winner = None;
+        for entry in self.log.get_entries() {
+            match &entry.event {
+                LogEvent::Started { players: names, .. } => players = names.join(", "),
+                LogEvent::Finished { winner: w, .. } => winner = Some(w.clone()),
+                _ => {}
+            }
+        }
+        match winner {
+            Some(Some(winner)) => format!("{} · {} won", players, winner),
+            Some(None) => format!("{} · tie", players),
+            None => players,
+        }
+    }
+
+    /// get_step returns the events shown so far and the events of the whole game
+    pub fn get_step(&self) -> (usize, usize) {
+        (self.step, self.log.get_entries().len())
+    }
+
+    /// next shows one more event of the game, until the end of the game
+    pub fn next(&mut self) {
+        self.step = (self.step + 1).min(self.log.get_entries().len());
+    }
+
+    /// previous takes back the last event shown, the start of the game is kept
+    pub fn previous(&mut self) {
+        if self.step > 1 {
+            self.step -= 1;
+        }
+    }
+
+    /// get_events returns the events of the game up to the current step
+    pub fn get_events(&self) -> &[LogEntry] {
+        &self.log.get_entries()[..self.step]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::RuleSet;
+    use crate::score::ScoreBoard;
+
+    fn new_log() -> GameLog {
+        let mut log = GameLog::new();
+        log.record(LogEvent::Started {
+            players: vec![String::from("a"), String::from("b")],
+            rules: RuleSet::default(),
+        });
+        log.record(LogEvent::Redeal {
+            player: String::from("a"),
+        });
+        log.record(LogEvent::Finished {
+            score_board: ScoreBoard::default(),
+            winner: Some(String::from("b")),
+        });
+        log
+    }
+
+    #[test]
+    fn test_replay() {
+        let mut replay = Replay::new(new_log());
+        assert_eq!(replay.get_name(), "a, b · b won");
+        assert_eq!(replay.get_step(), (1, 3));
+        replay.previous();
+        assert_eq!(replay.get_events().len(), 1);
+        replay.next();
+        replay.next();
+        replay.next();
+        assert_eq!(replay.get_step(), (3, 3));
+        assert!(matches!(
+            replay.get_events()[2].event,
+            LogEvent::Finished { .. }
+        ));
+    }
+
+    #[test]
+    fn test_load_all() {
+        let dir = std::env::temp_dir().join(format!("callbreak-replays-{}", std::process::id()));
+        assert!(Replay::load_all(&dir).unwrap().is_empty());
+        new_log().save_new(&dir).unwrap();
+        let replays = Replay::load_all(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(replays.len(), 1);
+        assert_eq!(replays[0].get_name(), "a, b · b won");
+    }
+}
@@ -1,27 +1,51 @@
 use ratatui::{
-    buffer::Buffer,
-    crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind},
-    layout::{Alignment, Rect},
+    crossterm::event::{self, Event, KeyCode},
     prelude::Backend,
-    style::Stylize,
-    symbols::border,
-    text::{Line, Text},
-    widgets::{
-        block::{Position, Title},
-        Block, Paragraph, Widget,
-    },
     Frame, Terminal,
 };
 use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
-use crate::game::Game;
+use crate::bid::Bid;
+use crate::bot::{Bot, Difficulty};
+use crate::game::{Game, GameError, GameState, Move, TimeLimits, MAX_PLAYERS, MIN_PLAYERS};
+use crate::log::{GameLog, LogEvent};
+use crate::pin;
+use crate::rules::{Reshuffle, RulePreset, PARTNERSHIP_PLAYERS};
+use crate::stats::Stats;
+use crate::view::GameView;
 
+use super::config::{
+    AnimationSpeed, Config, TimeLimit, MAX_CALL, MAX_MIN_TOTAL_CALL, MAX_TOTAL_DEALS, MIN_CALL,
+};
+use super::profile::{Profiles, AVATARS};
+use super::remote::{RemoteSession, RemoteUpdate};
+use super::replay::Replay;
+use super::theme::Theme;
 use super::ui;
 
-#[derive(PartialEq)]
+// how long to wait for a key press before checking the server for updates
+const REMOTE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+// how often the countdown of a timed turn is redrawn
+const COUNTDOWN_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Clone, Copy, PartialEq)]
 pub enum CurrentScreen {
     Main,
     NewGame,
+    Game,
+    ScoreBoard,
+    Settings,
+    Rules,
+    // the lifetime statistics of the players of this device
+    Statistics,
+    // the saved profiles of the regular players
+    Profiles,
+    // the finished games of this device, stepped through one event at a time
+    Replay,
+    // the players seated in a hosted game, waiting for it to start
+    Lobby,
     Exiting,
 }
 
@@ -29,39 +53,247 @@ pub enum CurrentScreen {
 pub enum NewGamePopups {
     NumberOfPlayers,
     PlayerNames,
+    PlayerPin,
+}
+
+#[derive(PartialEq)]
+pub enum GamePopups {
+    // asks the pin of the current player before revealing the cards
+    RevealHand,
+}
+
+#[derive(PartialEq)]
+pub enum SettingsPopups {
+    // edits the default player names as a comma separated list
+    PlayerNames,
+}
+
+#[derive(PartialEq)]
+pub enum LobbyPopups {
+    // asks the name to join the hosted game with
+    PlayerName,
+    Chat,
 }
 
 #[derive(PartialEq)]
 pub enum Popups {
     None,
     NewGamePopups(NewGamePopups),
+    GamePopups(GamePopups),
+    SettingsPopups(SettingsPopups),
+    LobbyPopups(LobbyPopups),
+}
+
+/// MenuItem is a single entry of the main menu
+#[derive(Clone, Copy, PartialEq)]
+pub enum MenuItem {
+    NewGame,
+    Continue,
+    LoadReplay,
+    Statistics,
+    Profiles,
+    Settings,
+    Rules,
+    Quit,
+}
+
+impl MenuItem {
+    pub const ALL: [MenuItem; 8] = [
+        MenuItem::NewGame,
+        MenuItem::Continue,
+        MenuItem::LoadReplay,
+        MenuItem::Statistics,
+        MenuItem::Profiles,
+        MenuItem::Settings,
+        MenuItem::Rules,
+        MenuItem::Quit,
+    ];
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            MenuItem::NewGame => "New Game",
+            MenuItem::Continue => "Continue",
+            MenuItem::LoadReplay => "Load Replay",
+            MenuItem::Statistics => "Statistics",
+            MenuItem::Profiles => "Profiles",
+            MenuItem::Settings => "Settings",
+            MenuItem::Rules => "Rules",
+            MenuItem::Quit => "Quit",
+        }
+    }
 }
 
-pub struct State<'a> {
-    pub game: Game<'a>,
+/// Setting is a single option of the settings screen
+#[derive(Clone, Copy, PartialEq)]
+pub enum Setting {
+    Theme,
+    Rules,
+    Redeal,
+    Partnership,
+    NilBid,
+    Hook,
+    MinTotalCall,
+    TotalDeals,
+    MinCall,
+    MaxCall,
+    BotDifficulty,
+    AnimationSpeed,
+    CallTimeLimit,
+    ThrowTimeLimit,
+    PlayerNames,
+}
+
+impl Setting {
+    pub const ALL: [Setting; 15] = [
+        Setting::Theme,
+        Setting::Rules,
+        Setting::Redeal,
+        Setting::Partnership,
+        Setting::NilBid,
+        Setting::Hook,
+        Setting::MinTotalCall,
+        Setting::TotalDeals,
+        Setting::MinCall,
+        Setting::MaxCall,
+        Setting::BotDifficulty,
+        Setting::AnimationSpeed,
+        Setting::CallTimeLimit,
+        Setting::ThrowTimeLimit,
+        Setting::PlayerNames,
+    ];
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Setting::Theme => "Theme",
+            Setting::Rules => "Rules",
+            Setting::Redeal => "Redeal a weak hand",
+            Setting::Partnership => "Partnership (2v2)",
+            Setting::NilBid => "Nil call",
+            Setting::Hook => "Hook the last caller",
+            Setting::MinTotalCall => "Minimum total call",
+            Setting::TotalDeals => "Number of deals",
+            Setting::MinCall => "Minimum call",
+            Setting::MaxCall => "Maximum call",
+            Setting::BotDifficulty => "Bot difficulty",
+            Setting::AnimationSpeed => "Animation speed",
+            Setting::CallTimeLimit => "Time to call",
+            Setting::ThrowTimeLimit => "Time to throw",
+            Setting::PlayerNames => "Default player names",
+        }
+    }
+}
+
+pub struct State {
+    pub game: Game,
+    pub config: Config,
+    // the file the settings are saved to, None if it couldn't be read
+    pub config_path: Option<PathBuf>,
     pub current_screen: CurrentScreen,
+    // screens to go back to, once the score board or the exit prompt is closed
+    pub previous_screens: Vec<CurrentScreen>,
     pub current_popup: Popups,
     pub total_players: u8,
+    pub player_name: String,
     pub input_buffer: String,
     pub error: String,
+    // the player whose cards are currently revealed on the screen
+    pub revealed_player: Option<String>,
+    pub selected_card: usize,
+    pub selected_call: Bid,
+    pub selected_setting: usize,
+    pub selected_menu_item: usize,
+    // first line of the rules shown on the rules screen
+    pub rules_scroll: u16,
+    // computer players of the current game, played by the bot
+    pub bot_players: Vec<String>,
+    pub bot: Bot,
+    // the game hosted on another machine, None for a game played on this device
+    pub remote: Option<RemoteSession>,
+    // the history of the local game, replayed into the statistics once it's over
+    pub log: GameLog,
+    // the statistics shown on the statistics screen and the player selected there
+    pub stats: Stats,
+    pub selected_stats_player: usize,
+    // the profiles picked in the new game wizard, saved to the file of the path if there is one
+    pub profiles: Profiles,
+    pub profiles_path: Option<PathBuf>,
+    pub selected_profile: usize,
+    // the finished games saved to the directory if there is one, the latest first,
+    // and the one being watched
+    pub replays: Vec<Replay>,
+    pub replays_dir: Option<PathBuf>,
+    pub selected_replay: usize,
+    pub replay: Option<Replay>,
 }
 
-impl<'a> State<'a> {
-    pub fn new(game: Game<'a>) -> Self {
+impl State {
+    pub fn new(game: Game, config: Config) -> Self {
         Self {
             game,
+            bot: Bot::new(config.bot_difficulty),
+            config,
+            config_path: None,
             current_screen: CurrentScreen::Main,
+            previous_screens: vec![],
             current_popup: Popups::None,
             input_buffer: String::new(),
             total_players: 0,
+            player_name: String::new(),
             error: String::from(""),
+            revealed_player: None,
+            selected_card: 0,
+            selected_call: Bid::default(),
+            selected_setting: 0,
+            selected_menu_item: 0,
+            rules_scroll: 0,
+            bot_players: vec![],
+            remote: None,
+            log: GameLog::new(),
+            stats: Stats::default(),
+            selected_stats_player: 0,
+            profiles: Profiles::default(),
+            profiles_path: None,
+            selected_profile: 0,
+            replays: vec![],
+            replays_dir: None,
+            selected_replay: 0,
+            replay: None,
         }
     }
 
     pub fn run_app<B: Backend>(&mut self, terminal: &mut Terminal<B>) -> io::Result<bool> {
         loop {
             terminal.draw(|f| self.render_frame(f))?;
-            let exit_result = self.handle_events();
+            // a hosted game is redrawn whenever the server sends an update
+            if self.remote.is_some() {
+                self.poll_remote();
+                if !event::poll(REMOTE_POLL_INTERVAL)? {
+                    continue;
+                }
+            }
+            // the countdown is redrawn while waiting for a key, the turn is played once the time is up
+            if self.is_timed_turn() {
+                if self.game.is_out_of_time() {
+                    self.play_timed_out_turn();
+                    continue;
+                }
+                if !event::poll(COUNTDOWN_INTERVAL)? {
+                    continue;
+                }
+            }
+            // the bot moves once the animation delay has passed without any key press
+            if self.is_bot_turn() && !event::poll(self.config.animation_speed.get_delay())? {
+                self.play_bot_turn();
+                continue;
+            }
+            let event = event::read()?;
+            if let Event::Resize(_, _) = event {
+                // the layout is picked from the terminal size on the next draw
+                terminal.autoresize()?;
+                terminal.clear()?;
+                continue;
+            }
+            let exit_result = self.handle_events(event);
 
             match exit_result {
                 Ok(true) => {
@@ -79,109 +311,976 @@ impl<'a> State<'a> {
         ui::draw_main_screen(frame, self);
     }
 
-    fn handle_events(&mut self) -> io::Result<bool> {
-        if let Event::Key(key) = event::read()? {
-            self.reset_error();
-            if key.kind == event::KeyEventKind::Release {
-                // Skip events that are not KeyEventKind::Press
-                return Ok(true);
+    fn handle_events(&mut self, event: Event) -> io::Result<bool> {
+        let key = match event {
+            Event::Key(key) if key.kind != event::KeyEventKind::Release => key,
+            // Skip events that are not KeyEventKind::Press
+            _ => return Ok(false),
+        };
+        self.reset_error();
+
+        // a focused text field takes every key, so typing never triggers a shortcut
+        if self.has_text_focus() {
+            self.handle_text_input(key.code);
+            return Ok(false);
+        }
+
+        match self.current_screen {
+            CurrentScreen::Main => self.handle_menu_events(key.code),
+            CurrentScreen::Game => self.handle_game_events(key.code),
+            CurrentScreen::ScoreBoard => match key.code {
+                KeyCode::Esc | KeyCode::Char('s') => self.go_back(),
+                KeyCode::Char('q') => self.set_exiting(),
+                _ => {}
+            },
+            CurrentScreen::Settings => self.handle_settings_events(key.code),
+            CurrentScreen::Rules => match key.code {
+                KeyCode::Up => self.rules_scroll = self.rules_scroll.saturating_sub(1),
+                KeyCode::Down => self.rules_scroll = self.rules_scroll.saturating_add(1),
+                KeyCode::Esc => self.go_back(),
+                KeyCode::Char('q') => self.set_exiting(),
+                _ => {}
+            },
+            CurrentScreen::Statistics => match key.code {
+                KeyCode::Up => {
+                    self.selected_stats_player = self.selected_stats_player.saturating_sub(1);
+                }
+                KeyCode::Down => {
+                    let last = self.stats.get_players().len().saturating_sub(1);
+                    self.selected_stats_player = (self.selected_stats_player + 1).min(last);
+                }
+                KeyCode::Esc => self.go_back(),
+                KeyCode::Char('q') => self.set_exiting(),
+                _ => {}
+            },
+            CurrentScreen::Profiles => self.handle_profiles_events(key.code),
+            CurrentScreen::Replay => self.handle_replay_events(key.code),
+            CurrentScreen::Lobby => match key.code {
+                KeyCode::Char('r') => {
+                    if let Some(remote) = self.remote.as_mut() {
+                        remote.toggle_ready();
+                    }
+                }
+                // the spectators can't chat with the players
+                KeyCode::Char('t') | KeyCode::Enter
+                    if !self.remote.as_ref().is_some_and(|r| r.is_spectating()) =>
+                {
+                    self.set_popup_state(Popups::LobbyPopups(LobbyPopups::Chat))
+                }
+                KeyCode::Char('q') => self.set_exiting(),
+                _ => {}
+            },
+            CurrentScreen::Exiting => match key.code {
+                KeyCode::Char('y') => return Ok(true),
+                KeyCode::Char('n') | KeyCode::Esc => self.go_back(),
+                _ => {}
+            },
+            // the new game wizard always has one of its text fields focused
+            CurrentScreen::NewGame => {}
+        }
+        Ok(false)
+    }
+
+    /// has_text_focus checks if a text field of the current screen takes the key presses
+    pub fn has_text_focus(&self) -> bool {
+        matches!(
+            (self.current_screen, &self.current_popup),
+            (CurrentScreen::NewGame, Popups::NewGamePopups(_))
+                | (
+                    CurrentScreen::Game,
+                    Popups::GamePopups(GamePopups::RevealHand)
+                )
+                | (
+                    CurrentScreen::Settings,
+                    Popups::SettingsPopups(SettingsPopups::PlayerNames)
+                )
+                | (CurrentScreen::Lobby, Popups::LobbyPopups(_))
+        )
+    }
+
+    /// handle_text_input edits the focused text field, [Enter] submits it and [Esc] cancels it
+    fn handle_text_input(&mut self, key_code: KeyCode) {
+        match (key_code, &self.current_popup) {
+            (KeyCode::Delete | KeyCode::Backspace, _) => {
+                self.input_buffer.pop();
             }
-            match self.current_screen {
-                CurrentScreen::NewGame => match self.current_popup {
-                    Popups::NewGamePopups(NewGamePopups::NumberOfPlayers) => match key.code {
-                        KeyCode::Delete => {
-                            self.input_buffer.pop();
-                        }
-                        KeyCode::Backspace => {
-                            self.input_buffer.pop();
-                        }
+            (KeyCode::Esc, Popups::SettingsPopups(_) | Popups::LobbyPopups(LobbyPopups::Chat)) => {
+                self.input_buffer.clear();
+                self.set_popup_state(Popups::None);
+            }
+            // there is no menu to go back to while joining a hosted game
+            (KeyCode::Esc, Popups::LobbyPopups(LobbyPopups::PlayerName)) => self.set_exiting(),
+            // leaving the wizard or the pin prompt goes back to the menu
+            (KeyCode::Esc, _) => self.open_main_menu(),
+            (KeyCode::Tab, Popups::NewGamePopups(NewGamePopups::PlayerNames)) => {
+                self.add_bot_player()
+            }
+            (KeyCode::Up, Popups::NewGamePopups(NewGamePopups::PlayerNames)) => {
+                self.pick_profile(false)
+            }
+            (KeyCode::Down, Popups::NewGamePopups(NewGamePopups::PlayerNames)) => {
+                self.pick_profile(true)
+            }
+            (KeyCode::Enter, Popups::NewGamePopups(NewGamePopups::NumberOfPlayers)) => {
+                self.submit_total_players()
+            }
+            (KeyCode::Enter, Popups::NewGamePopups(NewGamePopups::PlayerNames)) => {
+                self.submit_player_name()
+            }
+            (KeyCode::Enter, Popups::NewGamePopups(NewGamePopups::PlayerPin)) => {
+                self.add_player_with_pin()
+            }
+            (KeyCode::Enter, Popups::GamePopups(GamePopups::RevealHand)) => {
+                self.reveal_current_player()
+            }
+            (KeyCode::Enter, Popups::SettingsPopups(SettingsPopups::PlayerNames)) => {
+                self.config.player_names = self.input_buffer.split(',').map(String::from).collect();
+                self.config = self.config.clone().validate();
+                self.input_buffer.clear();
+                self.set_popup_state(Popups::None);
+                self.save_config();
+            }
+            (KeyCode::Enter, Popups::LobbyPopups(LobbyPopups::PlayerName)) => {
+                self.join_remote_game()
+            }
+            (KeyCode::Enter, Popups::LobbyPopups(LobbyPopups::Chat)) => {
+                if let Some(remote) = self.remote.as_mut() {
+                    remote.send_chat(&self.input_buffer);
+                }
+                self.input_buffer.clear();
+                self.set_popup_state(Popups::None);
+            }
+            (KeyCode::Char(c), Popups::NewGamePopups(NewGamePopups::NumberOfPlayers))
+                if c.is_ascii_digit() =>
+            {
+                self.input_buffer.push(c)
+            }
+            (KeyCode::Char(_), Popups::NewGamePopups(NewGamePopups::NumberOfPlayers)) => {}
+            (
+                KeyCode::Char(c),
+                Popups::NewGamePopups(NewGamePopups::PlayerPin)
+                | Popups::GamePopups(GamePopups::RevealHand),
+            ) => self.push_pin_char(c),
+            (KeyCode::Char(c), _) => self.input_buffer.push(c),
+            _ => {}
+        }
+    }
 
-                        KeyCode::Enter => {
-                            if let Popups::NewGamePopups(NewGamePopups::NumberOfPlayers) =
-                                self.current_popup
-                            {
-                                let total_players_result = self.input_buffer.parse();
-                                match total_players_result {
-                                    Ok(total_players) => {
-                                        if total_players < 5 {
-                                            self.set_popup_state(Popups::NewGamePopups(
-                                                NewGamePopups::PlayerNames,
-                                            ));
-                                            self.total_players = total_players;
-                                            self.input_buffer.clear();
-                                        } else {
-                                            // error component
-                                            self.set_error(String::from(
-                                                "You've entered too many players (max 4)",
-                                            ))
-                                        }
-                                    }
-                                    Err(e) => self.set_error(e.to_string()),
-                                }
-                            }
-                        }
+    /// handle_menu_events moves through the main menu and opens the selected entry
+    fn handle_menu_events(&mut self, key_code: KeyCode) {
+        match key_code {
+            KeyCode::Up => {
+                self.selected_menu_item = self.selected_menu_item.saturating_sub(1);
+            }
+            KeyCode::Down => {
+                self.selected_menu_item =
+                    (self.selected_menu_item + 1).min(MenuItem::ALL.len() - 1);
+            }
+            KeyCode::Enter => self.select_menu_item(MenuItem::ALL[self.selected_menu_item]),
+            KeyCode::Char('q') => self.set_exiting(),
+            _ => {}
+        }
+    }
 
-                        KeyCode::Char('q') => {
-                            self.current_screen = CurrentScreen::Exiting;
-                        }
+    /// is_menu_item_enabled checks if the menu entry can be opened right now
+    pub fn is_menu_item_enabled(&self, item: MenuItem) -> bool {
+        match item {
+            MenuItem::Continue => self.can_continue(),
+            MenuItem::LoadReplay => !self.replays.is_empty(),
+            _ => true,
+        }
+    }
 
-                        _ => {
-                            // check if the input is an number or not
-                            if let Ok(_m) = key.code.to_string().parse::<i32>() {
-                                self.input_buffer.push_str(&key.code.to_string());
-                            }
-                        }
-                    },
-                    Popups::NewGamePopups(NewGamePopups::PlayerNames) => match key.code {
-                        KeyCode::Delete => {
-                            self.input_buffer.pop();
-                        }
-                        KeyCode::Backspace => {
-                            self.input_buffer.pop();
-                        }
+    fn select_menu_item(&mut self, item: MenuItem) {
+        if !self.is_menu_item_enabled(item) {
+            match item {
+                MenuItem::Continue => self.set_error(String::from("No game in progress")),
+                _ => self.set_error(String::from("No saved replays found")),
+            }
+            return;
+        }
 
-                        KeyCode::Enter => {
-                            if let Popups::NewGamePopups(NewGamePopups::PlayerNames) =
-                                self.current_popup
-                            {
-                                self.input_buffer.clear();
-                            }
-                        }
+        match item {
+            MenuItem::NewGame => self.set_current_screen_new_game(),
+            MenuItem::Continue => self.continue_game(),
+            MenuItem::LoadReplay => {
+                self.selected_replay = 0;
+                self.replay = None;
+                self.previous_screens.push(self.current_screen);
+                self.current_screen = CurrentScreen::Replay;
+            }
+            MenuItem::Statistics => self.show_statistics(),
+            MenuItem::Profiles => {
+                // the linked statistics are shown next to the profiles
+                self.stats = Stats::load().unwrap_or_default();
+                self.selected_profile = 0;
+                self.previous_screens.push(self.current_screen);
+                self.current_screen = CurrentScreen::Profiles;
+            }
+            MenuItem::Settings => {
+                self.previous_screens.push(self.current_screen);
+                self.current_screen = CurrentScreen::Settings;
+            }
+            MenuItem::Rules => {
+                self.rules_scroll = 0;
+                self.previous_screens.push(self.current_screen);
+                self.current_screen = CurrentScreen::Rules;
+            }
+            MenuItem::Quit => self.set_exiting(),
+        }
+    }
+
+    /// can_continue checks if there is a game in progress to go back to
+    fn can_continue(&self) -> bool {
+        matches!(
+            self.game.get_state(),
+            GameState::Calling | GameState::Playing
+        )
+    }
+
+    /// continue_game goes back to the running game, the hand is hidden until the pin is entered again
+    fn continue_game(&mut self) {
+        self.previous_screens.clear();
+        self.set_current_screen(CurrentScreen::Game);
+        self.revealed_player = None;
+        // the clock didn't run while the game was paused in the menu
+        self.game.resume_turn_clock();
+        self.next_turn();
+    }
+
+    /// open_main_menu leaves the current screen for the menu, with the cards of the game hidden
+    fn open_main_menu(&mut self) {
+        self.game.pause_turn_clock();
+        self.input_buffer.clear();
+        self.revealed_player = None;
+        self.previous_screens.clear();
+        self.set_popup_state(Popups::None);
+        self.set_current_screen(CurrentScreen::Main);
+        self.selected_menu_item = if self.can_continue() { 1 } else { 0 };
+    }
 
-                        _ => {
-                            self.input_buffer.push_str(&key.code.to_string());
+    /// submit_total_players checks the number of players and asks for the first player
+    fn submit_total_players(&mut self) {
+        match self.input_buffer.parse::<u8>() {
+            Ok(total_players)
+                if self.config.partnership && total_players as usize != PARTNERSHIP_PLAYERS =>
+            {
+                self.set_error(GameError::InvalidTeams.to_string())
+            }
+            Ok(total_players)
+                if (MIN_PLAYERS..=MAX_PLAYERS).contains(&(total_players as usize)) =>
+            {
+                self.total_players = total_players;
+                self.ask_next_player_name();
+            }
+            Ok(_) => self.set_error(format!(
+                "Enter between {} and {} players",
+                MIN_PLAYERS, MAX_PLAYERS
+            )),
+            Err(e) => self.set_error(e.to_string()),
+        }
+    }
+
+    /// submit_player_name checks the typed name and asks for the pin of the player
+    fn submit_player_name(&mut self) {
+        let name = self.input_buffer.trim().to_string();
+        if name.is_empty() {
+            self.set_error(String::from("Player name can't be empty"));
+        } else if self.game.get_players().iter().any(|p| p.get_name() == name) {
+            self.set_error(String::from("Player already added in the game"));
+        } else {
+            self.player_name = name;
+            self.input_buffer.clear();
+            self.set_popup_state(Popups::NewGamePopups(NewGamePopups::PlayerPin));
+        }
+    }
+
+    /// step_call moves the call picker to the next or the previous call allowed at the table
+    fn step_call(&mut self, view: &GameView, forward: bool) {
+        // the calls forbidden to the last caller are skipped
+        let bids = view.get_allowed_bids();
+        let idx = bids
+            .iter()
+            .position(|b| *b == self.selected_call)
+            .unwrap_or(0);
+        let idx = if forward {
+            (idx + 1).min(bids.len().saturating_sub(1))
+        } else {
+            idx.saturating_sub(1)
+        };
+        if let Some(bid) = bids.get(idx) {
+            self.selected_call = *bid;
+        }
+    }
+
+    /// handle_game_events handles the calls and the card throws of the running game
+    fn handle_game_events(&mut self, key_code: KeyCode) {
+        let view = match self.get_view() {
+            Some(view) => view,
+            None => return,
+        };
+        match key_code {
+            KeyCode::Esc if self.remote.is_none() => self.open_main_menu(),
+            KeyCode::Char('s') => self.show_score_board(),
+            KeyCode::Char('q') => self.set_exiting(),
+            KeyCode::Char('n') if self.remote.is_none() && view.state == GameState::Finished => {
+                self.set_current_screen_new_game();
+            }
+            KeyCode::Left => match view.state {
+                GameState::Calling => self.step_call(&view, false),
+                GameState::Playing => {
+                    self.selected_card = self.selected_card.saturating_sub(1);
+                }
+                _ => {}
+            },
+            KeyCode::Right => match view.state {
+                GameState::Calling => self.step_call(&view, true),
+                GameState::Playing if self.selected_card + 1 < view.hand.len() => {
+                    self.selected_card += 1;
+                }
+                _ => {}
+            },
+            KeyCode::Char('r') if view.can_redeal => {
+                if let Some(remote) = self.remote.as_mut() {
+                    remote.redeal();
+                    return;
+                }
+                let player_name = view.current_player.unwrap_or_default();
+                match self.play_local_move(&player_name, Move::Redeal) {
+                    Ok(()) => self.next_turn(),
+                    Err(e) => self.set_error(e.to_string()),
+                }
+            }
+            KeyCode::Enter if view.is_my_turn() => {
+                if let Some(remote) = self.remote.as_mut() {
+                    // the server checks the move and answers with the new state or an error
+                    match view.state {
+                        GameState::Calling => remote.place_call(self.selected_call.get_value()),
+                        GameState::Playing => {
+                            if let Some(card) = view.hand.get(self.selected_card) {
+                                remote.throw(card.clone());
+                            }
                         }
-                    },
-                    _ => {}
-                },
+                        _ => {}
+                    }
+                    return;
+                }
 
-                CurrentScreen::Exiting => match key.code {
-                    KeyCode::Char('y') => {
-                        return Ok(true);
+                let player_name = match &view.current_player {
+                    Some(p) => p.clone(),
+                    None => return,
+                };
+                let result = match view.state {
+                    GameState::Calling => self
+                        .play_local_move(&player_name, Move::Call(self.selected_call.get_value())),
+                    GameState::Playing => {
+                        self.play_local_move(&player_name, Move::Throw(self.selected_card))
                     }
-                    KeyCode::Char('n') => {
-                        self.current_screen = CurrentScreen::Main;
+                    _ => Ok(()),
+                };
+                match result {
+                    Ok(()) => self.next_turn(),
+                    Err(e) => self.set_error(e.to_string()),
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// get_view returns the game as shown on the screen: the seat of the hosted game, or
+    /// the local game with the cards of the revealed player while it's their turn
+    pub fn get_view(&self) -> Option<GameView> {
+        if let Some(remote) = &self.remote {
+            return remote.get_view();
+        }
+        let current_player = self.game.get_current_player().map(|p| p.get_name());
+        let seat = self
+            .revealed_player
+            .as_deref()
+            .filter(|p| current_player == Some(*p));
+        Some(GameView::new(&self.game, seat))
+    }
+
+    /// start_remote opens the lobby of the hosted game, the name is asked if it's not given
+    pub fn start_remote(&mut self, session: RemoteSession, name: Option<String>) {
+        self.remote = Some(session);
+        self.previous_screens.clear();
+        self.set_current_screen(CurrentScreen::Lobby);
+        match name {
+            Some(name) => {
+                self.input_buffer = name;
+                self.join_remote_game();
+            }
+            None => {
+                self.input_buffer = self
+                    .config
+                    .get_player_name(0)
+                    .map(String::from)
+                    .unwrap_or_default();
+                self.set_popup_state(Popups::LobbyPopups(LobbyPopups::PlayerName));
+            }
+        }
+    }
+
+    /// join_remote_game asks the server for a seat, or to watch, with the typed name
+    fn join_remote_game(&mut self) {
+        let name = self.input_buffer.trim().to_string();
+        if name.is_empty() {
+            self.set_error(String::from("Player name can't be empty"));
+            return;
+        }
+        match self.remote.as_mut() {
+            Some(remote) if remote.watching => remote.watch(&name),
+            Some(remote) => remote.join(&name),
+            None => {}
+        }
+        self.input_buffer.clear();
+        self.set_popup_state(Popups::None);
+    }
+
+    /// poll_remote applies the updates of the server to the screens
+    fn poll_remote(&mut self) {
+        let remote = match self.remote.as_mut() {
+            Some(remote) => remote,
+            None => return,
+        };
+        let previous_view = remote.view.clone();
+        for update in remote.poll() {
+            match update {
+                RemoteUpdate::Error(message) => {
+                    // a rejected join asks for another name
+                    let remote = self.remote.as_ref().expect("remote session");
+                    if remote.seat.is_none() && !remote.is_spectating() {
+                        self.input_buffer = remote.name.clone();
+                        self.set_popup_state(Popups::LobbyPopups(LobbyPopups::PlayerName));
                     }
-                    _ => {}
-                },
-                _ => {}
+                    self.set_error(message);
+                }
+                RemoteUpdate::State => self.apply_remote_view(previous_view.as_ref()),
+                RemoteUpdate::Joined | RemoteUpdate::Lobby | RemoteUpdate::Disconnected => {}
+            }
+        }
+    }
+
+    /// apply_remote_view opens the game once it has started and resets the selection
+    /// when the turn comes to the player
+    fn apply_remote_view(&mut self, previous_view: Option<&GameView>) {
+        let view = match self.remote.as_ref().and_then(|r| r.view.as_ref()) {
+            Some(view) => view,
+            None => return,
+        };
+        let was_my_turn = previous_view.is_some_and(|v| v.is_my_turn() && v.state == view.state);
+        if view.is_my_turn() && !was_my_turn {
+            self.selected_card = 0;
+            self.selected_call = view.get_default_bid();
+        }
+        self.selected_card = self.selected_card.min(view.hand.len().saturating_sub(1));
+
+        let is_finished = view.state == GameState::Finished;
+        let was_finished = previous_view.is_some_and(|v| v.state == GameState::Finished);
+        if self.current_screen == CurrentScreen::Lobby {
+            self.set_popup_state(Popups::None);
+            self.set_current_screen(CurrentScreen::Game);
+        }
+        if is_finished && !was_finished {
+            self.show_score_board();
+        }
+    }
+
+    /// handle_settings_events changes the selected setting and saves the config
+    fn handle_settings_events(&mut self, key_code: KeyCode) {
+        let setting = Setting::ALL[self.selected_setting];
+        match key_code {
+            KeyCode::Esc => self.go_back(),
+            KeyCode::Char('q') => self.set_exiting(),
+            KeyCode::Up => self.selected_setting = self.selected_setting.saturating_sub(1),
+            KeyCode::Down => {
+                self.selected_setting = (self.selected_setting + 1).min(Setting::ALL.len() - 1)
+            }
+            KeyCode::Enter if setting == Setting::PlayerNames => {
+                self.input_buffer = self.config.player_names.join(", ");
+                self.set_popup_state(Popups::SettingsPopups(SettingsPopups::PlayerNames));
+            }
+            KeyCode::Left => self.change_setting(setting, false),
+            KeyCode::Right | KeyCode::Enter => self.change_setting(setting, true),
+            _ => {}
+        }
+    }
+
+    /// change_setting moves the setting to its next or previous value and saves the config
+    fn change_setting(&mut self, setting: Setting, forward: bool) {
+        let config = &mut self.config;
+        match setting {
+            Setting::Theme => {
+                config.theme = cycle(&Theme::ALL, config.theme, forward);
+            }
+            Setting::Rules => {
+                config.rules = cycle(&RulePreset::ALL, config.rules, forward);
+            }
+            Setting::Redeal => {
+                config.redeal = cycle(&Reshuffle::ALL, config.redeal, forward);
+            }
+            Setting::Partnership => config.partnership = !config.partnership,
+            Setting::NilBid => config.nil_bid = !config.nil_bid,
+            Setting::Hook => config.hook = !config.hook,
+            Setting::MinTotalCall => {
+                config.min_total_call = step(config.min_total_call, forward, 0, MAX_MIN_TOTAL_CALL);
+            }
+            Setting::TotalDeals => {
+                config.total_deals = step(config.total_deals, forward, 1, MAX_TOTAL_DEALS);
+            }
+            Setting::MinCall => {
+                config.min_call = step(config.min_call, forward, MIN_CALL, config.max_call);
+            }
+            Setting::MaxCall => {
+                config.max_call = step(config.max_call, forward, config.min_call, MAX_CALL);
             }
+            Setting::BotDifficulty => {
+                config.bot_difficulty = cycle(&Difficulty::ALL, config.bot_difficulty, forward);
+                self.bot = Bot::new(config.bot_difficulty);
+            }
+            Setting::AnimationSpeed => {
+                config.animation_speed =
+                    cycle(&AnimationSpeed::ALL, config.animation_speed, forward);
+            }
+            Setting::CallTimeLimit => {
+                config.call_time_limit = cycle(&TimeLimit::ALL, config.call_time_limit, forward);
+            }
+            Setting::ThrowTimeLimit => {
+                config.throw_time_limit = cycle(&TimeLimit::ALL, config.throw_time_limit, forward);
+            }
+            Setting::PlayerNames => return,
+        }
+        self.save_config();
+    }
+
+    /// load_config reads the saved settings, the changes are saved back to the same file
+    pub fn load_config(&mut self) {
+        let path = match Config::get_path() {
+            Some(path) => path,
+            None => return,
+        };
+        match Config::load_from(&path) {
+            Ok(config) => {
+                self.bot = Bot::new(config.bot_difficulty);
+                self.config = config;
+                self.config_path = Some(path);
+            }
+            // a broken file is left alone instead of being overwritten
+            Err(e) => self.set_error(format!("Unable to read the settings: {}", e)),
+        }
+    }
+
+    fn save_config(&mut self) {
+        let result = match &self.config_path {
+            Some(path) => self.config.save_to(path),
+            None => return,
+        };
+        if let Err(e) = result {
+            self.set_error(format!("Unable to save the settings: {}", e));
+        }
+    }
+
+    /// add_player_with_pin adds the player of the wizard to the game and starts
+    /// the game once all the players are added
+    fn add_player_with_pin(&mut self) {
+        let pin = std::mem::take(&mut self.input_buffer);
+        // a regular has to enter the pin of their profile to play as them
+        let result = match self.profiles.get_profile_mut(&self.player_name) {
+            Some(profile) => profile.verify_pin(&pin),
+            None => pin::validate(&pin),
+        };
+        let is_new_profile = self.profiles.get_profile(&self.player_name).is_none();
+        // the wrong pins of a profile are saved, so a lockout outlasts the app
+        if !is_new_profile {
+            self.save_profiles();
+        }
+        if let Err(e) = result {
+            self.set_error(e.to_string());
+            return;
+        }
+
+        if let Err(e) = self.game.add_players(&self.player_name, &pin) {
+            self.set_error(e.to_string());
+            self.set_popup_state(Popups::NewGamePopups(NewGamePopups::PlayerNames));
+            return;
+        }
+        // the new players are saved as profiles, so they are picked instead of typed next time
+        if is_new_profile {
+            self.profiles.add(&self.player_name, &pin);
+            self.save_profiles();
+        }
+
+        self.start_game_if_ready();
+    }
 
-            // insert logic to handle key events here
-            match key.code {
-                KeyCode::Char('q') => {
-                    self.current_screen = CurrentScreen::Exiting;
+    /// pick_profile fills the player name with the next or the previous saved profile
+    /// that isn't in the game yet
+    fn pick_profile(&mut self, forward: bool) {
+        let names: Vec<String> = self
+            .profiles
+            .get_profiles()
+            .iter()
+            .map(|p| p.get_name().to_string())
+            .filter(|n| self.game.get_players().iter().all(|p| p.get_name() != n))
+            .collect();
+        if names.is_empty() {
+            return;
+        }
+        self.input_buffer = match names.iter().position(|n| *n == self.input_buffer) {
+            Some(idx) if forward => names[(idx + 1) % names.len()].clone(),
+            Some(idx) => names[(idx + names.len() - 1) % names.len()].clone(),
+            None if forward => names[0].clone(),
+            None => names[names.len() - 1].clone(),
+        };
+    }
+
+    /// load_profiles reads the saved profiles, the changes are saved back to the same file
+    pub fn load_profiles(&mut self) {
+        let path = match Profiles::get_path() {
+            Some(path) => path,
+            None => return,
+        };
+        match Profiles::load_from(&path) {
+            Ok(profiles) => {
+                self.profiles = profiles;
+                self.profiles_path = Some(path);
+            }
+            // a broken file is left alone instead of being overwritten
+            Err(e) => self.set_error(format!("Unable to read the profiles: {}", e)),
+        }
+    }
+
+    fn save_profiles(&mut self) {
+        let result = match &self.profiles_path {
+            Some(path) => self.profiles.save_to(path),
+            None => return,
+        };
+        if let Err(e) = result {
+            self.set_error(format!("Unable to save the profiles: {}", e));
+        }
+    }
+
+    /// handle_profiles_events edits the avatar and the theme of the selected profile
+    fn handle_profiles_events(&mut self, key_code: KeyCode) {
+        let name = match self.profiles.get_profiles().get(self.selected_profile) {
+            Some(p) => p.get_name().to_string(),
+            None => String::new(),
+        };
+        let total = self.profiles.get_profiles().len();
+        let themes: Vec<Option<Theme>> = std::iter::once(None)
+            .chain(Theme::ALL.iter().copied().map(Some))
+            .collect();
+        match key_code {
+            KeyCode::Up => self.selected_profile = self.selected_profile.saturating_sub(1),
+            KeyCode::Down => {
+                self.selected_profile = (self.selected_profile + 1).min(total.saturating_sub(1))
+            }
+            KeyCode::Left | KeyCode::Right => {
+                if let Some(profile) = self.profiles.get_profile_mut(&name) {
+                    profile.avatar = cycle(&AVATARS, profile.avatar, key_code == KeyCode::Right);
+                    self.save_profiles();
                 }
-                KeyCode::Char('n') => {
-                    self.set_current_screen_new_game();
+            }
+            KeyCode::Char('t') => {
+                if let Some(profile) = self.profiles.get_profile_mut(&name) {
+                    profile.theme = cycle(&themes, profile.theme, true);
+                    self.save_profiles();
                 }
-                _ => {}
             }
+            // the statistics of the player are kept
+            KeyCode::Char('d') if self.profiles.remove(&name).is_some() => {
+                self.selected_profile = self.selected_profile.min(total.saturating_sub(2));
+                self.save_profiles();
+            }
+            KeyCode::Esc => self.go_back(),
+            KeyCode::Char('q') => self.set_exiting(),
+            _ => {}
         }
-        Ok(false)
+    }
+
+    /// get_theme returns the theme of the profile whose cards are shown, the one of the settings otherwise
+    pub fn get_theme(&self) -> Theme {
+        self.revealed_player
+            .as_deref()
+            .and_then(|name| self.profiles.get_profile(name))
+            .and_then(|p| p.theme)
+            .unwrap_or(self.config.theme)
+    }
+
+    /// add_bot_player adds a computer player to the game instead of the name being typed
+    fn add_bot_player(&mut self) {
+        let bot_name = (1..)
+            .map(|i| format!("Bot {}", i))
+            .find(|name| self.game.get_players().iter().all(|p| p.get_name() != name))
+            .expect("no free bot name");
+
+        // the pin of a bot is never asked
+        if let Err(e) = self.game.add_players(&bot_name, "") {
+            self.set_error(e.to_string());
+            return;
+        }
+        self.bot_players.push(bot_name);
+        self.start_game_if_ready();
+    }
+
+    /// start_game_if_ready asks for the next player, or starts the game once all the players are added
+    fn start_game_if_ready(&mut self) {
+        if self.game.get_players().len() < self.total_players as usize {
+            self.ask_next_player_name();
+            return;
+        }
+
+        match self.game.start() {
+            Ok(()) => {
+                self.log = GameLog::new();
+                self.log.record(LogEvent::Started {
+                    players: self
+                        .game
+                        .get_players()
+                        .iter()
+                        .map(|p| p.get_name().to_string())
+                        .collect(),
+                    rules: self.game.get_rules().clone(),
+                });
+                self.set_current_screen(CurrentScreen::Game);
+                self.next_turn();
+            }
+            Err(e) => self.set_error(e.to_string()),
+        }
+    }
+
+    /// ask_next_player_name opens the player name popup filled with the default name from the config
+    fn ask_next_player_name(&mut self) {
+        let player_idx = self.game.get_players().len();
+        self.input_buffer = match self.config.get_player_name(player_idx) {
+            Some(name) if self.game.get_players().iter().all(|p| p.get_name() != name) => {
+                name.to_string()
+            }
+            _ => String::new(),
+        };
+        self.set_popup_state(Popups::NewGamePopups(NewGamePopups::PlayerNames));
+    }
+
+    /// is_bot_turn checks if the bot has to play for the current player
+    fn is_bot_turn(&self) -> bool {
+        self.current_screen == CurrentScreen::Game
+            && self
+                .game
+                .get_current_player()
+                .is_some_and(|p| self.is_bot(p.get_name()))
+    }
+
+    /// is_timed_turn checks if a human player of the local game is playing against the clock
+    fn is_timed_turn(&self) -> bool {
+        self.current_screen == CurrentScreen::Game
+            && self.game.get_time_left().is_some()
+            && !self.is_bot_turn()
+    }
+
+    pub fn is_bot(&self, player_name: &str) -> bool {
+        self.bot_players.iter().any(|b| b == player_name)
+    }
+
+    /// play_bot_turn makes the call or throws the card for the current computer player
+    fn play_bot_turn(&mut self) {
+        let player_name = match self.game.get_current_player() {
+            Some(p) => p.get_name().to_string(),
+            None => return,
+        };
+        let result = match self.bot.choose_move(&self.game) {
+            Some(bot_move) => self.play_local_move(&player_name, bot_move),
+            None => Ok(()),
+        };
+        match result {
+            Ok(()) => self.next_turn(),
+            Err(e) => self.set_error(e.to_string()),
+        }
+    }
+
+    /// play_local_move plays the move in the local game and keeps it in the log
+    fn play_local_move(&mut self, player_name: &str, player_move: Move) -> Result<(), GameError> {
+        let event = LogEvent::play_move(&mut self.game, player_name, player_move)?;
+        self.log.record(event);
+        Ok(())
+    }
+
+    /// play_timed_out_turn makes the lowest call or throws the lowest card for the
+    /// current player once their time is up
+    fn play_timed_out_turn(&mut self) {
+        let player_name = match self.game.get_current_player() {
+            Some(p) => p.get_name().to_string(),
+            None => return,
+        };
+        let result = match self.game.get_timeout_move() {
+            Some(timeout_move) => self.play_local_move(&player_name, timeout_move),
+            None => Ok(()),
+        };
+        match result {
+            Ok(()) => {
+                self.next_turn();
+                self.set_error(format!("{} ran out of time", player_name));
+            }
+            Err(e) => self.set_error(e.to_string()),
+        }
+    }
+
+    /// push_pin_char types the character of a pin or a passphrase, up to the longest one allowed
+    fn push_pin_char(&mut self, c: char) {
+        if !c.is_control() && self.input_buffer.chars().count() < pin::MAX_PIN_LENGTH {
+            self.input_buffer.push(c);
+        }
+    }
+
+    /// reveal_current_player shows the cards of the current player if the pin matches
+    fn reveal_current_player(&mut self) {
+        let pin = std::mem::take(&mut self.input_buffer);
+        let player_name = match self.game.get_current_player() {
+            Some(p) => p.get_name().to_string(),
+            None => return,
+        };
+        // the player is locked out for a while after too many wrong pins
+        match self.game.verify_pin(&player_name, &pin) {
+            Ok(()) => {
+                self.revealed_player = Some(player_name);
+                self.set_popup_state(Popups::None);
+            }
+            Err(e) => self.set_error(e.to_string()),
+        }
+    }
+
+    /// next_turn hides the cards and asks for the pin if the turn moved on to another player
+    fn next_turn(&mut self) {
+        self.selected_card = 0;
+        if let Some(view) = self.get_view() {
+            self.selected_call = view.get_default_bid();
+        }
+
+        if self.game.get_state() == GameState::Finished {
+            self.revealed_player = None;
+            self.set_popup_state(Popups::None);
+            self.log.record(LogEvent::Finished {
+                score_board: self.game.get_score_board().clone(),
+                winner: self.game.get_game_winner(),
+            });
+            self.record_stats();
+            self.save_replay();
+            self.show_score_board();
+            return;
+        }
+
+        let current_player = self.game.get_current_player().map(|p| p.get_name());
+        // the bots don't need their cards to be revealed
+        if current_player.is_some_and(|p| self.is_bot(p)) {
+            self.set_popup_state(Popups::None);
+            return;
+        }
+        if current_player != self.revealed_player.as_deref() {
+            self.revealed_player = None;
+            self.set_popup_state(Popups::GamePopups(GamePopups::RevealHand));
+        }
+    }
+
+    /// record_stats adds the finished local game to the statistics of its human players
+    fn record_stats(&mut self) {
+        let players: Vec<String> = self
+            .game
+            .get_players()
+            .iter()
+            .map(|p| p.get_name().to_string())
+            .filter(|p| !self.is_bot(p))
+            .collect();
+        if players.is_empty() {
+            return;
+        }
+        let result = Stats::load().and_then(|mut stats| {
+            stats.record_game(&self.log, &players);
+            stats.save()
+        });
+        if let Err(e) = result {
+            self.set_error(format!("Unable to save the statistics: {}", e));
+        }
+    }
+
+    /// load_replays reads the finished games saved on this device, the new games are
+    /// saved to the same directory
+    pub fn load_replays(&mut self) {
+        if let Some(dir) = Replay::get_dir() {
+            self.load_replays_from(&dir);
+        }
+    }
+
+    fn load_replays_from(&mut self, dir: &Path) {
+        match Replay::load_all(dir) {
+            Ok(replays) => {
+                self.replays = replays;
+                self.replays_dir = Some(dir.to_path_buf());
+            }
+            Err(e) => self.set_error(format!("Unable to read the replays: {}", e)),
+        }
+    }
+
+    /// save_replay saves the log of the finished local game, so it can be watched again
+    fn save_replay(&mut self) {
+        let result = match &self.replays_dir {
+            Some(dir) => self.log.save_new(dir),
+            None => return,
+        };
+        match result {
+            Ok(_) => self.replays.insert(0, Replay::new(self.log.clone())),
+            Err(e) => self.set_error(format!("Unable to save the replay: {}", e)),
+        }
+    }
+
+    /// handle_replay_events picks a saved game and steps through the one picked
+    fn handle_replay_events(&mut self, key_code: KeyCode) {
+        match (key_code, self.replay.as_mut()) {
+            (KeyCode::Right, Some(replay)) => replay.next(),
+            (KeyCode::Left, Some(replay)) => replay.previous(),
+            // leaving a replay goes back to the list of the saved games
+            (KeyCode::Esc, Some(_)) => self.replay = None,
+            (KeyCode::Up, None) => self.selected_replay = self.selected_replay.saturating_sub(1),
+            (KeyCode::Down, None) => {
+                let last = self.replays.len().saturating_sub(1);
+                self.selected_replay = (self.selected_replay + 1).min(last);
+            }
+            (KeyCode::Enter, None) => self.replay = self.replays.get(self.selected_replay).cloned(),
+            (KeyCode::Esc, None) => self.go_back(),
+            (KeyCode::Char('q'), _) => self.set_exiting(),
+            _ => {}
+        }
+    }
+
+    /// show_statistics opens the statistics screen with the stats saved on this device
+    fn show_statistics(&mut self) {
+        match Stats::load() {
+            Ok(stats) => self.stats = stats,
+            Err(e) => {
+                self.set_error(format!("Unable to read the statistics: {}", e));
+                return;
+            }
+        }
+        self.selected_stats_player = 0;
+        self.previous_screens.push(self.current_screen);
+        self.current_screen = CurrentScreen::Statistics;
     }
 
     pub fn set_current_screen_new_game(&mut self) {
+        self.game = Game::default();
+        // the config is validated on load, so the game accepts its values
+        if let Err(e) = self.game.set_rules(self.config.get_rules()).and_then(|_| {
+            self.game.set_time_limits(TimeLimits {
+                call: self.config.call_time_limit.get_limit(),
+                throw: self.config.throw_time_limit.get_limit(),
+            })
+        }) {
+            self.set_error(e.to_string());
+        }
+        self.bot_players.clear();
+        self.total_players = 0;
+        self.input_buffer.clear();
+        self.revealed_player = None;
+        self.previous_screens.clear();
         self.current_screen = CurrentScreen::NewGame;
         self.current_popup = Popups::NewGamePopups(NewGamePopups::NumberOfPlayers);
     }
@@ -190,6 +1289,22 @@ impl<'a> State<'a> {
         self.current_screen = screen;
     }
 
+    /// show_score_board opens the score board and remembers the screen to go back to
+    pub fn show_score_board(&mut self) {
+        self.previous_screens.push(self.current_screen);
+        self.current_screen = CurrentScreen::ScoreBoard;
+    }
+
+    fn set_exiting(&mut self) {
+        self.previous_screens.push(self.current_screen);
+        self.current_screen = CurrentScreen::Exiting;
+    }
+
+    /// go_back returns to the screen that was open before the current one
+    fn go_back(&mut self) {
+        self.current_screen = self.previous_screens.pop().unwrap_or(CurrentScreen::Main);
+    }
+
     pub fn set_error(&mut self, error_str: String) {
         self.error = error_str
     }
@@ -202,3 +1317,331 @@ impl<'a> State<'a> {
         self.current_popup = popup;
     }
 }
+
+/// cycle returns the next or the previous option, wrapping around at the ends
+fn cycle<T: Copy + PartialEq>(options: &[T], current: T, forward: bool) -> T {
+    let idx = options.iter().position(|o| *o == current).unwrap_or(0);
+    if forward {
+        options[(idx + 1) % options.len()]
+    } else {
+        options[(idx + options.len() - 1) % options.len()]
+    }
+}
+
+/// step increases or decreases the value by one, staying within min and max
+fn step(value: u8, forward: bool, min: u8, max: u8) -> u8 {
+    if forward {
+        value.saturating_add(1).min(max)
+    } else {
+        value.saturating_sub(1).max(min)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::crossterm::event::KeyEvent;
+
+    // returns true once a key asked the app to exit
+    fn press(state: &mut State, keys: &[KeyCode]) -> bool {
+        keys.iter().any(|key| {
+            state
+                .handle_events(Event::Key(KeyEvent::from(*key)))
+                .unwrap()
+        })
+    }
+
+    fn type_text(state: &mut State, text: &str) {
+        let keys: Vec<KeyCode> = text.chars().map(KeyCode::Char).collect();
+        press(state, &keys);
+    }
+
+    #[test]
+    fn test_text_field_ignores_shortcuts() {
+        let mut state = State::new(Game::default(), Config::default());
+        state.set_current_screen_new_game();
+        type_text(&mut state, "2");
+        press(&mut state, &[KeyCode::Enter]);
+
+        // 'q' and 'n' are part of the name, not the quit and new game shortcuts
+        state.input_buffer.clear();
+        type_text(&mut state, "quinn");
+        assert_eq!(state.input_buffer, "quinn");
+        assert!(state.current_screen == CurrentScreen::NewGame);
+
+        press(&mut state, &[KeyCode::Enter]);
+        type_text(&mut state, "12q4");
+        assert_eq!(state.input_buffer, "12q4");
+        assert!(state.current_popup == Popups::NewGamePopups(NewGamePopups::PlayerPin));
+
+        // [Esc] leaves the wizard for the menu
+        press(&mut state, &[KeyCode::Esc]);
+        assert!(state.current_screen == CurrentScreen::Main);
+        assert!(state.input_buffer.is_empty());
+    }
+
+    #[test]
+    fn test_main_menu() {
+        let mut state = State::new(Game::default(), Config::default());
+        assert!(!state.is_menu_item_enabled(MenuItem::Continue));
+
+        // a disabled entry stays on the menu
+        press(&mut state, &[KeyCode::Down, KeyCode::Enter]);
+        assert!(state.current_screen == CurrentScreen::Main);
+        assert!(!state.error.is_empty());
+
+        press(
+            &mut state,
+            &[
+                KeyCode::Down,
+                KeyCode::Down,
+                KeyCode::Down,
+                KeyCode::Down,
+                KeyCode::Down,
+            ],
+        );
+        assert!(MenuItem::ALL[state.selected_menu_item] == MenuItem::Rules);
+        press(&mut state, &[KeyCode::Enter]);
+        assert!(state.current_screen == CurrentScreen::Rules);
+        press(&mut state, &[KeyCode::Esc]);
+        assert!(state.current_screen == CurrentScreen::Main);
+
+        press(&mut state, &[KeyCode::Down, KeyCode::Enter]);
+        assert!(state.current_screen == CurrentScreen::Exiting);
+        assert!(press(&mut state, &[KeyCode::Char('y')]));
+    }
+
+    #[test]
+    fn test_continue_game() {
+        let mut state = State::new(Game::default(), Config::default());
+        state.game.add_players("a", "1111").unwrap();
+        state.game.add_players("b", "2222").unwrap();
+        state.game.start().unwrap();
+        state.continue_game();
+        assert!(state.current_popup == Popups::GamePopups(GamePopups::RevealHand));
+
+        // leaving the game hides the hand and points the menu to Continue
+        type_text(&mut state, "2222");
+        press(&mut state, &[KeyCode::Enter]);
+        assert!(state.revealed_player.is_some());
+        press(&mut state, &[KeyCode::Esc]);
+        assert!(state.revealed_player.is_none());
+        assert!(MenuItem::ALL[state.selected_menu_item] == MenuItem::Continue);
+
+        press(&mut state, &[KeyCode::Enter]);
+        assert!(state.current_screen == CurrentScreen::Game);
+        assert!(state.current_popup == Popups::GamePopups(GamePopups::RevealHand));
+    }
+
+    #[test]
+    fn test_profiles() {
+        let mut state = State::new(Game::default(), Config::default());
+        state.profiles.add("Ayan", "1234");
+        state.set_current_screen_new_game();
+        type_text(&mut state, "2");
+        press(&mut state, &[KeyCode::Enter]);
+
+        // a saved profile is picked instead of typed, with the pin of the profile
+        press(&mut state, &[KeyCode::Down]);
+        assert_eq!(state.input_buffer, "Ayan");
+        press(&mut state, &[KeyCode::Enter]);
+        type_text(&mut state, "4321");
+        press(&mut state, &[KeyCode::Enter]);
+        assert!(!state.error.is_empty());
+        assert!(state.game.get_players().is_empty());
+        type_text(&mut state, "1234");
+        press(&mut state, &[KeyCode::Enter]);
+        assert_eq!(state.game.get_players().len(), 1);
+
+        // the seated profiles aren't offered again, a new player is saved as a profile
+        press(&mut state, &[KeyCode::Down]);
+        assert!(state.input_buffer.is_empty());
+        type_text(&mut state, "Bo");
+        press(&mut state, &[KeyCode::Enter]);
+        type_text(&mut state, "1111");
+        press(&mut state, &[KeyCode::Enter]);
+        assert!(state.current_screen == CurrentScreen::Game);
+        let bo = state.profiles.get_profile_mut("Bo").unwrap();
+        assert_eq!(bo.verify_pin("1111"), Ok(()));
+
+        // the preferred theme is used while the cards of the profile are shown
+        state.profiles.get_profile_mut("Ayan").unwrap().theme = Some(Theme::Ascii);
+        state.revealed_player = Some(String::from("Ayan"));
+        assert_eq!(state.get_theme(), Theme::Ascii);
+        state.revealed_player = Some(String::from("Bo"));
+        assert_eq!(state.get_theme(), state.config.theme);
+
+        state.open_main_menu();
+        state.select_menu_item(MenuItem::Profiles);
+        assert!(state.current_screen == CurrentScreen::Profiles);
+        press(
+            &mut state,
+            &[KeyCode::Down, KeyCode::Char('t'), KeyCode::Right],
+        );
+        let bo = state.profiles.get_profile("Bo").unwrap();
+        assert_eq!(bo.theme, Some(Theme::ALL[0]));
+        assert_eq!(bo.avatar, AVATARS[2]);
+        press(&mut state, &[KeyCode::Char('d')]);
+        assert!(state.profiles.get_profile("Bo").is_none());
+        assert_eq!(state.selected_profile, 0);
+    }
+
+    #[test]
+    fn test_reveal_lockout() {
+        let mut state = State::new(Game::default(), Config::default());
+        state.game.add_players("a", "a passphrase").unwrap();
+        state.game.add_players("b", "a passphrase").unwrap();
+        state.game.start().unwrap();
+        state.continue_game();
+
+        type_text(&mut state, "0000");
+        press(&mut state, &[KeyCode::Enter]);
+        assert!(state.error.contains("2 attempts left"));
+        // too many wrong pins lock the player out, even of the right one
+        for _ in 1..pin::MAX_ATTEMPTS {
+            type_text(&mut state, "0000");
+            press(&mut state, &[KeyCode::Enter]);
+        }
+        type_text(&mut state, "a passphrase");
+        press(&mut state, &[KeyCode::Enter]);
+        assert!(state.error.starts_with("Too many incorrect pins"));
+        assert!(state.revealed_player.is_none());
+        assert!(state.current_popup == Popups::GamePopups(GamePopups::RevealHand));
+    }
+
+    /// poll_until polls the server until the condition holds
+    fn poll_until(state: &mut State, condition: impl Fn(&State) -> bool) {
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        while !condition(state) {
+            assert!(
+                std::time::Instant::now() < deadline,
+                "no update from the server"
+            );
+            state.poll_remote();
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn test_replay() {
+        let mut state = State::new(Game::default(), Config::default());
+        assert!(!state.is_menu_item_enabled(MenuItem::LoadReplay));
+
+        // a finished game is saved as a replay and can be watched right away
+        let dir = std::env::temp_dir().join(format!("callbreak-state-{}", std::process::id()));
+        state.replays_dir = Some(dir.clone());
+        state.log.record(LogEvent::Started {
+            players: vec![String::from("a"), String::from("b")],
+            rules: state.game.get_rules().clone(),
+        });
+        for player in ["a", "b"] {
+            state.log.record(LogEvent::Redeal {
+                player: String::from(player),
+            });
+        }
+        state.save_replay();
+        state.load_replays_from(&dir);
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(state.replays.len(), 1);
+
+        state.select_menu_item(MenuItem::LoadReplay);
+        assert!(state.current_screen == CurrentScreen::Replay);
+        press(
+            &mut state,
+            &[KeyCode::Enter, KeyCode::Right, KeyCode::Right],
+        );
+        assert_eq!(state.replay.as_ref().unwrap().get_step(), (3, 3));
+        press(&mut state, &[KeyCode::Left]);
+        assert_eq!(state.replay.as_ref().unwrap().get_step(), (2, 3));
+        press(&mut state, &[KeyCode::Esc]);
+        assert!(state.replay.is_none());
+        press(&mut state, &[KeyCode::Esc]);
+        assert!(state.current_screen == CurrentScreen::Main);
+    }
+
+    #[test]
+    fn test_remote_game() {
+        use crate::net::server::{Server, ServerOptions};
+
+        let options = ServerOptions {
+            total_players: 2,
+            total_deals: 1,
+            ..ServerOptions::default()
+        };
+        let server = Server::bind("127.0.0.1:0", options).unwrap();
+        let addr = server.local_addr().unwrap().to_string();
+        std::thread::spawn(move || server.run());
+
+        // the name is asked in the lobby, typing 'q' doesn't quit
+        let mut a = State::new(Game::default(), Config::default());
+        a.start_remote(RemoteSession::connect(&addr).unwrap(), None);
+        assert!(a.current_screen == CurrentScreen::Lobby);
+        assert!(a.has_text_focus());
+        a.input_buffer.clear();
+        type_text(&mut a, "quinn");
+        press(&mut a, &[KeyCode::Enter]);
+        assert!(!a.has_text_focus());
+        poll_until(&mut a, |s| s.remote.as_ref().unwrap().seat == Some(0));
+
+        // a taken name asks for another one
+        let mut b = State::new(Game::default(), Config::default());
+        b.start_remote(
+            RemoteSession::connect(&addr).unwrap(),
+            Some(String::from("quinn")),
+        );
+        poll_until(&mut b, |s| !s.error.is_empty());
+        assert!(b.current_popup == Popups::LobbyPopups(LobbyPopups::PlayerName));
+        b.input_buffer = String::from("b");
+        press(&mut b, &[KeyCode::Enter]);
+        poll_until(&mut b, |s| s.remote.as_ref().unwrap().seat == Some(1));
+
+        // chat from the lobby
+        press(&mut b, &[KeyCode::Char('t')]);
+        type_text(&mut b, "ready?");
+        press(&mut b, &[KeyCode::Enter]);
+        poll_until(&mut a, |s| {
+            s.remote
+                .as_ref()
+                .unwrap()
+                .chat
+                .contains(&String::from("b: ready?"))
+        });
+
+        // a spectator waits in the lobby without taking a seat or chatting
+        let mut session = RemoteSession::connect(&addr).unwrap();
+        session.watching = true;
+        let mut c = State::new(Game::default(), Config::default());
+        c.start_remote(session, Some(String::from("coach")));
+        poll_until(&mut c, |s| s.remote.as_ref().unwrap().is_spectating());
+        press(&mut c, &[KeyCode::Char('t'), KeyCode::Char('r')]);
+        assert!(c.current_popup == Popups::None);
+        assert_eq!(c.remote.as_ref().unwrap().seat, None);
+
+        // the game opens once both are ready
+        press(&mut a, &[KeyCode::Char('r')]);
+        press(&mut b, &[KeyCode::Char('r')]);
+        poll_until(&mut a, |s| s.current_screen == CurrentScreen::Game);
+        poll_until(&mut b, |s| s.current_screen == CurrentScreen::Game);
+        poll_until(&mut c, |s| s.current_screen == CurrentScreen::Game);
+        let view = c.get_view().unwrap();
+        assert!(view.seat.is_none() && view.hand.is_empty());
+
+        // the call is sent to the server by the player whose turn it is
+        let (first, second) = if a.get_view().unwrap().is_my_turn() {
+            (&mut a, &mut b)
+        } else {
+            (&mut b, &mut a)
+        };
+        assert!(!second.get_view().unwrap().is_my_turn());
+        press(first, &[KeyCode::Right, KeyCode::Enter]);
+        poll_until(second, |s| s.get_view().unwrap().is_my_turn());
+        let view = second.get_view().unwrap();
+        assert!(view
+            .players
+            .iter()
+            .any(|p| p.call.map(|c| c.get_value()) == Some(view.call_range.0 + 1)));
+        // there is no local menu for a hosted game
+        press(second, &[KeyCode::Esc]);
+        assert!(second.current_screen == CurrentScreen::Game);
+    }
+}
@@ -0,0 +1,140 @@
+use ratatui::{
+    style::{Color, Style},
+    text::Span,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::card::{Card, CardStyle, Suit};
+
+/// Theme decides how the cards are printed and the colors used by the screens
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Theme {
+    // emoji suits, these can misalign in terminals without emoji support
+    #[default]
+    Emoji,
+    // plain unicode suits without the variation selector
+    Unicode,
+    // letters for the suits, e.g. AS or 10H
+    Ascii,
+    // colorblind friendly deck where every suit has its own color
+    FourColor,
+}
+
+/// Palette holds the colors used across the screens
+pub struct Palette {
+    pub text: Color,
+    pub popup: Color,
+    pub input: Color,
+    pub input_text: Color,
+    pub body: Color,
+    pub footer: Color,
+    pub highlight: Color,
+    pub success: Color,
+    pub warning: Color,
+    pub error: Color,
+}
+
+impl Theme {
+    pub const ALL: [Theme; 4] = [Theme::Emoji, Theme::Unicode, Theme::Ascii, Theme::FourColor];
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Theme::Emoji => "Emoji",
+            Theme::Unicode => "Unicode",
+            Theme::Ascii => "ASCII",
+            Theme::FourColor => "Four Color",
+        }
+    }
+
+    pub fn get_card_style(&self) -> CardStyle {
+        match self {
+            Theme::Emoji => CardStyle::Emoji,
+            Theme::Unicode | Theme::FourColor => CardStyle::Unicode,
+            Theme::Ascii => CardStyle::Ascii,
+        }
+    }
+
+    pub fn get_suit_color(&self, suit: Suit) -> Color {
+        match (self, suit) {
+            (Theme::FourColor, Suit::Hearts) => Color::Red,
+            (Theme::FourColor, Suit::Diamonds) => Color::LightBlue,
+            (Theme::FourColor, Suit::Club) => Color::Green,
+            (Theme::FourColor, Suit::Spade) => Color::White,
+            // the classic red and black deck, with a lighter shade for the second suit of a color
+            (_, Suit::Hearts) => Color::Red,
+            (_, Suit::Diamonds) => Color::LightRed,
+            (_, Suit::Spade) => Color::White,
+            (_, Suit::Club) => Color::Gray,
+        }
+    }
+
+    pub fn get_palette(&self) -> Palette {
+        let palette = Palette {
+            text: Color::White,
+            popup: Color::Blue,
+            input: Color::Yellow,
+            input_text: Color::Black,
+            body: Color::Cyan,
+            footer: Color::LightBlue,
+            highlight: Color::Yellow,
+            success: Color::Green,
+            warning: Color::LightRed,
+            error: Color::Red,
+        };
+        match self {
+            // avoid telling the states apart only by red and green
+            Theme::FourColor => Palette {
+                success: Color::LightCyan,
+                warning: Color::LightMagenta,
+                error: Color::Magenta,
+                ..palette
+            },
+            _ => palette,
+        }
+    }
+
+    /// card_span returns the styled card to be shown on the table or in the hand
+    pub fn card_span(&self, card: &Card) -> Span<'static> {
+        Span::styled(
+            card.get_display_str(self.get_card_style()),
+            Style::default().fg(self.get_suit_color(card.get_suit())),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::config::Config;
+
+    #[test]
+    fn test_suit_colors() {
+        let suits = [Suit::Hearts, Suit::Diamonds, Suit::Club, Suit::Spade];
+        for theme in Theme::ALL {
+            let colors: Vec<Color> = suits.iter().map(|s| theme.get_suit_color(*s)).collect();
+            for (i, color) in colors.iter().enumerate() {
+                assert!(
+                    !colors[i + 1..].contains(color),
+                    "{} shares a suit color",
+                    theme.get_name()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_theme_config_round_trip() {
+        for theme in Theme::ALL {
+            let config = Config {
+                theme,
+                ..Config::default()
+            };
+            let content = toml::to_string_pretty(&config).unwrap();
+            assert_eq!(toml::from_str::<Config>(&content).unwrap().theme, theme);
+        }
+        let config: Config = toml::from_str("theme = \"unicode\"").unwrap();
+        assert_eq!(config.theme, Theme::Unicode);
+        assert!(toml::from_str::<Config>("theme = \"neon\"").is_err());
+    }
+}
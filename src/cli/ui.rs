@@ -1,15 +1,46 @@
-use color_eyre::owo_colors::OwoColorize;
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
-    style::{Color, Style, Stylize},
+    style::{Modifier, Style, Stylize},
     text::{Line, Span},
-    widgets::{block::Title, Block, Borders, Clear, Padding, Paragraph, Wrap},
+    widgets::{block::Title, Block, Borders, Cell, Clear, Paragraph, Row, Table, Wrap},
     Frame,
 };
 
-use super::state::{CurrentScreen, NewGamePopups, Popups, State};
+use crate::card::{Card, Suit};
+use crate::game::{GameState, Round};
+use crate::rules::Reshuffle;
+use crate::score::Overtricks;
+use crate::view::{GameView, PlayerView};
+
+use super::state::{
+    CurrentScreen, GamePopups, LobbyPopups, MenuItem, NewGamePopups, Popups, Setting,
+    SettingsPopups, State,
+};
+
+// below this size the screens can't be drawn without overlapping
+pub const MIN_WIDTH: u16 = 40;
+pub const MIN_HEIGHT: u16 = 20;
+// below this width the screens switch to the single column layout
+pub const COMPACT_WIDTH: u16 = 80;
+// the countdown of the turn turns to the warning colour from here
+const TIME_WARNING_SECONDS: u64 = 5;
+
+/// is_compact tells if the screens should use the single column layout
+fn is_compact(area: Rect) -> bool {
+    area.width < COMPACT_WIDTH
+}
+
+fn is_too_small(area: Rect) -> bool {
+    area.width < MIN_WIDTH || area.height < MIN_HEIGHT
+}
 
 pub fn draw_main_screen(frame: &mut Frame, state: &mut State) {
+    let palette = state.get_theme().get_palette();
+    if is_too_small(frame.area()) {
+        draw_too_small_notice(frame, state);
+        return;
+    }
+    let compact = is_compact(frame.area());
     let layouts = Layout::default()
         .direction(Direction::Vertical)
         .constraints([Constraint::Min(2), Constraint::Length(3)].as_ref())
@@ -18,60 +49,109 @@ pub fn draw_main_screen(frame: &mut Frame, state: &mut State) {
     let title = Title::from("  Callbreak  ".bold());
     let sub_title = Title::from(" By Ayan Banerjee ");
 
-    let header_block = Block::bordered()
+    let mut header_block = Block::bordered()
         .title(title.alignment(Alignment::Center))
-        .title(sub_title.alignment(Alignment::Right))
         .style(Style::default())
         .borders(Borders::ALL);
+    if !compact {
+        header_block = header_block.title(sub_title.alignment(Alignment::Right));
+    }
 
+    let body = header_block.inner(layouts[0]);
     frame.render_widget(header_block, layouts[0]);
 
     // if new game then show a popup to add new players
     // if game is running then show the game screen
-    if state.current_screen == CurrentScreen::NewGame {
-        match state.current_popup {
+    match state.current_screen {
+        CurrentScreen::Main => draw_main_menu(frame, state, body),
+        CurrentScreen::NewGame => match state.current_popup {
             Popups::NewGamePopups(NewGamePopups::NumberOfPlayers) => {
                 draw_new_game_popup(frame, state)
             }
-            Popups::NewGamePopups(NewGamePopups::PlayerNames) => {
+            Popups::NewGamePopups(NewGamePopups::PlayerNames)
+            | Popups::NewGamePopups(NewGamePopups::PlayerPin) => {
                 draw_new_game_popup_with_player(frame, state)
             }
             _ => {}
+        },
+        CurrentScreen::Game => {
+            if let Some(view) = state.get_view() {
+                draw_game_screen(frame, state, &view, body);
+            }
+            if state.current_popup == Popups::GamePopups(GamePopups::RevealHand) {
+                draw_reveal_hand_popup(frame, state);
+            }
         }
-        draw_new_game_popup(frame, state);
+        CurrentScreen::ScoreBoard => {
+            if let Some(view) = state.get_view() {
+                draw_score_board(frame, state, &view, body);
+            }
+        }
+        CurrentScreen::Settings => draw_settings_screen(frame, state, body),
+        CurrentScreen::Rules => draw_rules_screen(frame, state, body),
+        CurrentScreen::Statistics => draw_statistics_screen(frame, state, body),
+        CurrentScreen::Profiles => draw_profiles_screen(frame, state, body),
+        CurrentScreen::Replay => draw_replay_screen(frame, state, body),
+        CurrentScreen::Lobby => {
+            draw_lobby_screen(frame, state, body);
+            match state.current_popup {
+                Popups::LobbyPopups(LobbyPopups::PlayerName) => {
+                    draw_text_popup(frame, state, " Join Game ", "Enter your name")
+                }
+                Popups::LobbyPopups(LobbyPopups::Chat) => {
+                    draw_text_popup(frame, state, " Chat ", "Message")
+                }
+                _ => {}
+            }
+        }
+        CurrentScreen::Exiting => {}
     }
 
     // footer section with options
     let footer_options = vec![
         match state.current_screen {
-            CurrentScreen::NewGame => Span::styled("New Game", Style::default().fg(Color::Green)),
-            CurrentScreen::Main => Span::styled("Main Screen", Style::default().fg(Color::Yellow)),
-            CurrentScreen::Exiting => Span::styled("Exiting", Style::default().fg(Color::LightRed)),
+            CurrentScreen::NewGame => {
+                Span::styled("New Game", Style::default().fg(palette.success))
+            }
+            CurrentScreen::Main => {
+                Span::styled("Main Screen", Style::default().fg(palette.highlight))
+            }
+            // a lost connection to the hosted game is shown in place of the screen name
+            CurrentScreen::Game | CurrentScreen::Lobby
+                if state.remote.as_ref().is_some_and(|r| !r.is_connected()) =>
+            {
+                Span::styled("Reconnecting", Style::default().fg(palette.warning))
+            }
+            CurrentScreen::Game => Span::styled("Game", Style::default().fg(palette.success)),
+            CurrentScreen::Lobby => Span::styled("Lobby", Style::default().fg(palette.success)),
+            CurrentScreen::ScoreBoard => {
+                Span::styled("Scoreboard", Style::default().fg(palette.highlight))
+            }
+            CurrentScreen::Settings => {
+                Span::styled("Settings", Style::default().fg(palette.highlight))
+            }
+            CurrentScreen::Rules => Span::styled("Rules", Style::default().fg(palette.highlight)),
+            CurrentScreen::Statistics => {
+                Span::styled("Statistics", Style::default().fg(palette.highlight))
+            }
+            CurrentScreen::Profiles => {
+                Span::styled("Profiles", Style::default().fg(palette.highlight))
+            }
+            CurrentScreen::Replay => Span::styled("Replay", Style::default().fg(palette.highlight)),
+            CurrentScreen::Exiting => Span::styled("Exiting", Style::default().fg(palette.warning)),
         }
         .to_owned(),
         // A white divider bar to separate the two sections
-        Span::styled(" | ", Style::default().fg(Color::White)),
+        Span::styled(" | ", Style::default().fg(palette.text)),
         // options
-        {
-            match state.current_screen {
-                CurrentScreen::NewGame => Span::styled(
-                    "(q) to quit / [Enter] to Select",
-                    Style::default().fg(Color::White),
-                ),
-                CurrentScreen::Main => Span::styled(
-                    "(q) to quit / (n) to start new game",
-                    Style::default().fg(Color::White),
-                ),
-                CurrentScreen::Exiting => Span::styled(
-                    "(y) for Yes / (n) for No",
-                    Style::default().fg(Color::White),
-                ),
-            }
-        },
+        Span::styled(
+            get_footer_options(state, compact),
+            Style::default().fg(palette.text),
+        ),
     ];
 
     let footer = Paragraph::new(Line::from(footer_options))
-        .style(Style::default().fg(Color::LightBlue))
+        .style(Style::default().fg(palette.footer))
         .block(Block::default().borders(Borders::ALL));
 
     frame.render_widget(footer, layouts[1]);
@@ -81,13 +161,472 @@ pub fn draw_main_screen(frame: &mut Frame, state: &mut State) {
     }
 }
 
+/// get_footer_options returns the keys of the screen, shortened for the compact layout
+fn get_footer_options(state: &State, compact: bool) -> &'static str {
+    // the shortcuts are off while a text field has the focus
+    if state.has_text_focus() {
+        return match (state.current_screen, compact) {
+            (CurrentScreen::Settings, false) => "[Enter] to save / [Esc] to cancel",
+            (CurrentScreen::Settings, true) => "⏎ save · Esc cancel",
+            (CurrentScreen::Game, false) => "[Enter] to reveal the hand / [Esc] for the menu",
+            (CurrentScreen::Game, true) => "⏎ reveal · Esc menu",
+            (CurrentScreen::Lobby, false) => "[Enter] to send / [Esc] to cancel",
+            (CurrentScreen::Lobby, true) => "⏎ send · Esc cancel",
+            (_, false) => "[Enter] to Select / [Esc] for the menu",
+            (_, true) => "⏎ select · Esc menu",
+        };
+    }
+    // the spectators can only look around
+    if state.remote.as_ref().is_some_and(|r| r.is_spectating()) {
+        return match (state.current_screen, compact) {
+            (CurrentScreen::Game, false) => "(q) to quit / (s) for scores",
+            (CurrentScreen::Game, true) => "q quit · s scores",
+            (CurrentScreen::Lobby, false) => "(q) to quit / waiting for the game to start",
+            (CurrentScreen::Lobby, true) => "q quit",
+            (CurrentScreen::ScoreBoard, false) => "(q) to quit / (s) or [Esc] to go back",
+            (CurrentScreen::ScoreBoard, true) => "q quit · Esc back",
+            (_, false) => "(q) to quit",
+            (_, true) => "q quit",
+        };
+    }
+    match (state.current_screen, compact) {
+        (CurrentScreen::NewGame, false) => "[Enter] to Select / [Esc] for the menu",
+        (CurrentScreen::NewGame, true) => "⏎ select · Esc menu",
+        (CurrentScreen::Main, false) => "(q) to quit / [↑↓] to choose / [Enter] to Select",
+        (CurrentScreen::Main, true) => "q quit · ↑↓ choose · ⏎ select",
+        (CurrentScreen::Game, false) => {
+            "(q) to quit / [←→] to choose / [Enter] to Select / (s) for scores / [Esc] for menu"
+        }
+        (CurrentScreen::Game, true) => "q quit · ←→ choose · ⏎ select · s scores",
+        (CurrentScreen::Lobby, false) => "(q) to quit / (r) to toggle ready / (t) to chat",
+        (CurrentScreen::Lobby, true) => "q quit · r ready · t chat",
+        (CurrentScreen::ScoreBoard, false) => "(q) to quit / (s) or [Esc] to go back",
+        (CurrentScreen::ScoreBoard, true) => "q quit · Esc back",
+        (CurrentScreen::Settings, false) => {
+            "(q) to quit / [↑↓] to select / [←→] to change / [Esc] to go back"
+        }
+        (CurrentScreen::Settings, true) => "↑↓ select · ←→ change · Esc back",
+        (CurrentScreen::Rules, false) => "(q) to quit / [↑↓] to scroll / [Esc] to go back",
+        (CurrentScreen::Rules, true) => "q quit · ↑↓ scroll · Esc back",
+        (CurrentScreen::Statistics, false) => "(q) to quit / [↑↓] to choose / [Esc] to go back",
+        (CurrentScreen::Statistics, true) => "q quit · ↑↓ choose · Esc back",
+        (CurrentScreen::Profiles, false) => {
+            "(q) to quit / [↑↓] to choose / [←→] for the avatar / (t) for the theme / (d) to delete"
+        }
+        (CurrentScreen::Profiles, true) => "←→ avatar · t theme · d delete",
+        (CurrentScreen::Replay, false) if state.replay.is_some() => {
+            "(q) to quit / [←→] to step / [Esc] for the saved games"
+        }
+        (CurrentScreen::Replay, true) if state.replay.is_some() => "q quit · ←→ step · Esc back",
+        (CurrentScreen::Replay, false) => {
+            "(q) to quit / [↑↓] to choose / [Enter] to watch / [Esc] to go back"
+        }
+        (CurrentScreen::Replay, true) => "q quit · ↑↓ choose · ⏎ watch · Esc back",
+        (CurrentScreen::Exiting, _) => "(y) for Yes / (n) for No",
+    }
+}
+
+/// draw_main_menu shows the menu entries in a box at the center of the body
+fn draw_main_menu(frame: &mut Frame, state: &State, rect: Rect) {
+    let palette = state.get_theme().get_palette();
+    let menu_lines: Vec<Line> = MenuItem::ALL
+        .iter()
+        .enumerate()
+        .map(|(i, item)| {
+            let is_selected = i == state.selected_menu_item;
+            let line = format!(
+                "{} {}",
+                if is_selected { "▶" } else { " " },
+                item.get_name()
+            );
+            let style = if !state.is_menu_item_enabled(*item) {
+                Style::default().add_modifier(Modifier::DIM)
+            } else if is_selected {
+                Style::default().fg(palette.highlight).bold()
+            } else {
+                Style::default()
+            };
+            Line::styled(line, style)
+        })
+        .collect();
+
+    let menu_rect = popup_rect(0, 24, MenuItem::ALL.len() as u16 + 2, rect);
+    frame.render_widget(
+        Paragraph::new(menu_lines)
+            .block(Block::bordered().title(Title::from(" Menu ").alignment(Alignment::Center))),
+        menu_rect,
+    );
+}
+
+/// draw_statistics_screen lists the players with saved statistics and shows the
+/// details of the selected one
+fn draw_statistics_screen(frame: &mut Frame, state: &State, rect: Rect) {
+    let palette = state.get_theme().get_palette();
+    let players = state.stats.get_players();
+    if players.is_empty() {
+        frame.render_widget(
+            Paragraph::new("No games played yet, finish a game to see the statistics")
+                .wrap(Wrap { trim: true })
+                .block(Block::bordered().title(" Statistics ")),
+            rect,
+        );
+        return;
+    }
+
+    let chunks = if is_compact(frame.area()) {
+        Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(players.len() as u16 + 2),
+                Constraint::Min(0),
+            ])
+            .split(rect)
+    } else {
+        Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Length(24), Constraint::Min(0)])
+            .split(rect)
+    };
+
+    let player_lines: Vec<Line> = players
+        .iter()
+        .enumerate()
+        .map(|(i, (name, _))| {
+            if i == state.selected_stats_player {
+                Line::styled(
+                    format!("▶ {}", name),
+                    Style::default().fg(palette.highlight).bold(),
+                )
+            } else {
+                Line::from(format!("  {}", name))
+            }
+        })
+        .collect();
+    frame.render_widget(
+        Paragraph::new(player_lines).block(Block::bordered().title(" Players ")),
+        chunks[0],
+    );
+
+    let (name, stats) = players[state.selected_stats_player.min(players.len() - 1)];
+    let percent = |value: f32| format!("{:.0}%", value * 100.0);
+    let mut lines = vec![
+        Line::from(format!("Games played    {}", stats.games_played)),
+        Line::from(format!("Games won       {}", stats.wins)),
+        Line::from(format!("Average score   {:.1}", stats.get_average_score())),
+        Line::from(format!(
+            "Calls made      {} of {} ({})",
+            stats.calls_made,
+            stats.deals_played,
+            percent(stats.get_call_accuracy())
+        )),
+        Line::from(format!(
+            "Overtricks      {:.2} a deal",
+            stats.get_overtrick_rate()
+        )),
+        Line::from(format!(
+            "Trumps thrown   {}",
+            percent(stats.get_trump_rate())
+        )),
+        Line::from(format!(
+            "Win streak      {} (best {})",
+            stats.current_streak, stats.longest_streak
+        )),
+        Line::from(format!("Rating          {}", get_rating_text(state, name))),
+        Line::from(""),
+        Line::styled(
+            "Head to head (won-lost-tied)",
+            Style::default().fg(palette.highlight).bold(),
+        ),
+    ];
+    lines.extend(stats.head_to_head.iter().map(|(opponent, record)| {
+        Line::from(format!(
+            "{:<16}{}-{}-{}",
+            opponent, record.wins, record.losses, record.ties
+        ))
+    }));
+    frame.render_widget(
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .block(Block::bordered().title(format!(" {} ", name))),
+        chunks[1],
+    );
+}
+
+/// draw_replay_screen lists the saved games, or the events of the picked one up to the
+/// current step with the latest event at the bottom
+fn draw_replay_screen(frame: &mut Frame, state: &State, rect: Rect) {
+    let palette = state.get_theme().get_palette();
+    let replay = match &state.replay {
+        Some(replay) => replay,
+        None => {
+            let lines: Vec<Line> = state
+                .replays
+                .iter()
+                .enumerate()
+                .map(|(i, replay)| {
+                    if i == state.selected_replay {
+                        Line::styled(
+                            format!("▶ {}", replay.get_name()),
+                            Style::default().fg(palette.highlight).bold(),
+                        )
+                    } else {
+                        Line::from(format!("  {}", replay.get_name()))
+                    }
+                })
+                .collect();
+            frame.render_widget(
+                Paragraph::new(lines).block(Block::bordered().title(" Saved Games ")),
+                rect,
+            );
+            return;
+        }
+    };
+
+    let events = replay.get_events();
+    let shown = rect.height.saturating_sub(2) as usize;
+    let lines: Vec<Line> = events
+        .iter()
+        .enumerate()
+        .skip(events.len().saturating_sub(shown))
+        .map(|(i, entry)| {
+            if i + 1 == events.len() {
+                Line::styled(
+                    entry.event.to_string(),
+                    Style::default().fg(palette.highlight).bold(),
+                )
+            } else {
+                Line::from(entry.event.to_string())
+            }
+        })
+        .collect();
+    let (step, total) = replay.get_step();
+    frame.render_widget(
+        Paragraph::new(lines).block(Block::bordered().title(format!(
+            " {} ({}/{}) ",
+            replay.get_name(),
+            step,
+            total
+        ))),
+        rect,
+    );
+}
+
+/// draw_profiles_screen lists the saved profiles with their preferred theme and linked statistics
+fn draw_profiles_screen(frame: &mut Frame, state: &State, rect: Rect) {
+    let palette = state.get_theme().get_palette();
+    let profiles = state.profiles.get_profiles();
+    if profiles.is_empty() {
+        frame.render_widget(
+            Paragraph::new("No profiles yet, the players of a new game are saved as profiles")
+                .wrap(Wrap { trim: true })
+                .block(Block::bordered().title(" Profiles ")),
+            rect,
+        );
+        return;
+    }
+
+    let compact = is_compact(frame.area());
+    let lines: Vec<Line> = profiles
+        .iter()
+        .enumerate()
+        .map(|(i, profile)| {
+            let theme = profile.theme.map_or("Settings", |t| t.get_name());
+            let stats = match profile.get_stats(&state.stats) {
+                Some(stats) => format!(
+                    "{} games, {} won, rating {:.0}",
+                    stats.games_played,
+                    stats.wins,
+                    profile.get_rating(&state.stats).value
+                ),
+                None => String::from("no games yet"),
+            };
+            let text = if compact {
+                format!("{:<16} {}", profile.get_label(), theme)
+            } else {
+                format!("{:<20} {:<12} {}", profile.get_label(), theme, stats)
+            };
+            if i == state.selected_profile {
+                Line::styled(
+                    format!("▶ {}", text),
+                    Style::default().fg(palette.highlight).bold(),
+                )
+            } else {
+                Line::from(format!("  {}", text))
+            }
+        })
+        .collect();
+    frame.render_widget(
+        Paragraph::new(lines).block(Block::bordered().title(" Profiles ")),
+        rect,
+    );
+}
+
+/// get_rating_text returns the rating of the player with their place among the rated
+/// players and bots
+fn get_rating_text(state: &State, name: &str) -> String {
+    let ranking = state.stats.get_ratings().get_ranking();
+    match ranking.iter().position(|(n, _)| *n == name) {
+        Some(place) => format!(
+            "{:.0} (#{} of {})",
+            ranking[place].1.value,
+            place + 1,
+            ranking.len()
+        ),
+        None => String::from("-"),
+    }
+}
+
+/// draw_rules_screen explains how the game is played, with the rules from the settings
+fn draw_rules_screen(frame: &mut Frame, state: &State, rect: Rect) {
+    let palette = state.get_theme().get_palette();
+    let config = &state.config;
+    let game_rules = config.get_rules();
+    let trump = game_rules
+        .trump_suit
+        .get_symbol(config.theme.get_card_style());
+    let heading =
+        |text: &'static str| Line::styled(text, Style::default().fg(palette.highlight).bold());
+    let reshuffle = match game_rules.reshuffle {
+        Reshuffle::Never => "",
+        Reshuffle::NoTrump => " The cards are shuffled again if a hand has no trump.",
+        Reshuffle::NoFaceCard => " The cards are shuffled again if a hand has no face card.",
+        Reshuffle::NoTrumpOrFaceCard => {
+            " The cards are shuffled again if a hand has no trump or no face card."
+        }
+    };
+    let redeal = match game_rules.redeal {
+        Reshuffle::Never => String::new(),
+        weak_hand => format!(
+            " A player whose hand has {} may ask for a redeal instead, up to {} times a deal.",
+            match weak_hand {
+                Reshuffle::NoTrump => "no trump",
+                Reshuffle::NoFaceCard => "no face card",
+                _ => "no trump or no face card",
+            },
+            game_rules.max_redeals
+        ),
+    };
+    let nil_bid = match game_rules.get_nil_bid() {
+        Some(nil_bid) => format!(
+            " A player may also call nil, to win no round at all: it scores {} if no round is \
+             won and minus {} otherwise.",
+            nil_bid.bonus, nil_bid.penalty
+        ),
+        None => String::new(),
+    };
+    let hook = if game_rules.hook {
+        " The dealer calls last and can't make the calls add up to the number of rounds, \
+         so someone is bound to fail."
+    } else {
+        ""
+    };
+    let min_total_call = match game_rules.min_total_call {
+        0 => String::new(),
+        total => format!(
+            " The calls have to add up to at least {}, the dealer makes up for the rest.",
+            total
+        ),
+    };
+    let partnership = if game_rules.partnership {
+        " In a partnership the opposite players play as a team: their calls add up to the \
+         contract of the team and their rounds count together."
+    } else {
+        ""
+    };
+    let overtake = if game_rules.must_overtake {
+        " The winning card has to be beaten whenever possible."
+    } else {
+        ""
+    };
+    let rules = vec![
+        Line::styled(
+            format!("{} rules", config.rules.get_name()),
+            Style::default().fg(palette.highlight).bold(),
+        ),
+        Line::from(""),
+        heading("Dealing"),
+        Line::from(format!(
+            "The deck is dealt equally among the players, starting next to the dealer. With 3 \
+             or 5 players the lowest cards are left out so that every hand is even. The dealer \
+             moves on to the next player after every deal.{}",
+            reshuffle
+        )),
+        Line::from(""),
+        heading("Calling"),
+        Line::from(format!(
+            "Every player calls the number of rounds they expect to win, between {} and {}.{}{}{}{}",
+            config.min_call, config.max_call, nil_bid, hook, min_total_call, redeal
+        )),
+        Line::from(""),
+        heading("Playing"),
+        Line::from(format!(
+            "The player next to the dealer leads the first round. Everyone has to follow the \
+             suit of the lead card, if they can't they have to play a trump ({}), and only \
+             then any other card.{}",
+            trump, overtake
+        )),
+        Line::from(
+            "The highest trump wins the round, or the highest card of the lead suit if no trump \
+             was played. The winner leads the next round.",
+        ),
+        Line::from(""),
+        heading("Scoring"),
+        Line::from(match game_rules.overtricks {
+            Overtricks::Tenths => {
+                "Winning at least the called rounds scores the call, plus 0.1 for every extra \
+                 round. Winning fewer rounds scores minus the call."
+            }
+            Overtricks::Ignored => {
+                "Winning at least the called rounds scores the call, the extra rounds don't \
+                 count. Winning fewer rounds scores minus the call."
+            }
+        }),
+        Line::from(format!(
+            "The player with the highest total after {} deals wins the game.{}",
+            config.total_deals, partnership
+        )),
+    ];
+    frame.render_widget(
+        Paragraph::new(rules)
+            .wrap(Wrap { trim: true })
+            .scroll((state.rules_scroll, 0))
+            .block(Block::bordered().title(" Rules ")),
+        rect,
+    );
+}
+
+/// draw_too_small_notice replaces the screens when the terminal can't fit them
+fn draw_too_small_notice(frame: &mut Frame, state: &State) {
+    let palette = state.get_theme().get_palette();
+    let area = frame.area();
+    let notice = Paragraph::new(vec![
+        Line::styled(
+            "Terminal too small",
+            Style::default().fg(palette.warning).bold(),
+        ),
+        Line::from(format!(
+            "Resize to at least {}x{} (now {}x{})",
+            MIN_WIDTH, MIN_HEIGHT, area.width, area.height
+        )),
+    ])
+    .alignment(Alignment::Center)
+    .wrap(Wrap { trim: true });
+    // vertically centered as far as the lines fit
+    let top = area.height.saturating_sub(2) / 2;
+    frame.render_widget(
+        notice,
+        Rect::new(area.x, area.y + top, area.width, area.height - top),
+    );
+}
+
 fn draw_new_game_popup(frame: &mut Frame, state: &mut State) {
+    let palette = state.get_theme().get_palette();
     frame.render_widget(Clear, frame.area());
-    let popup_layout = centered_rect(50, 40, frame.area());
+    let popup_layout = popup_rect(50, MIN_WIDTH, NEW_GAME_POPUP_HEIGHT, frame.area());
     let popup = Block::default()
         .title(Title::from(" New Game ").alignment(Alignment::Center))
         .borders(Borders::NONE)
-        .style(Style::default().fg(Color::White).bg(Color::Blue));
+        .style(Style::default().fg(palette.text).bg(palette.popup));
 
     frame.render_widget(popup, popup_layout);
 
@@ -104,14 +643,18 @@ fn draw_new_game_popup(frame: &mut Frame, state: &mut State) {
         )
         .split(popup_layout);
 
-    let header = Title::from("Enter the number of players to be playing: ");
+    let header = Title::from(if is_compact(frame.area()) {
+        "Number of players: "
+    } else {
+        "Enter the number of players to be playing: "
+    });
 
     let header_block = Block::default()
         .title(header)
-        .style(Style::default().bg(Color::Yellow).fg(Color::White));
+        .style(Style::default().bg(palette.input).fg(palette.text));
 
     let header_body = Paragraph::new(state.input_buffer.clone())
-        .style(Style::new().bold().fg(Color::Black))
+        .style(Style::new().bold().fg(palette.input_text))
         .block(header_block)
         .wrap(Wrap { trim: true });
 
@@ -119,33 +662,38 @@ fn draw_new_game_popup(frame: &mut Frame, state: &mut State) {
 
     let body = Block::default()
         .borders(Borders::ALL)
-        .style(Style::default().fg(Color::White).bg(Color::Cyan));
+        .style(Style::default().fg(palette.text).bg(palette.body));
 
     frame.render_widget(body, popup_chunks[1]);
 
     let footer = Paragraph::new(Line::from("Press 'q' to exit"))
         .block(Block::default().borders(Borders::ALL))
-        .style(Style::default().fg(Color::White));
+        .style(Style::default().fg(palette.text));
 
     frame.render_widget(footer, popup_chunks[2]);
 }
 
+// margins, the input, the added players and the footer of the new game popups
+const NEW_GAME_POPUP_HEIGHT: u16 = 13;
+
 fn draw_error_modal(frame: &mut Frame, state: &State, rect: Rect) {
+    let palette = state.get_theme().get_palette();
     frame.render_widget(Clear, rect);
     let error_modal = Paragraph::new(state.error.to_owned())
         .block(Block::default().borders(Borders::ALL))
-        .bg(Color::Red);
+        .bg(palette.error);
 
     frame.render_widget(error_modal, rect);
 }
 
 fn draw_new_game_popup_with_player(frame: &mut Frame, state: &mut State) {
+    let palette = state.get_theme().get_palette();
     frame.render_widget(Clear, frame.area());
-    let popup_layout = centered_rect(50, 40, frame.area());
+    let popup_layout = popup_rect(50, MIN_WIDTH, NEW_GAME_POPUP_HEIGHT, frame.area());
     let popup = Block::default()
         .title(Title::from(" New Game ").alignment(Alignment::Center))
         .borders(Borders::NONE)
-        .style(Style::default().fg(Color::White).bg(Color::Blue));
+        .style(Style::default().fg(palette.text).bg(palette.popup));
 
     frame.render_widget(popup, popup_layout);
 
@@ -162,51 +710,767 @@ fn draw_new_game_popup_with_player(frame: &mut Frame, state: &mut State) {
         )
         .split(popup_layout);
 
-    let header = Title::from("Enter the name of the player: ");
+    // the pin is masked while it's being typed
+    let (header, input) = if state.current_popup == Popups::NewGamePopups(NewGamePopups::PlayerPin)
+    {
+        let profile = state.profiles.get_profile(&state.player_name);
+        (
+            Title::from(match profile {
+                Some(profile) => format!("Pin of {}: ", profile.get_label()),
+                None if is_compact(frame.area()) => format!("Pin for {}: ", state.player_name),
+                None => format!("Enter a pin or passphrase for {}: ", state.player_name),
+            }),
+            "*".repeat(state.input_buffer.len()),
+        )
+    } else {
+        (
+            Title::from(format!(
+                "Enter the name of the player {}: ",
+                state.game.get_players().len() + 1
+            )),
+            state.input_buffer.clone(),
+        )
+    };
 
     let header_block = Block::default()
         .title(header)
-        .style(Style::default().bg(Color::Yellow).fg(Color::White));
+        .style(Style::default().bg(palette.input).fg(palette.text));
 
-    let header_body = Paragraph::new(state.input_buffer.clone())
-        .style(Style::new().bold().fg(Color::Black))
+    let header_body = Paragraph::new(input)
+        .style(Style::new().bold().fg(palette.input_text))
         .block(header_block)
         .wrap(Wrap { trim: true });
 
     frame.render_widget(header_body, popup_chunks[0]);
 
-    let body = Block::default()
-        .borders(Borders::ALL)
-        .style(Style::default().fg(Color::White).bg(Color::Cyan));
+    let added_players: Vec<String> = state
+        .game
+        .get_players()
+        .iter()
+        .map(|p| match state.profiles.get_profile(p.get_name()) {
+            Some(profile) => profile.get_label(),
+            None => p.get_name().to_string(),
+        })
+        .collect();
+    let body = Paragraph::new(format!("Players: {}", added_players.join(", ")))
+        .block(Block::default().borders(Borders::ALL))
+        .style(Style::default().fg(palette.text).bg(palette.body))
+        .wrap(Wrap { trim: true });
 
     frame.render_widget(body, popup_chunks[1]);
 
-    let footer = Paragraph::new(Line::from("Press 'q' to exit"))
+    let footer_text = if state.current_popup == Popups::NewGamePopups(NewGamePopups::PlayerNames) {
+        "Press [↑↓] for a profile / [Tab] to add a bot"
+    } else {
+        "Press 'q' to exit"
+    };
+    let footer = Paragraph::new(Line::from(footer_text))
         .block(Block::default().borders(Borders::ALL))
-        .style(Style::default().fg(Color::White));
+        .style(Style::default().fg(palette.text));
 
     frame.render_widget(footer, popup_chunks[2]);
 }
 
-/// helper function to create a centered rect using up certain percentage of the available rect `r`
-fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
-    // Cut the given rectangle into three vertical pieces
-    let popup_layout = Layout::default()
+fn draw_game_screen(frame: &mut Frame, state: &State, view: &GameView, rect: Rect) {
+    let hand_lines = get_hand_lines(state, view, rect.width.saturating_sub(2));
+    let game_chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Percentage((100 - percent_y) / 2),
-            Constraint::Percentage(percent_y),
-            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Length(1),
+            Constraint::Min(4),
+            Constraint::Length(hand_lines.len().max(3) as u16 + 2),
         ])
-        .split(r);
+        .split(rect);
+
+    // info line with the deal and round numbers
+    let (deal_no, total_deals) = view.deal;
+    let (round_no, total_rounds) = view.round;
+    // a new game can only be started from a local game
+    let new_game_hint = if state.remote.is_some() {
+        ""
+    } else {
+        " Press (n) for a new game"
+    };
+    let info = match &view.winner {
+        Some(winner) => format!("Game over, {} won the game!{}", winner, new_game_hint),
+        None if view.state == GameState::Finished => {
+            format!("Game over, it's a tie!{}", new_game_hint)
+        }
+        None => format!(
+            "Deal {}/{}  ·  Round {}/{}  ·  Trump {}",
+            deal_no,
+            total_deals,
+            round_no,
+            total_rounds,
+            view.rules
+                .trump_suit
+                .get_symbol(state.get_theme().get_card_style())
+        ),
+    };
+    let mut info = vec![Span::raw(info)];
+    if view.state == GameState::Calling {
+        // the running total of the calls against the rounds to be won
+        info.push(Span::raw(format!(
+            "  ·  Called {}/{}",
+            view.call_total, total_rounds
+        )));
+    }
+    if let Some(time_left) = view.time_left_ms {
+        // rounded up, so the countdown only shows 0s when the time is up
+        let seconds = time_left.div_ceil(1000);
+        let palette = state.get_theme().get_palette();
+        let color = if seconds <= TIME_WARNING_SECONDS {
+            palette.warning
+        } else {
+            palette.text
+        };
+        info.push(Span::raw("  ·  "));
+        info.push(Span::styled(
+            format!("⏱ {}s", seconds),
+            Style::default().fg(color),
+        ));
+    }
+    frame.render_widget(
+        Paragraph::new(Line::from(info))
+            .alignment(Alignment::Center)
+            .bold(),
+        game_chunks[0],
+    );
 
-    // Then cut the middle vertical piece into three width-wise pieces
-    Layout::default()
+    if is_compact(frame.area()) {
+        draw_compact_table(frame, state, view, game_chunks[1]);
+    } else {
+        draw_wide_table(frame, state, view, game_chunks[1]);
+    }
+
+    draw_hand(frame, state, view, hand_lines, game_chunks[2]);
+}
+
+/// get_table_rounds returns the cards of the current round, or of the previous round
+/// if a new round is yet to start, with a flag telling which one it is
+fn get_table_rounds(view: &GameView) -> (bool, &[Round]) {
+    if view.table.is_empty() && !view.last_round.is_empty() {
+        (true, &view.last_round)
+    } else {
+        (false, &view.table)
+    }
+}
+
+/// get_call_str returns the call of the player, or "-" if it's yet to be made
+fn get_call_str(player: &PlayerView) -> String {
+    match player.call {
+        Some(call) => call.to_string(),
+        None => String::from("-"),
+    }
+}
+
+/// draw_compact_table shows every player on a single line with the card they threw
+fn draw_compact_table(frame: &mut Frame, state: &State, view: &GameView, rect: Rect) {
+    let palette = state.get_theme().get_palette();
+    let (is_last_round, rounds) = get_table_rounds(view);
+
+    let mut lines: Vec<Line> = view
+        .players
+        .iter()
+        .map(|p| {
+            let is_current = view.current_player.as_deref() == Some(p.name.as_str());
+            let mut spans = vec![Span::raw(format!(
+                "{} {:<9.9} {:>2}/{:<2} ",
+                if is_current { "▶" } else { " " },
+                p.name,
+                get_call_str(p),
+                p.tricks
+            ))];
+            if let Some(r) = rounds.iter().find(|r| r.get_player() == p.name) {
+                spans.push(state.get_theme().card_span(r.get_card()));
+            }
+            let line = Line::from(spans);
+            if is_current {
+                line.style(Style::default().fg(palette.highlight).bold())
+            } else {
+                line
+            }
+        })
+        .collect();
+    if let (true, Some(winner)) = (is_last_round, &view.last_round_winner) {
+        lines.push(Line::styled(
+            format!("{} won the round", winner),
+            Style::default().fg(palette.success),
+        ));
+    }
+
+    let title = if is_last_round {
+        " Last Round (call/tricks) "
+    } else {
+        " Table (call/tricks) "
+    };
+    frame.render_widget(
+        Paragraph::new(lines).block(Block::bordered().title(title)),
+        rect,
+    );
+}
+
+/// draw_wide_table shows the players and the cards on the table side by side
+fn draw_wide_table(frame: &mut Frame, state: &State, view: &GameView, rect: Rect) {
+    let palette = state.get_theme().get_palette();
+    let table_chunks = Layout::default()
         .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
+        .split(rect);
+
+    // players with their calls and the tricks taken in this deal
+    let player_lines: Vec<Line> = view
+        .players
+        .iter()
+        .map(|p| {
+            let is_current = view.current_player.as_deref() == Some(p.name.as_str());
+            let mut line = format!(
+                "{} {:<12} call {:>2}  tricks {:>2}",
+                if is_current { "▶" } else { " " },
+                p.name,
+                get_call_str(p),
+                p.tricks
+            );
+            if view.rules.partnership {
+                line.push_str(&format!("  team {}", p.team + 1));
+            }
+            if is_current {
+                Line::styled(line, Style::default().fg(palette.highlight).bold())
+            } else {
+                Line::from(line)
+            }
+        })
+        .collect();
+    frame.render_widget(
+        Paragraph::new(player_lines).block(Block::bordered().title(" Players ")),
+        table_chunks[0],
+    );
+
+    // cards thrown in the current round, or the previous round if a new round is yet to start
+    let (is_last_round, rounds) = get_table_rounds(view);
+    let title = if is_last_round {
+        " Last Round "
+    } else {
+        " Table "
+    };
+    let mut table_lines: Vec<Line> = rounds
+        .iter()
+        .map(|r| {
+            Line::from(vec![
+                Span::raw(format!("{:<12} ", r.get_player())),
+                state.get_theme().card_span(r.get_card()),
+            ])
+        })
+        .collect();
+    if let (true, Some(winner)) = (is_last_round, &view.last_round_winner) {
+        table_lines.push(Line::from(""));
+        table_lines.push(Line::styled(
+            format!("{} won the round", winner),
+            Style::default().fg(palette.success),
+        ));
+    }
+    frame.render_widget(
+        Paragraph::new(table_lines).block(Block::bordered().title(title)),
+        table_chunks[1],
+    );
+}
+
+/// get_hand_lines returns the cards of the seat of the view packed into lines of the given
+/// width, so that a card is never split across two lines, followed by the call picker
+fn get_hand_lines(state: &State, view: &GameView, width: u16) -> Vec<Line<'static>> {
+    if view.seat.is_none() {
+        return get_spectator_hand_lines(state, view, width);
+    }
+    if view.hand.is_empty() {
+        return vec![];
+    }
+
+    // the selection is only shown when it's the turn of the seat
+    let is_my_turn = view.is_my_turn();
+    let is_playing = is_my_turn && view.state == GameState::Playing;
+    let mut lines = vec![];
+    let mut cards: Vec<Span> = vec![];
+    for (i, c) in view.hand.iter().enumerate() {
+        let span = state.get_theme().card_span(c);
+        let span = if is_playing && i == state.selected_card {
+            span.add_modifier(Modifier::REVERSED)
+        } else if is_playing && !view.eligible_cards.contains(&i) {
+            span.add_modifier(Modifier::DIM)
+        } else {
+            span
+        };
+        let line_width: usize = cards.iter().map(|s| s.width()).sum();
+        if !cards.is_empty() && line_width + span.width() > width as usize {
+            lines.push(Line::from(std::mem::take(&mut cards)));
+        }
+        cards.push(span);
+    }
+    lines.push(Line::from(cards));
+
+    if is_my_turn && view.state == GameState::Calling {
+        let (min_call, max_call) = view.call_range;
+        let redeal_hint = if view.can_redeal {
+            "  ·  (r) to ask for a redeal"
+        } else {
+            ""
+        };
+        let nil = if view.nil_bid.is_some() { ", nil" } else { "" };
+        // the last caller is told which calls the rules take away
+        let forbidden_hint = match view.forbidden_bids.as_slice() {
+            [] => String::new(),
+            bids => format!(
+                "  ·  not allowed: {}",
+                bids.iter()
+                    .map(|b| b.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        };
+        lines.push(Line::from(format!(
+            "Your call ({}-{}{}): ◀ {} ▶{}{}",
+            min_call, max_call, nil, state.selected_call, forbidden_hint, redeal_hint
+        )));
+    }
+    lines
+}
+
+/// get_spectator_hand_lines lists the hands of all the players, if they are shown to the spectators
+fn get_spectator_hand_lines(state: &State, view: &GameView, width: u16) -> Vec<Line<'static>> {
+    let name_width = view
+        .players
+        .iter()
+        .map(|p| p.name.chars().count())
+        .max()
+        .unwrap_or(0);
+    let mut lines = vec![];
+    for player in view.players.iter().filter(|p| !p.hand.is_empty()) {
+        let mut spans = vec![Span::raw(format!("{:<1$} ", player.name, name_width))];
+        for c in &player.hand {
+            let span = state.get_theme().card_span(c);
+            let line_width: usize = spans.iter().map(|s| s.width()).sum();
+            if line_width + span.width() > width as usize {
+                lines.push(Line::from(std::mem::take(&mut spans)));
+                spans.push(Span::raw(" ".repeat(name_width + 1)));
+            }
+            spans.push(span);
+        }
+        lines.push(Line::from(spans));
+    }
+    lines
+}
+
+/// draw_hand shows the cards of the seat of the view with the call picker or the selected card
+fn draw_hand(frame: &mut Frame, state: &State, view: &GameView, lines: Vec<Line>, rect: Rect) {
+    let spectator_delay = state.remote.as_ref().and_then(|r| r.spectator_delay);
+    let seat = match &view.seat {
+        Some(seat) if !lines.is_empty() => seat,
+        _ if spectator_delay.is_some() => {
+            let title = match spectator_delay {
+                Some(delay) if !delay.is_zero() => {
+                    format!(" Hands · watching {}s behind ", delay.as_secs_f32())
+                }
+                _ => String::from(" Hands · watching "),
+            };
+            let hand: Paragraph = if lines.is_empty() {
+                Paragraph::new("The hands are hidden from the spectators")
+            } else {
+                Paragraph::new(lines)
+            };
+            frame.render_widget(hand.block(Block::bordered().title(title)), rect);
+            return;
+        }
+        _ => {
+            let text = match &view.current_player {
+                Some(p) if state.is_bot(p) => format!("{} is thinking...", p),
+                Some(p) => format!("{}'s cards are hidden", p),
+                None => String::new(),
+            };
+            frame.render_widget(
+                Paragraph::new(text).block(Block::bordered().title(" Hand ")),
+                rect,
+            );
+            return;
+        }
+    };
+
+    let title = match &view.current_player {
+        Some(p) if p != seat && view.state != GameState::Finished => {
+            format!(" {}'s Hand · waiting for {} ", seat, p)
+        }
+        _ => format!(" {}'s Hand ", seat),
+    };
+    let hand = Paragraph::new(lines)
+        .wrap(Wrap { trim: false })
+        .block(Block::bordered().title(title));
+    frame.render_widget(hand, rect);
+}
+
+fn draw_reveal_hand_popup(frame: &mut Frame, state: &State) {
+    let palette = state.get_theme().get_palette();
+    let player_name = match state.game.get_current_player() {
+        Some(p) => match state.profiles.get_profile(p.get_name()) {
+            Some(profile) => profile.get_label(),
+            None => p.get_name().to_string(),
+        },
+        None => return,
+    };
+    let popup_layout = popup_rect(50, MIN_WIDTH, 7, frame.area());
+    frame.render_widget(Clear, popup_layout);
+
+    let popup = Paragraph::new(vec![
+        Line::from(format!("Pass the device to {}", player_name)),
+        Line::from(""),
+        Line::from(format!("Pin: {}", "*".repeat(state.input_buffer.len()))),
+    ])
+    .alignment(Alignment::Center)
+    .block(
+        Block::bordered()
+            .title(Title::from(" Reveal Hand ").alignment(Alignment::Center))
+            .style(Style::default().fg(palette.text).bg(palette.popup)),
+    );
+    frame.render_widget(popup, popup_layout);
+}
+
+/// draw_score_board shows every deal of the match with the running totals and the standings
+fn draw_score_board(frame: &mut Frame, state: &State, view: &GameView, rect: Rect) {
+    let palette = state.get_theme().get_palette();
+    let score_board = &view.score_board;
+    let players = score_board.get_players();
+    let is_finished = view.state == GameState::Finished;
+
+    if score_board.get_deals().is_empty() {
+        let empty = Paragraph::new("No deals played yet")
+            .alignment(Alignment::Center)
+            .block(Block::bordered().title(" Scoreboard "));
+        frame.render_widget(empty, rect);
+        return;
+    }
+
+    let score_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(4),
+            Constraint::Length(players.len() as u16 + 2),
+        ])
+        .split(rect);
+
+    // every cell shows call/tricks, the score of the deal and the running total
+    let header = Row::new(
+        std::iter::once(Cell::from("Deal")).chain(players.iter().map(|p| Cell::from(p.as_str()))),
+    )
+    .style(Style::default().bold().fg(palette.highlight));
+
+    // the full cells need about 20 columns a player, which a big table may not have
+    let compact = is_compact(frame.area()) || (rect.width as usize) < 8 + players.len() * 20;
+    let running_totals = score_board.running_totals();
+    let mut rows: Vec<Row> = score_board
+        .get_deals()
+        .iter()
+        .zip(running_totals.iter())
+        .map(|(deal, totals)| {
+            Row::new(std::iter::once(Cell::from(deal.deal_no.to_string())).chain(
+                deal.entries.iter().zip(totals.iter()).map(|(e, total)| {
+                    Cell::from(if compact {
+                        format!("{}/{} {:>5}", e.call, e.tricks, total)
+                    } else {
+                        format!("{}/{}  {:>5}  ({})", e.call, e.tricks, e.score, total)
+                    })
+                }),
+            ))
+        })
+        .collect();
+    rows.push(
+        Row::new(
+            std::iter::once(Cell::from("Total")).chain(
+                score_board
+                    .totals()
+                    .iter()
+                    .map(|t| Cell::from(t.to_string())),
+            ),
+        )
+        .style(Style::default().bold()),
+    );
+
+    let widths = std::iter::once(Constraint::Length(if compact { 5 } else { 6 }))
+        .chain(players.iter().map(|_| Constraint::Fill(1)))
+        .collect::<Vec<Constraint>>();
+    let title = if compact {
+        " Scoreboard (call/tricks total) "
+    } else {
+        " Scoreboard (call/tricks  score  (total)) "
+    };
+    let table = Table::new(rows, widths)
+        .header(header)
+        .block(Block::bordered().title(title));
+    frame.render_widget(table, score_chunks[0]);
+
+    // standings with the winner highlighted once the game is finished
+    let winner = &view.winner;
+    let standing_lines: Vec<Line> = score_board
+        .standings()
+        .iter()
+        .enumerate()
+        .map(|(i, (name, total))| {
+            let line = format!("{}. {:<12} {:>6}", i + 1, name, total.to_string());
+            if winner.as_deref() == Some(name.as_str()) {
+                Line::styled(
+                    format!("{}  ★ Winner", line),
+                    Style::default().fg(palette.success).bold(),
+                )
+            } else {
+                Line::from(line)
+            }
+        })
+        .collect();
+    let title = if is_finished {
+        " Final Standings "
+    } else {
+        " Standings "
+    };
+    frame.render_widget(
+        Paragraph::new(standing_lines).block(Block::bordered().title(title)),
+        score_chunks[1],
+    );
+}
+
+/// draw_lobby_screen shows the seats of the hosted game next to the chat
+fn draw_lobby_screen(frame: &mut Frame, state: &State, rect: Rect) {
+    let palette = state.get_theme().get_palette();
+    let remote = match &state.remote {
+        Some(remote) => remote,
+        None => return,
+    };
+    let lobby_chunks = if is_compact(frame.area()) {
+        Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(remote.total_seats.max(1) as u16 + 2),
+                Constraint::Min(3),
+            ])
+            .split(rect)
+    } else {
+        Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
+            .split(rect)
+    };
+
+    // taken seats with their ready marks, followed by the free ones
+    let my_seat = remote.seat.and_then(|i| remote.seats.get(i));
+    let mut seat_lines: Vec<Line> = remote
+        .seats
+        .iter()
+        .map(|s| {
+            let mut line = format!("{} {}", if s.ready { "✔" } else { "·" }, s.name);
+            if let Some(rating) = s.rating {
+                line.push_str(&format!(" ({})", rating));
+            }
+            if my_seat.is_some_and(|m| m.name == s.name) {
+                Line::styled(line, Style::default().fg(palette.highlight).bold())
+            } else if s.ready {
+                Line::styled(line, Style::default().fg(palette.success))
+            } else {
+                Line::from(line)
+            }
+        })
+        .collect();
+    for _ in remote.seats.len()..remote.total_seats {
+        seat_lines.push(Line::styled(
+            "  (empty seat)",
+            Style::default().add_modifier(Modifier::DIM),
+        ));
+    }
+    let title = format!(" Seats {}/{} ", remote.seats.len(), remote.total_seats);
+    frame.render_widget(
+        Paragraph::new(seat_lines).block(Block::bordered().title(title)),
+        lobby_chunks[0],
+    );
+
+    // only the latest chat lines that fit in the box
+    let status = if !remote.is_connected() {
+        format!(" Chat · connection to {} lost ", remote.addr)
+    } else if remote.is_spectating() {
+        format!(" Chat · watching {} ", remote.addr)
+    } else if remote.ready {
+        String::from(" Chat · ready, waiting for the others ")
+    } else {
+        format!(" Chat · connected to {} ", remote.addr)
+    };
+    let visible = lobby_chunks[1].height.saturating_sub(2) as usize;
+    let chat_lines: Vec<Line> = remote
+        .chat
+        .iter()
+        .skip(remote.chat.len().saturating_sub(visible))
+        .map(|l| Line::from(l.as_str()))
+        .collect();
+    frame.render_widget(
+        Paragraph::new(chat_lines)
+            .wrap(Wrap { trim: false })
+            .block(Block::bordered().title(status)),
+        lobby_chunks[1],
+    );
+}
+
+/// draw_text_popup shows a single line text field over the current screen
+fn draw_text_popup(frame: &mut Frame, state: &State, title: &str, label: &str) {
+    let palette = state.get_theme().get_palette();
+    let popup_layout = popup_rect(50, MIN_WIDTH, 5, frame.area());
+    frame.render_widget(Clear, popup_layout);
+
+    let popup = Paragraph::new(vec![
+        Line::from(format!("{}:", label)),
+        Line::from(format!("{}_", state.input_buffer)),
+    ])
+    .block(
+        Block::bordered()
+            .title(Title::from(title.to_string()).alignment(Alignment::Center))
+            .style(Style::default().fg(palette.text).bg(palette.popup)),
+    );
+    frame.render_widget(popup, popup_layout);
+}
+
+/// draw_settings_screen shows the settings with a preview of the selected theme
+fn draw_settings_screen(frame: &mut Frame, state: &State, rect: Rect) {
+    let palette = state.get_theme().get_palette();
+    let config = &state.config;
+    let compact = is_compact(frame.area());
+    let settings_chunks = Layout::default()
+        .direction(Direction::Vertical)
         .constraints([
-            Constraint::Percentage((100 - percent_x) / 2),
-            Constraint::Percentage(percent_x),
-            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Length(Setting::ALL.len() as u16 + 2),
+            Constraint::Length(3),
+            Constraint::Min(0),
         ])
-        .split(popup_layout[1])[1] // Return the middle chunk
+        .split(rect);
+
+    let setting_lines: Vec<Line> = Setting::ALL
+        .iter()
+        .enumerate()
+        .map(|(i, setting)| {
+            let value = match setting {
+                Setting::Theme => config.theme.get_name().to_string(),
+                Setting::Rules => config.rules.get_name().to_string(),
+                Setting::Redeal => config.redeal.get_name().to_string(),
+                Setting::Partnership if config.partnership => String::from("On"),
+                Setting::Partnership => String::from("Off"),
+                Setting::NilBid if config.nil_bid => {
+                    format!("On (+{} / -{})", config.nil_bonus, config.nil_penalty)
+                }
+                Setting::NilBid => String::from("Off"),
+                Setting::Hook if config.hook => String::from("On"),
+                Setting::Hook => String::from("Off"),
+                Setting::MinTotalCall if config.min_total_call == 0 => String::from("Off"),
+                Setting::MinTotalCall => config.min_total_call.to_string(),
+                Setting::TotalDeals => config.total_deals.to_string(),
+                Setting::MinCall => config.min_call.to_string(),
+                Setting::MaxCall => config.max_call.to_string(),
+                Setting::BotDifficulty => config.bot_difficulty.get_name().to_string(),
+                Setting::AnimationSpeed => config.animation_speed.get_name().to_string(),
+                Setting::CallTimeLimit => config.call_time_limit.get_name().to_string(),
+                Setting::ThrowTimeLimit => config.throw_time_limit.get_name().to_string(),
+                Setting::PlayerNames if config.player_names.is_empty() => String::from("-"),
+                Setting::PlayerNames => config.player_names.join(", "),
+            };
+            let line = if compact {
+                format!("{}: ◀ {} ▶", setting.get_name(), value)
+            } else {
+                format!("{:<22} ◀ {} ▶", setting.get_name(), value)
+            };
+            if i == state.selected_setting {
+                Line::styled(line, Style::default().fg(palette.highlight).bold())
+            } else {
+                Line::from(line)
+            }
+        })
+        .collect();
+    frame.render_widget(
+        Paragraph::new(setting_lines).block(Block::bordered().title(" Settings ")),
+        settings_chunks[0],
+    );
+
+    let theme = config.theme;
+    let preview_cards = [
+        Card::new(Suit::Spade, String::from("A")),
+        Card::new(Suit::Hearts, String::from("10")),
+        Card::new(Suit::Diamonds, String::from("K")),
+        Card::new(Suit::Club, String::from("7")),
+    ];
+    let preview: Vec<Span> = preview_cards.iter().map(|c| theme.card_span(c)).collect();
+    frame.render_widget(
+        Paragraph::new(Line::from(preview)).block(Block::bordered().title(" Preview ")),
+        settings_chunks[1],
+    );
+
+    if state.current_popup == Popups::SettingsPopups(SettingsPopups::PlayerNames) {
+        draw_player_names_popup(frame, state);
+    }
+}
+
+fn draw_player_names_popup(frame: &mut Frame, state: &State) {
+    let palette = state.get_theme().get_palette();
+    let popup_layout = popup_rect(60, MIN_WIDTH, 8, frame.area());
+    frame.render_widget(Clear, popup_layout);
+
+    let popup = Paragraph::new(vec![
+        Line::from("Enter the names separated by commas:"),
+        Line::from(""),
+        Line::styled(
+            state.input_buffer.clone(),
+            Style::default().fg(palette.input_text).bg(palette.input),
+        ),
+    ])
+    .wrap(Wrap { trim: true })
+    .block(
+        Block::bordered()
+            .title(Title::from(" Default Player Names ").alignment(Alignment::Center))
+            .style(Style::default().fg(palette.text).bg(palette.popup)),
+    );
+    frame.render_widget(popup, popup_layout);
+}
+
+/// popup_rect returns a rect of the given height centered in `r`, taking `percent_x` of
+/// the width but at least `min_width`, so the popups stay readable on narrow terminals
+fn popup_rect(percent_x: u16, min_width: u16, height: u16, r: Rect) -> Rect {
+    let width = (r.width * percent_x / 100).max(min_width).min(r.width);
+    let height = height.min(r.height);
+    Rect::new(
+        r.x + (r.width - width) / 2,
+        r.y + (r.height - height) / 2,
+        width,
+        height,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_popup_rect() {
+        let area = Rect::new(0, 0, 120, 40);
+        assert_eq!(
+            popup_rect(50, MIN_WIDTH, 13, area),
+            Rect::new(30, 13, 60, 13)
+        );
+
+        // the minimum width wins over the percentage on narrow terminals
+        let narrow = Rect::new(0, 0, 60, 20);
+        assert_eq!(
+            popup_rect(50, MIN_WIDTH, 13, narrow),
+            Rect::new(10, 3, 40, 13)
+        );
+
+        // never larger than the available area
+        let tiny = Rect::new(0, 0, 30, 10);
+        assert_eq!(popup_rect(50, MIN_WIDTH, 13, tiny), Rect::new(0, 0, 30, 10));
+    }
+
+    #[test]
+    fn test_layout_modes() {
+        assert!(!is_compact(Rect::new(0, 0, 80, 24)));
+        assert!(is_compact(Rect::new(0, 0, 79, 24)));
+        assert!(!is_too_small(Rect::new(0, 0, MIN_WIDTH, MIN_HEIGHT)));
+        assert!(is_too_small(Rect::new(0, 0, MIN_WIDTH - 1, 30)));
+        assert!(is_too_small(Rect::new(0, 0, 100, MIN_HEIGHT - 1)));
+    }
 }
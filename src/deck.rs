@@ -1,4 +1,5 @@
-use rand::Rng;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
 
 use crate::card::{Card, Suit};
 
@@ -8,25 +9,38 @@ pub struct Deck {
 
 pub const DECK_LEN: u8 = 52;
 
+impl Default for Deck {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Deck {
     /// Initializes a new deck with all cards
     /// ```rust
+    /// # use callbreak::deck::Deck;
     /// let my_deck = Deck::new();
-    /// ````
+    /// ```
     pub fn new() -> Self {
-        let mut deck = Deck {
-            cards: vec![],
-        };
+        let mut deck = Deck { cards: vec![] };
         deck.cards = deck.create_deck();
         deck
     }
 
     fn create_deck(&self) -> Vec<Card> {
         let mut card_deck: Vec<Card> = vec![];
-        let hearts: Vec<&str> = vec!["2", "3", "4", "5", "6", "7", "8", "9", "10", "J", "Q", "K", "A"];
-        let spades: Vec<&str> = vec!["2", "3", "4", "5", "6", "7", "8", "9", "10", "J", "Q", "K", "A"];
-        let clubs: Vec<&str> = vec!["2", "3", "4", "5", "6", "7", "8", "9", "10", "J", "Q", "K", "A"];
-        let diamonds: Vec<&str> = vec!["2", "3", "4", "5", "6", "7", "8", "9", "10", "J", "Q", "K", "A"];
+        let hearts: Vec<&str> = vec![
+            "2", "3", "4", "5", "6", "7", "8", "9", "10", "J", "Q", "K", "A",
+        ];
+        let spades: Vec<&str> = vec![
+            "2", "3", "4", "5", "6", "7", "8", "9", "10", "J", "Q", "K", "A",
+        ];
+        let clubs: Vec<&str> = vec![
+            "2", "3", "4", "5", "6", "7", "8", "9", "10", "J", "Q", "K", "A",
+        ];
+        let diamonds: Vec<&str> = vec![
+            "2", "3", "4", "5", "6", "7", "8", "9", "10", "J", "Q", "K", "A",
+        ];
 
         for c in hearts {
             card_deck.push(Card::new(Suit::Hearts, c.to_string()))
@@ -49,10 +63,13 @@ impl Deck {
 
     /// Prints current deck stored in Deck.cards field
     ///  ```rust
+    ///     # use callbreak::deck::Deck;
     ///     let my_deck = Deck::new();
     ///     my_deck.print_deck();
     ///
     ///     // Output
+    ///  ```
+    ///  ```text
     ///     [ 2 ♥️ ][ 3 ♥️ ][ 4 ♥️ ][ 5 ♥️ ][ 6 ♥️ ][ 7 ♥️ ][ 8 ♥️ ][ 9 ♥️ ][ 10 ♥️ ][ J ♥️ ][ Q ♥️ ][ K ♥️ ][ A ♥️ ]
     ///     [ 2 ♠️ ][ 3 ♠️ ][ 4 ♠️ ][ 5 ♠️ ][ 6 ♠️ ][ 7 ♠️ ][ 8 ♠️ ][ 9 ♠️ ][ 10 ♠️ ][ J ♠️ ][ Q ♠️ ][ K ♠️ ][ A ♠️ ]
     ///     [ 2 ♣️ ][ 3 ♣️ ][ 4 ♣️ ][ 5 ♣️ ][ 6 ♣️ ][ 7 ♣️ ][ 8 ♣️ ][ 9 ♣️ ][ 10 ♣️ ][ J ♣️ ][ Q ♣️ ][ K ♣️ ][ A ♣️ ]
@@ -75,14 +92,64 @@ impl Deck {
 
     pub fn shuffle(&mut self) {
         for i in 0..self.cards.len() {
-            let r = i + (rand::thread_rng().gen_range(0..self.total_len()) % (self.total_len() - i));
+            let r =
+                i + (rand::thread_rng().gen_range(0..self.total_len()) % (self.total_len() - i));
             self.cards.swap(i, r)
         }
     }
 
+    /// shuffle_with_seed shuffles the deck the same way every time for the same seed,
+    /// so that a shuffle can be checked by anyone who knows the seed
+    pub fn shuffle_with_seed(&mut self, seed: [u8; 32]) {
+        self.shuffle_with_rng(&mut ChaCha20Rng::from_seed(seed));
+    }
+
+    /// shuffle_with_rng shuffles the deck with the given generator, so that a seeded
+    /// generator can keep going over several shuffles
+    pub fn shuffle_with_rng<R: Rng>(&mut self, rng: &mut R) {
+        for i in (1..self.cards.len()).rev() {
+            let r = rng.gen_range(0..=i);
+            self.cards.swap(i, r)
+        }
+    }
+
+    /// remove_low_cards takes the lowest cards out until the deck can be dealt evenly
+    /// among the players, the trumps are the last to go
+    pub fn remove_low_cards(&mut self, total_players: usize, trump_suit: Suit) {
+        while !self.cards.len().is_multiple_of(total_players) {
+            let lowest = self
+                .cards
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, c)| (c.get_suit() == trump_suit, c.get_priority()))
+                .map(|(i, _)| i);
+            match lowest {
+                Some(i) => self.cards.remove(i),
+                None => return,
+            };
+        }
+    }
+
     pub fn distribute(&mut self) -> Card {
         self.cards.swap_remove(0)
     }
+
+    /// deal gives `cards_each` cards to every one of the `total_players` hands, one at a
+    /// time starting from the hand at `first_seat`
+    pub fn deal(
+        &mut self,
+        total_players: usize,
+        first_seat: usize,
+        cards_each: usize,
+    ) -> Vec<Vec<Card>> {
+        let mut hands = vec![vec![]; total_players];
+        let mut i = first_seat;
+        for _ in 0..cards_each * total_players {
+            hands[i].push(self.distribute());
+            i = (i + 1) % total_players;
+        }
+        hands
+    }
 }
 
 #[cfg(test)]
@@ -125,6 +192,51 @@ mod tests {
         assert_ne!(deck.cards[0].get_priority(), 2);
     }
 
+    #[test]
+    fn test_shuffle_with_seed() {
+        let mut deck = Deck::new();
+        deck.shuffle_with_seed([7; 32]);
+        let mut same = Deck::new();
+        same.shuffle_with_seed([7; 32]);
+        let mut other = Deck::new();
+        other.shuffle_with_seed([8; 32]);
+
+        assert_eq!(deck.cards, same.cards);
+        assert_ne!(deck.cards, other.cards);
+        assert_ne!(deck.cards, Deck::new().cards);
+        assert_eq!(deck.total_len(), 52);
+    }
+
+    #[test]
+    fn test_deal() {
+        let mut deck = Deck::new();
+        let hands = deck.deal(3, 1, 17);
+
+        assert_eq!(hands.len(), 3);
+        assert!(hands.iter().all(|h| h.len() == 17));
+        // the hand at the first seat gets the first card
+        assert_eq!(hands[1][0], Card::new(Suit::Hearts, String::from("2")));
+        assert_eq!(deck.total_len(), 1);
+    }
+
+    #[test]
+    fn test_remove_low_cards() {
+        let mut deck = Deck::new();
+        deck.remove_low_cards(4, Suit::Spade);
+        assert_eq!(deck.total_len(), 52);
+
+        deck.remove_low_cards(5, Suit::Hearts);
+        assert_eq!(deck.total_len(), 50);
+        // the 2s of the other suits go before the trump
+        let two = |suit| Card::new(suit, String::from("2"));
+        assert!(!deck.cards.contains(&two(Suit::Spade)));
+        assert!(!deck.cards.contains(&two(Suit::Club)));
+        assert!(deck.cards.contains(&two(Suit::Hearts)));
+
+        deck.remove_low_cards(3, Suit::Hearts);
+        assert_eq!(deck.total_len(), 48);
+    }
+
     #[test]
     fn test_distribute() {
         let mut deck = Deck::new();
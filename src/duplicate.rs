@@ -0,0 +1,730 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::bot::Difficulty;
+use crate::game::{Game, GameError, MAX_PLAYERS, MIN_PLAYERS};
+use crate::log::{GameLog, LogEvent};
+use crate::rules::{Reshuffle, RulePreset, RuleSet, DEFAULT_MAX_REDEALS};
+use crate::score::{Score, ScoreBoard};
+use crate::shuffle::{self, Seed};
+use crate::tournament::{self, Entrant, SetupArgs};
+
+const USAGE: &str = "usage: callbreak duplicate [new <file> --players a,b,c [--bots N] [--bot-level easy|normal|hard] [--deals N] [--rules standard|nepali|indian] [--seed <hex>] | play <file> | record <file> <game.json> | results <file>]";
+
+#[derive(Debug, PartialEq)]
+pub enum DuplicateError {
+    InvalidPlayers,
+    DuplicatePlayer(String),
+    // the seats are compared one player each
+    Partnership,
+    InvalidSeed,
+    // the game log isn't of the players of the match
+    PlayersMismatch,
+    RulesMismatch,
+    // the game log was dealt from another seed
+    WrongDeals,
+    GameNotFinished,
+    // a game of the same seating is in the match already
+    SeatingRecorded,
+}
+
+impl fmt::Display for DuplicateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DuplicateError::InvalidPlayers => write!(
+                f,
+                "A duplicate match is played by {} to {} players",
+                MIN_PLAYERS, MAX_PLAYERS
+            ),
+            DuplicateError::DuplicatePlayer(name) => {
+                write!(f, "{} is registered more than once", name)
+            }
+            DuplicateError::Partnership => {
+                write!(f, "The duplicate games are played without partners")
+            }
+            DuplicateError::InvalidSeed => write!(f, "The seed of the match is not valid"),
+            DuplicateError::PlayersMismatch => {
+                write!(f, "The game wasn't played by the players of the match")
+            }
+            DuplicateError::RulesMismatch => {
+                write!(f, "The game wasn't played with the rules of the match")
+            }
+            DuplicateError::WrongDeals => {
+                write!(f, "The game wasn't dealt from the seed of the match")
+            }
+            DuplicateError::GameNotFinished => write!(f, "The game in the log isn't over"),
+            DuplicateError::SeatingRecorded => {
+                write!(
+                    f,
+                    "A game with the players in these seats is recorded already"
+                )
+            }
+        }
+    }
+}
+
+/// DuplicateGame is a game of the match, the seats are in the order the players sat
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DuplicateGame {
+    pub players: Vec<String>,
+    // the score of every seat in every deal
+    pub scores: Vec<Vec<Score>>,
+}
+
+/// DuplicateResult compares a player with the others who held the same cards
+#[derive(Clone, Debug, PartialEq)]
+pub struct DuplicateResult {
+    pub name: String,
+    pub games: u32,
+    pub total_score: Score,
+    // the points scored over the average of the same seat in the same deal
+    pub over_par: Score,
+    // the deals that were compared with at least one other player
+    pub boards: u32,
+}
+
+/// DuplicateMatch plays the same deals again and again, with the players rotated through
+/// every seat, so everyone is scored against the others who held the same cards
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DuplicateMatch {
+    // the seed of the match in hex, the deals of every game come from it
+    seed: String,
+    entrants: Vec<Entrant>,
+    rules: RuleSet,
+    games: Vec<DuplicateGame>,
+}
+
+impl DuplicateMatch {
+    pub fn new(entrants: Vec<Entrant>, rules: RuleSet, seed: Seed) -> Result<Self, DuplicateError> {
+        if !(MIN_PLAYERS..=MAX_PLAYERS).contains(&entrants.len()) {
+            return Err(DuplicateError::InvalidPlayers);
+        }
+        if rules.partnership {
+            return Err(DuplicateError::Partnership);
+        }
+        for (i, entrant) in entrants.iter().enumerate() {
+            if entrants[..i].iter().any(|e| e.name == entrant.name) {
+                return Err(DuplicateError::DuplicatePlayer(entrant.name.clone()));
+            }
+        }
+        Ok(DuplicateMatch {
+            seed: hex::encode(seed),
+            entrants,
+            rules,
+            games: vec![],
+        })
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let content = fs::read_to_string(path)?;
+        serde_json::from_str(&content).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let content = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(path, content)
+    }
+
+    pub fn get_seed(&self) -> Result<Seed, DuplicateError> {
+        shuffle::parse_seed(&self.seed).ok_or(DuplicateError::InvalidSeed)
+    }
+
+    pub fn get_games(&self) -> &[DuplicateGame] {
+        &self.games
+    }
+
+    /// get_seating returns the players in the order of the seats for a rotation, after
+    /// as many rotations as players everyone has held every seat
+    pub fn get_seating(&self, rotation: usize) -> Vec<String> {
+        let total = self.entrants.len();
+        (0..total)
+            .map(|seat| self.entrants[(seat + rotation) % total].name.clone())
+            .collect()
+    }
+
+    /// get_deal_seeds returns the seeds the deals are shuffled from, the same as the ones
+    /// of a server hosting the match with `--deal-seed`
+    fn get_deal_seeds(&self) -> Result<Vec<Seed>, DuplicateError> {
+        let no_entropy = vec![String::new(); self.entrants.len()];
+        Ok(
+            shuffle::get_duplicate_seeds(&self.get_seed()?, self.rules.total_deals)
+                .iter()
+                .zip(1..)
+                .map(|(seed, deal)| shuffle::get_deal_seed(seed, &no_entropy, deal))
+                .collect(),
+        )
+    }
+
+    /// new_game sets up a game of the match with the players seated for the rotation
+    pub fn new_game(&self, rotation: usize) -> Result<Game, GameError> {
+        let mut game = Game::new_game(self.rules.clone())?;
+        for player in self.get_seating(rotation) {
+            game.add_players(&player, "")?;
+        }
+        let seeds = self.get_deal_seeds().map_err(|_| GameError::InvalidState)?;
+        game.set_shuffle_seeds(seeds)?;
+        Ok(game)
+    }
+
+    /// record_log adds a finished game of the match, e.g. one hosted with
+    /// `callbreak serve --deal-seed`, the shuffle of every deal has to be revealed and
+    /// come from the seed
+    pub fn record_log(&mut self, log: &GameLog) -> Result<(), DuplicateError> {
+        let seeds = shuffle::get_duplicate_seeds(&self.get_seed()?, self.rules.total_deals);
+        let mut revealed = vec![false; seeds.len()];
+        let mut players = vec![];
+        for entry in log.get_entries() {
+            match &entry.event {
+                LogEvent::Started {
+                    players: started,
+                    rules,
+                } => {
+                    if *rules != self.rules {
+                        return Err(DuplicateError::RulesMismatch);
+                    }
+                    players = started.clone();
+                }
+                LogEvent::ShuffleRevealed { proof } => {
+                    let deal = (proof.deal as usize)
+                        .checked_sub(1)
+                        .ok_or(DuplicateError::WrongDeals)?;
+                    let seed = seeds.get(deal).ok_or(DuplicateError::WrongDeals)?;
+                    if proof.get_server_seed().ok().as_ref() != Some(seed) {
+                        return Err(DuplicateError::WrongDeals);
+                    }
+                    revealed[deal] = true;
+                }
+                LogEvent::Finished { score_board, .. } => {
+                    // a deal that wasn't revealed could have been dealt from anything
+                    if revealed.contains(&false) {
+                        return Err(DuplicateError::WrongDeals);
+                    }
+                    return self.record_game(players, score_board);
+                }
+                _ => {}
+            }
+        }
+        Err(DuplicateError::GameNotFinished)
+    }
+
+    fn record_game(
+        &mut self,
+        players: Vec<String>,
+        score_board: &ScoreBoard,
+    ) -> Result<(), DuplicateError> {
+        if players.len() != self.entrants.len()
+            || !self.entrants.iter().all(|e| players.contains(&e.name))
+        {
+            return Err(DuplicateError::PlayersMismatch);
+        }
+        if self.games.iter().any(|g| g.players == players) {
+            return Err(DuplicateError::SeatingRecorded);
+        }
+        let scores = score_board
+            .get_deals()
+            .iter()
+            .map(|deal| deal.entries.iter().map(|e| e.score).collect())
+            .collect();
+        self.games.push(DuplicateGame { players, scores });
+        Ok(())
+    }
+
+    /// play_bot_games plays the rotations no game was recorded for yet when only bots
+    /// are in the match, and returns how many were played
+    pub fn play_bot_games(&mut self) -> Result<usize, DuplicateError> {
+        let bots: HashMap<String, Difficulty> = self
+            .entrants
+            .iter()
+            .filter_map(|e| e.bot.map(|d| (e.name.clone(), d)))
+            .collect();
+        if bots.len() != self.entrants.len() {
+            return Ok(0);
+        }
+        let mut played = 0;
+        for rotation in 0..self.entrants.len() {
+            let seating = self.get_seating(rotation);
+            if self.games.iter().any(|g| g.players == seating) {
+                continue;
+            }
+            let mut game = self
+                .new_game(rotation)
+                .map_err(|_| DuplicateError::InvalidSeed)?;
+            if tournament::play_bots(&mut game, &bots).is_some() {
+                self.record_game(seating, game.get_score_board())?;
+                played += 1;
+            }
+        }
+        Ok(played)
+    }
+
+    /// get_results scores every player against the average of everyone who held the same
+    /// seat in the same deal, the best first
+    pub fn get_results(&self) -> Vec<DuplicateResult> {
+        // every seat of every deal is a board, with the score of each player who held it
+        let mut boards: HashMap<(usize, usize), Vec<(&str, Score)>> = HashMap::new();
+        for game in &self.games {
+            for (deal, scores) in game.scores.iter().enumerate() {
+                for (seat, score) in scores.iter().enumerate() {
+                    let player = game.players[seat].as_str();
+                    boards
+                        .entry((deal, seat))
+                        .or_default()
+                        .push((player, *score));
+                }
+            }
+        }
+
+        let mut results: Vec<DuplicateResult> = self
+            .entrants
+            .iter()
+            .map(|e| DuplicateResult {
+                name: e.name.clone(),
+                games: self
+                    .games
+                    .iter()
+                    .filter(|g| g.players.contains(&e.name))
+                    .count() as u32,
+                total_score: Score::default(),
+                over_par: Score::default(),
+                boards: 0,
+            })
+            .collect();
+        for board in boards.values() {
+            let total: i32 = board.iter().map(|(_, s)| s.get_tenths()).sum();
+            let par = total as f32 / board.len() as f32;
+            for (player, score) in board {
+                let result = match results.iter_mut().find(|r| r.name == *player) {
+                    Some(result) => result,
+                    None => continue,
+                };
+                result.total_score += *score;
+                if board.len() > 1 {
+                    let over_par = (score.get_tenths() as f32 - par).round() as i32;
+                    result.over_par += Score::from_tenths(over_par);
+                    result.boards += 1;
+                }
+            }
+        }
+        results.sort_by(|a, b| {
+            b.over_par
+                .cmp(&a.over_par)
+                .then(b.total_score.cmp(&a.total_score))
+                .then(a.name.cmp(&b.name))
+        });
+        results
+    }
+}
+
+/// DuplicateCommand is a `callbreak duplicate` command, the match is kept in a JSON
+/// file between the commands
+#[derive(Debug, PartialEq)]
+pub enum DuplicateCommand {
+    // registers the players and picks the seed of the deals
+    New {
+        path: PathBuf,
+        duplicate: DuplicateMatch,
+    },
+    // plays the rotations of a match between bots
+    Play {
+        path: PathBuf,
+    },
+    Record {
+        path: PathBuf,
+        log_path: PathBuf,
+    },
+    Results {
+        path: PathBuf,
+    },
+}
+
+impl DuplicateCommand {
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        let (command, path) = match args {
+            [command, path, ..] => (command.as_str(), PathBuf::from(path)),
+            _ => return Err(USAGE.to_string()),
+        };
+        match (command, &args[2..]) {
+            ("new", options) => Self::parse_new(path, options),
+            ("play", []) => Ok(DuplicateCommand::Play { path }),
+            ("record", [log_path]) => Ok(DuplicateCommand::Record {
+                path,
+                log_path: PathBuf::from(log_path),
+            }),
+            ("results", []) => Ok(DuplicateCommand::Results { path }),
+            _ => Err(USAGE.to_string()),
+        }
+    }
+
+    fn parse_new(path: PathBuf, args: &[String]) -> Result<Self, String> {
+        let mut setup = SetupArgs::default();
+        let mut seed = None;
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let value = args
+                .next()
+                .ok_or_else(|| format!("Missing value for {}", arg))?;
+            match arg.as_str() {
+                "--seed" => {
+                    seed = Some(
+                        shuffle::parse_seed(value)
+                            .ok_or_else(|| format!("Invalid value for {}: {}", arg, value))?,
+                    )
+                }
+                _ => setup.parse(arg, value)?,
+            }
+        }
+        let seed = seed.unwrap_or_else(shuffle::new_seed);
+        let duplicate = DuplicateMatch::new(setup.get_entrants(), setup.get_rules(), seed)
+            .map_err(|e| e.to_string())?;
+        Ok(DuplicateCommand::New { path, duplicate })
+    }
+
+    /// run carries out the command and returns what to print
+    pub fn run(self) -> io::Result<String> {
+        let to_io = |e: DuplicateError| io::Error::new(io::ErrorKind::InvalidInput, e.to_string());
+        match self {
+            DuplicateCommand::New { path, duplicate } => {
+                duplicate.save(&path)?;
+                Ok(get_seatings_text(&duplicate))
+            }
+            DuplicateCommand::Play { path } => {
+                let mut duplicate = DuplicateMatch::load(&path)?;
+                let played = duplicate.play_bot_games().map_err(to_io)?;
+                duplicate.save(&path)?;
+                Ok(format!(
+                    "Played {} games\n{}",
+                    played,
+                    get_results_text(&duplicate)
+                ))
+            }
+            DuplicateCommand::Record { path, log_path } => {
+                let mut duplicate = DuplicateMatch::load(&path)?;
+                duplicate
+                    .record_log(&GameLog::load(&log_path)?)
+                    .map_err(to_io)?;
+                duplicate.save(&path)?;
+                Ok(format!(
+                    "Recorded game {} of the match\n",
+                    duplicate.get_games().len()
+                ))
+            }
+            DuplicateCommand::Results { path } => {
+                Ok(get_results_text(&DuplicateMatch::load(&path)?))
+            }
+        }
+    }
+}
+
+/// get_serve_args returns the options of `callbreak serve` that host a game of the match,
+/// the log is only recorded if the server played the very rules of the match
+fn get_serve_args(duplicate: &DuplicateMatch) -> String {
+    let rules = &duplicate.rules;
+    // the preset the other rules of the match were changed from
+    let preset = RulePreset::ALL
+        .iter()
+        .copied()
+        .find(|p| {
+            *rules
+                == RuleSet {
+                    total_deals: rules.total_deals,
+                    redeal: rules.redeal,
+                    max_redeals: rules.max_redeals,
+                    partnership: rules.partnership,
+                    nil_bid: rules.nil_bid,
+                    hook: rules.hook,
+                    min_total_call: rules.min_total_call,
+                    ..p.get_rules()
+                }
+        })
+        .unwrap_or_default();
+    let mut args = format!(
+        "--players {} --deals {} --rules {}",
+        duplicate.entrants.len(),
+        rules.total_deals,
+        preset.get_name().to_lowercase()
+    );
+    let redeal = match rules.redeal {
+        Reshuffle::Never => None,
+        Reshuffle::NoTrump => Some("no-trump"),
+        Reshuffle::NoFaceCard => Some("no-face-card"),
+        Reshuffle::NoTrumpOrFaceCard => Some("either"),
+    };
+    if let Some(redeal) = redeal {
+        args.push_str(&format!(" --redeal {}", redeal));
+    }
+    if rules.max_redeals != DEFAULT_MAX_REDEALS {
+        args.push_str(&format!(" --max-redeals {}", rules.max_redeals));
+    }
+    if let Some(nil_bid) = rules.nil_bid {
+        args.push_str(&format!(" --nil {}", nil_bid.bonus));
+    }
+    if rules.hook {
+        args.push_str(" --hook on");
+    }
+    if rules.min_total_call > 0 {
+        args.push_str(&format!(" --min-total {}", rules.min_total_call));
+    }
+    args.push_str(&format!(" --deal-seed {}", duplicate.seed));
+    args
+}
+
+/// get_seatings_text tells how to host every game of the match and where the players sit
+fn get_seatings_text(duplicate: &DuplicateMatch) -> String {
+    let mut text = format!(
+        "Host every game with `callbreak serve {}`\n",
+        get_serve_args(duplicate)
+    );
+    for rotation in 0..duplicate.entrants.len() {
+        text.push_str(&format!(
+            "  Game {}: {}\n",
+            rotation + 1,
+            duplicate.get_seating(rotation).join(", ")
+        ));
+    }
+    text
+}
+
+fn get_results_text(duplicate: &DuplicateMatch) -> String {
+    let mut text = String::new();
+    for (place, r) in duplicate.get_results().iter().enumerate() {
+        let sign = if r.over_par > Score::default() {
+            "+"
+        } else {
+            ""
+        };
+        text.push_str(&format!(
+            "{:>3}. {:<20} {}{}  ({} pts in {} games, {} boards)\n",
+            place + 1,
+            r.name,
+            sign,
+            r.over_par,
+            r.total_score,
+            r.games,
+            r.boards
+        ));
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::GameState;
+    use crate::net::server::ServerOptions;
+
+    fn bots(total: usize) -> Vec<Entrant> {
+        (1..=total)
+            .map(|i| Entrant {
+                name: format!("Bot {}", i),
+                bot: Some(Difficulty::Normal),
+            })
+            .collect()
+    }
+
+    fn two_deals() -> RuleSet {
+        RuleSet {
+            total_deals: 2,
+            ..RuleSet::default()
+        }
+    }
+
+    #[test]
+    fn test_new() {
+        assert_eq!(
+            DuplicateMatch::new(bots(1), two_deals(), [0; 32]),
+            Err(DuplicateError::InvalidPlayers)
+        );
+        assert_eq!(
+            DuplicateMatch::new(bots(6), two_deals(), [0; 32]),
+            Err(DuplicateError::InvalidPlayers)
+        );
+        let duplicate = DuplicateMatch::new(bots(3), two_deals(), [0; 32]).unwrap();
+        assert_eq!(duplicate.get_seating(1), vec!["Bot 2", "Bot 3", "Bot 1"]);
+        assert_eq!(duplicate.get_seed(), Ok([0; 32]));
+    }
+
+    #[test]
+    fn test_same_deals() {
+        let duplicate = DuplicateMatch::new(bots(3), two_deals(), [7; 32]).unwrap();
+        let mut first = duplicate.new_game(0).unwrap();
+        let mut second = duplicate.new_game(1).unwrap();
+        first.start().unwrap();
+        second.start().unwrap();
+        // the seat keeps its cards, whoever sits there
+        for seat in 0..3 {
+            assert_eq!(
+                first.get_players()[seat].get_cards(),
+                second.get_players()[seat].get_cards()
+            );
+        }
+        assert_ne!(
+            first.get_players()[0].get_cards(),
+            first.get_players()[1].get_cards()
+        );
+        assert_eq!(first.get_state(), GameState::Calling);
+    }
+
+    #[test]
+    fn test_play_and_results() {
+        let mut duplicate = DuplicateMatch::new(bots(3), two_deals(), [3; 32]).unwrap();
+        assert_eq!(duplicate.play_bot_games(), Ok(3));
+        assert_eq!(duplicate.play_bot_games(), Ok(0));
+
+        let results = duplicate.get_results();
+        assert_eq!(results.len(), 3);
+        // every player held every seat of both deals
+        assert!(results.iter().all(|r| r.games == 3 && r.boards == 6));
+        // the results against the par add up to about nothing
+        let total: i32 = results.iter().map(|r| r.over_par.get_tenths()).sum();
+        assert!(total.abs() <= 6);
+        assert!(results[0].over_par >= results[2].over_par);
+    }
+
+    #[test]
+    fn test_record_log() {
+        let mut duplicate = DuplicateMatch::new(bots(2), two_deals(), [5; 32]).unwrap();
+        let players: Vec<String> = duplicate.get_seating(1);
+        let mut log = GameLog::new();
+        log.record(LogEvent::Started {
+            players: players.clone(),
+            rules: RuleSet::default(),
+        });
+        assert_eq!(
+            duplicate.record_log(&log),
+            Err(DuplicateError::RulesMismatch)
+        );
+
+        let mut log = GameLog::new();
+        log.record(LogEvent::Started {
+            players: vec![String::from("Bot 1"), String::from("x")],
+            rules: two_deals(),
+        });
+        for (seed, deal) in shuffle::get_duplicate_seeds(&[5; 32], 2).iter().zip(1..) {
+            log.record(LogEvent::ShuffleRevealed {
+                proof: shuffle::ShuffleProof::new(deal, seed, vec![], 0),
+            });
+        }
+        log.record(LogEvent::Finished {
+            score_board: ScoreBoard::default(),
+            winner: None,
+        });
+        assert_eq!(
+            duplicate.record_log(&log),
+            Err(DuplicateError::PlayersMismatch)
+        );
+
+        // a game dealt from another seed is refused
+        let mut log = GameLog::new();
+        log.record(LogEvent::Started {
+            players: players.clone(),
+            rules: two_deals(),
+        });
+        log.record(LogEvent::ShuffleRevealed {
+            proof: shuffle::ShuffleProof::new(1, &[1; 32], vec![], 0),
+        });
+        assert_eq!(duplicate.record_log(&log), Err(DuplicateError::WrongDeals));
+        let mut log = GameLog::new();
+        log.record(LogEvent::ShuffleRevealed {
+            proof: shuffle::ShuffleProof::new(0, &[5; 32], vec![], 0),
+        });
+        assert_eq!(duplicate.record_log(&log), Err(DuplicateError::WrongDeals));
+
+        let seeds = shuffle::get_duplicate_seeds(&[5; 32], 2);
+        let mut log = GameLog::new();
+        log.record(LogEvent::Started {
+            players,
+            rules: two_deals(),
+        });
+        log.record(LogEvent::ShuffleRevealed {
+            proof: shuffle::ShuffleProof::new(1, &seeds[0], vec![], 0),
+        });
+        assert_eq!(
+            duplicate.record_log(&log),
+            Err(DuplicateError::GameNotFinished)
+        );
+        // the second deal was never revealed
+        let mut unrevealed = log.clone();
+        unrevealed.record(LogEvent::Finished {
+            score_board: ScoreBoard::default(),
+            winner: None,
+        });
+        assert_eq!(
+            duplicate.record_log(&unrevealed),
+            Err(DuplicateError::WrongDeals)
+        );
+
+        log.record(LogEvent::ShuffleRevealed {
+            proof: shuffle::ShuffleProof::new(2, &seeds[1], vec![], 0),
+        });
+        log.record(LogEvent::Finished {
+            score_board: ScoreBoard::default(),
+            winner: None,
+        });
+        assert_eq!(duplicate.record_log(&log), Ok(()));
+        assert_eq!(duplicate.get_games().len(), 1);
+        // the same seating is only recorded once
+        assert_eq!(
+            duplicate.record_log(&log),
+            Err(DuplicateError::SeatingRecorded)
+        );
+        assert_eq!(duplicate.get_games().len(), 1);
+    }
+
+    #[test]
+    fn test_serve_args() {
+        let rules = RuleSet {
+            redeal: Reshuffle::NoFaceCard,
+            max_redeals: 1,
+            hook: true,
+            min_total_call: 8,
+            total_deals: 2,
+            ..RuleSet::nepali()
+        };
+        for rules in [two_deals(), rules] {
+            let duplicate = DuplicateMatch::new(bots(3), rules, [5; 32]).unwrap();
+            // the server hosting the game as told plays the rules of the match
+            let text = get_seatings_text(&duplicate);
+            let command = text.split('`').nth(1).unwrap();
+            let args: Vec<String> = command.split(' ').skip(2).map(String::from).collect();
+            let options = ServerOptions::from_args(&args).unwrap();
+            assert_eq!(options.get_rules(), duplicate.rules);
+            assert_eq!(options.total_players, 3);
+            assert_eq!(options.deal_seed, Some([5; 32]));
+        }
+    }
+
+    #[test]
+    fn test_command_from_args() {
+        let args = |line: &str| -> Vec<String> { line.split(' ').map(String::from).collect() };
+        let seed = hex::encode([9; 32]);
+        match DuplicateCommand::from_args(&args(&format!(
+            "new d.json --players ann,bob --bots 1 --deals 3 --seed {}",
+            seed
+        ))) {
+            Ok(DuplicateCommand::New { path, duplicate }) => {
+                assert_eq!(path, PathBuf::from("d.json"));
+                assert_eq!(duplicate.get_seating(0), vec!["ann", "bob", "Bot 1"]);
+                assert_eq!(duplicate.get_seed(), Ok([9; 32]));
+                assert!(get_seatings_text(&duplicate).contains(&seed));
+            }
+            result => panic!("unexpected {:?}", result),
+        }
+        assert_eq!(
+            DuplicateCommand::from_args(&args("record d.json game.json")),
+            Ok(DuplicateCommand::Record {
+                path: PathBuf::from("d.json"),
+                log_path: PathBuf::from("game.json"),
+            })
+        );
+        assert!(DuplicateCommand::from_args(&args("new d.json --seed xyz")).is_err());
+        assert!(DuplicateCommand::from_args(&args("play d.json extra")).is_err());
+    }
+}
@@ -1,178 +1,582 @@
-use std::collections::HashMap;
+use std::fmt;
+use std::time::{Duration, Instant};
 
-use crate::card::{Card, Suit, TRUMP_SUIT};
-use crate::deck;
-use crate::deck::Deck;
+use serde::{Deserialize, Serialize};
+
+use crate::bid::Bid;
+use crate::card::{Card, Suit, DEFAULT_TRUMP_SUIT};
+use crate::pin::PinError;
 use crate::player::Player;
+use crate::rules::{RuleSet, PARTNERSHIP_PLAYERS};
+use crate::score::{NilBid, ScoreBoard};
+use crate::shuffle::{get_redeal_seed, new_seed};
+
+// Default number of deals played in a match
+pub const DEFAULT_TOTAL_DEALS: u8 = 5;
+
+// the lowest and the highest calls known to the game
+pub const MIN_CALL: u8 = 2;
+pub const MAX_CALL: u8 = 8;
+// the call to take no tricks at all, allowed by the nil bid rule
+pub const NIL_CALL: u8 = 0;
 
-pub struct Game<'a> {
-    deck: Deck,
+pub const MIN_PLAYERS: usize = 2;
+pub const MAX_PLAYERS: usize = 5;
+
+pub struct Game {
+    rules: RuleSet,
     players: Vec<Player>,
-    default_calls: HashMap<u8, Call>,
-    current_round: Trick<'a>,
+    current_round: Trick,
+    last_round: Trick,
     total_rounds_count: u8,
     current_round_no: u8,
+    current_deal_no: u8,
+    dealer: usize,
+    current_turn: usize,
+    state: GameState,
+    score_board: ScoreBoard,
+    time_limits: TimeLimits,
+    // the seed of the shuffle of every deal, the decks are shuffled at random without them
+    shuffle_seeds: Vec<[u8; 32]>,
+    // the number of redeals asked for in every deal played so far
+    redeals: Vec<u8>,
+    // when the current player got the turn
+    turn_started: Instant,
+    // when the clock of the turn was paused, None while it runs
+    turn_paused: Option<Instant>,
+}
+
+/// TimeLimits are the optional clocks for a call and for a throw, the turn of a
+/// player who runs out of time is played for them
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct TimeLimits {
+    pub call: Option<Duration>,
+    pub throw: Option<Duration>,
+}
+
+/// Move is a call, the index of the card to throw from the hand or the ask for a
+/// redeal of a weak hand
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Move {
+    Call(u8),
+    Throw(usize),
+    Redeal,
 }
 
-struct Trick<'a> {
-    rounds: Vec<Round<'a>>,
-    winner: Participant<'a>,
-    lead_thrower: Participant<'a>,
+struct Trick {
+    rounds: Vec<Round>,
+    winner: Participant,
+    lead_thrower: Participant,
 }
 
-struct Participant<'a> {
-    player: &'a str,
+struct Participant {
+    player: String,
     suit: Suit,
-    priority: i32,
     value: String,
 }
 
-struct Round<'a> {
-    player: &'a str,
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Round {
+    player: String,
     card: Card,
 }
 
-#[derive(Clone, Copy, PartialEq, Debug)]
-pub enum Call {
-    Two(u8),
-    Three(u8),
-    Four(u8),
-    Five(u8),
-    Six(u8),
-    Seven(u8),
-    Eight(u8),
+/// GameState is the phase the game is currently in
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GameState {
+    // players are still being added
+    Waiting,
+    // players are making their calls for the deal
+    Calling,
+    // players are throwing cards
+    Playing,
+    // all the deals are played
+    Finished,
 }
 
-impl<'a> Game<'a> {
-    /// new_game initializes a new game with default calls and empty player vector
-    /// then once the game is initialized, the players can be added individually
-    /// ```rust
-    ///  let game = Game::new_game()
-    /// ```
-    pub fn new_game() -> Self {
-        let new_deck = Deck::new();
-        let mut game = Game {
-            deck: new_deck,
+#[derive(PartialEq, Debug)]
+pub enum GameError {
+    PlayerExists,
+    UnknownPlayer,
+    NotEnoughPlayers,
+    TooManyPlayers,
+    InvalidState,
+    NotPlayersTurn,
+    InvalidCall,
+    ForbiddenCall,
+    InvalidCard,
+    NoRedeal,
+    InvalidTeams,
+    // more redeals are allowed in a deal than the game supports
+    InvalidRedeals,
+    // the calls of the table can't add up to the minimum total call
+    InvalidMinTotal,
+    // the pin entered to reveal the hand of a player was refused
+    Pin(PinError),
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = match self {
+            GameError::Pin(e) => return write!(f, "{}", e),
+            GameError::PlayerExists => "Player already added in the game",
+            GameError::UnknownPlayer => "Player is not part of the game",
+            GameError::NotEnoughPlayers => "At least 2 players is needed",
+            GameError::TooManyPlayers => "Maximum 5 players can play",
+            GameError::InvalidState => "This action is not allowed right now",
+            GameError::NotPlayersTurn => "It's not the player's turn",
+            GameError::InvalidCall => "Invalid call supplied",
+            GameError::ForbiddenCall => "The calls can't add up to this as the last call",
+            GameError::InvalidCard => "This card can't be thrown",
+            GameError::NoRedeal => "A redeal can't be asked for now",
+            GameError::InvalidTeams => "A partnership needs exactly 4 players",
+            GameError::InvalidRedeals => "A deal can be redealt at most 10 times",
+            GameError::InvalidMinTotal => "The calls can't add up to the minimum total",
+        };
+        write!(f, "{}", msg)
+    }
+}
+
+impl Default for Game {
+    /// default initializes a new game with the default rules
+    fn default() -> Self {
+        Game {
+            rules: RuleSet::default(),
             players: vec![],
-            default_calls: HashMap::new(),
             current_round: Trick::new(),
+            last_round: Trick::new(),
             current_round_no: 0,
             total_rounds_count: 0,
+            current_deal_no: 0,
+            dealer: 0,
+            current_turn: 0,
+            state: GameState::Waiting,
+            score_board: ScoreBoard::default(),
+            time_limits: TimeLimits::default(),
+            shuffle_seeds: vec![],
+            redeals: vec![],
+            turn_started: Instant::now(),
+            turn_paused: None,
+        }
+    }
+}
+
+impl Game {
+    /// new_game initializes a new game with the calls of the rules and empty player vector
+    /// then once the game is initialized, the players can be added individually,
+    /// invalid rules are an error
+    /// ```rust
+    ///  # use callbreak::game::Game;
+    ///  # use callbreak::rules::RuleSet;
+    ///  let game = Game::new_game(RuleSet::default()).unwrap();
+    /// ```
+    pub fn new_game(rules: RuleSet) -> Result<Self, GameError> {
+        let mut game = Game::default();
+        game.set_rules(rules)?;
+        Ok(game)
+    }
+
+    /// set_rules replaces the rules of the game before it's started
+    pub fn set_rules(&mut self, rules: RuleSet) -> Result<(), GameError> {
+        if self.state != GameState::Waiting {
+            return Err(GameError::InvalidState);
+        }
+        rules.validate()?;
+        self.rules = RuleSet {
+            total_deals: rules.total_deals.max(1),
+            ..rules
         };
+        self.reset_calls();
+        Ok(())
+    }
 
-        game.initiate_calls();
-        game
+    pub fn get_rules(&self) -> &RuleSet {
+        &self.rules
     }
 
-    /// initiate_calls initiate default calls for the game
-    fn initiate_calls(&mut self) {
-        self.default_calls.insert(2, Call::Two(2));
-        self.default_calls.insert(3, Call::Three(3));
-        self.default_calls.insert(4, Call::Four(4));
-        self.default_calls.insert(5, Call::Five(5));
-        self.default_calls.insert(6, Call::Six(6));
-        self.default_calls.insert(7, Call::Seven(7));
-        self.default_calls.insert(8, Call::Eight(8));
+    /// set_call_range limits the calls the players can make, the range has to be
+    /// within the calls known to the game
+    pub fn set_call_range(&mut self, min_call: u8, max_call: u8) -> Result<(), GameError> {
+        if self.state != GameState::Waiting {
+            return Err(GameError::InvalidState);
+        }
+        let rules = RuleSet {
+            min_call,
+            max_call,
+            ..self.rules.clone()
+        };
+        rules.validate()?;
+        self.rules = rules;
+        self.reset_calls();
+        Ok(())
     }
 
+    /// reset_calls gives the players added so far the lowest call of the rules
+    fn reset_calls(&mut self) {
+        let lowest = Bid::lowest(&self.rules, self.players.len());
+        for p in self.players.iter_mut() {
+            p.set_call(lowest);
+        }
+    }
 
-    /// add_players adds individual player with a specific pin and a call value
-    // TODO: return Result or error from this function
-    pub fn add_players(&mut self, name: &str, pin: &u16, call: u8) {
+    /// set_total_deals sets the number of deals to be played before the game is finished
+    pub fn set_total_deals(&mut self, total_deals: u8) -> Result<(), GameError> {
+        if self.state != GameState::Waiting {
+            return Err(GameError::InvalidState);
+        }
+        self.rules.total_deals = total_deals.max(1);
+        Ok(())
+    }
+
+    /// set_shuffle_seeds makes the shuffle of every deal repeatable from its seed,
+    /// in the order of the deals
+    pub fn set_shuffle_seeds(&mut self, seeds: Vec<[u8; 32]>) -> Result<(), GameError> {
+        if self.state != GameState::Waiting {
+            return Err(GameError::InvalidState);
+        }
+        self.shuffle_seeds = seeds;
+        Ok(())
+    }
+
+    /// set_time_limits sets the clocks of the turns, None for no limit
+    pub fn set_time_limits(&mut self, time_limits: TimeLimits) -> Result<(), GameError> {
+        if self.state != GameState::Waiting {
+            return Err(GameError::InvalidState);
+        }
+        self.time_limits = time_limits;
+        Ok(())
+    }
+
+    pub fn get_time_limits(&self) -> TimeLimits {
+        self.time_limits
+    }
+
+    /// get_time_left returns the time the current player has left for the turn,
+    /// None if the turn has no limit
+    pub fn get_time_left(&self) -> Option<Duration> {
+        let limit = match self.state {
+            GameState::Calling => self.time_limits.call,
+            GameState::Playing => self.time_limits.throw,
+            _ => None,
+        }?;
+        let used = self
+            .turn_paused
+            .unwrap_or_else(Instant::now)
+            .saturating_duration_since(self.turn_started);
+        Some(limit.saturating_sub(used))
+    }
+
+    /// is_out_of_time checks if the current player has used up the time of the turn
+    pub fn is_out_of_time(&self) -> bool {
+        self.get_time_left() == Some(Duration::ZERO)
+    }
+
+    /// restart_turn_clock gives the current player the full time again
+    pub fn restart_turn_clock(&mut self) {
+        self.turn_started = Instant::now();
+        self.turn_paused = None;
+    }
+
+    /// pause_turn_clock stops the clock of the turn, e.g. while the game is left for the menu
+    pub fn pause_turn_clock(&mut self) {
+        self.turn_paused.get_or_insert_with(Instant::now);
+    }
+
+    /// resume_turn_clock starts the paused clock again with the time the player had left
+    pub fn resume_turn_clock(&mut self) {
+        if let Some(paused) = self.turn_paused.take() {
+            self.turn_started += paused.elapsed();
+        }
+    }
+
+    /// get_timeout_move returns the move made for a player who ran out of time:
+    /// the lowest call, or the lowest eligible card saving the trumps
+    pub fn get_timeout_move(&self) -> Option<Move> {
+        let player = self.get_current_player()?;
+        match self.state {
+            GameState::Calling => {
+                let bids = self.get_allowed_bids();
+                bids.iter()
+                    .find(|b| !b.is_nil())
+                    .or(bids.first())
+                    .map(|b| Move::Call(b.get_value()))
+            }
+            GameState::Playing => {
+                let cards = player.get_cards();
+                self.get_eligible_cards(player.get_name())
+                    .ok()?
+                    .into_iter()
+                    .min_by_key(|i| {
+                        (
+                            cards[*i].get_suit() == self.rules.trump_suit,
+                            cards[*i].get_priority(),
+                        )
+                    })
+                    .map(Move::Throw)
+            }
+            _ => None,
+        }
+    }
+
+    /// play_move makes the call or throws the card for the player
+    pub fn play_move(&mut self, player_name: &str, player_move: Move) -> Result<(), GameError> {
+        match player_move {
+            Move::Call(call) => self.place_call(player_name, call),
+            Move::Throw(card_idx) => self.throw(player_name, card_idx),
+            Move::Redeal => self.redeal(player_name),
+        }
+    }
+
+    /// get_call_range returns the lowest and the highest call a player can make at the
+    /// table, leaving out the nil call
+    pub fn get_call_range(&self) -> (u8, u8) {
+        self.rules.get_call_range(self.players.len())
+    }
+
+    /// get_bids returns every call a player can make at the table, nil first if allowed
+    pub fn get_bids(&self) -> Vec<Bid> {
+        Bid::get_bids(&self.rules, self.players.len())
+    }
+
+    /// get_allowed_bids returns the calls the current player can make, without the ones
+    /// the rules forbid to the last caller
+    pub fn get_allowed_bids(&self) -> Vec<Bid> {
+        let forbidden = self.get_forbidden_bids();
+        self.get_bids()
+            .into_iter()
+            .filter(|b| !forbidden.contains(b))
+            .collect()
+    }
+
+    /// get_forbidden_bids returns the calls the current player can't make, only the
+    /// dealer calls last and has any
+    pub fn get_forbidden_bids(&self) -> Vec<Bid> {
+        if self.state != GameState::Calling || self.current_turn != self.dealer {
+            return vec![];
+        }
+        self.rules
+            .get_forbidden_bids(self.get_call_total(), self.players.len())
+    }
+
+    /// get_call_total returns the calls made so far in the deal added up
+    pub fn get_call_total(&self) -> u8 {
+        self.players
+            .iter()
+            .filter(|p| self.has_called(p.get_name()))
+            .map(|p| p.get_call().get_value())
+            .sum()
+    }
+
+    /// get_nil_bid returns the scoring of the nil call, None if it can't be called
+    pub fn get_nil_bid(&self) -> Option<NilBid> {
+        self.rules.get_nil_bid()
+    }
+
+    /// add_players adds individual player with a specific pin, the call is made
+    /// by the player later at the start of every deal
+    pub fn add_players(&mut self, name: &str, pin: &str) -> Result<(), GameError> {
+        if self.state != GameState::Waiting {
+            return Err(GameError::InvalidState);
+        }
         // check if the player name is already there
         // we can use a hashmap as well here, but for the short number of players it works for now
         for p in self.players.iter() {
             if p.get_name() == name {
-                println!("Player already added in the game");
-                return;
+                return Err(GameError::PlayerExists);
             }
         }
+        if self.players.len() >= MAX_PLAYERS {
+            return Err(GameError::TooManyPlayers);
+        }
 
-        let player_call = self.default_calls.get(&call).expect("invalid call supplied");
-        let new_player = Player::new(name, pin, *player_call);
+        let new_player = Player::new(name, pin, Bid::lowest(&self.rules, self.players.len()));
         self.players.push(new_player);
+        Ok(())
     }
 
+    /// remove_player takes the player out of the game before it's started
+    pub fn remove_player(&mut self, name: &str) -> Result<(), GameError> {
+        if self.state != GameState::Waiting {
+            return Err(GameError::InvalidState);
+        }
+        let player_idx = self.get_player_index(name)?;
+        self.players.remove(player_idx);
+        Ok(())
+    }
 
     /// starts the game by shuffling and giving equal number of cards to the players
-    pub fn start(&mut self) {
+    pub fn start(&mut self) -> Result<(), GameError> {
+        if self.state != GameState::Waiting {
+            return Err(GameError::InvalidState);
+        }
         // check if at least 2 players are added
-        if self.players.len() < 2 {
-            println!("At least 2 players is needed");
-            return;
+        if self.players.len() < MIN_PLAYERS {
+            return Err(GameError::NotEnoughPlayers);
+        }
+        if self.rules.partnership && self.players.len() != PARTNERSHIP_PLAYERS {
+            return Err(GameError::InvalidTeams);
         }
+        self.rules.validate_players(self.players.len())?;
+        // the calls can't go over the hand at the size of the table
+        self.reset_calls();
 
-        // distribute the cards to each player
-        let mut i = 0;
-        loop {
-            if self.deck.total_len() == 0 {
-                break;
-            }
-            // shuffle the deck first
-            self.deck.shuffle();
-            self.players[i].add_card(self.deck.distribute());
-            i += 1;
-            if i >= self.players.len() {
-                i = 0
+        // the scoreboard keeps a column for every team, a single player is a team of one
+        let names = (0..self.get_teams().len())
+            .map(|team| self.get_team_name(team))
+            .collect();
+        self.score_board = ScoreBoard::with_overtricks(names, self.rules.overtricks)
+            .with_nil_bid(self.get_nil_bid());
+        self.start_deal();
+        Ok(())
+    }
+
+    /// start_deal shuffles a fresh deck and distributes it among the players, the deck is
+    /// shuffled again while the rules find a hand too weak to play
+    fn start_deal(&mut self) {
+        self.current_deal_no += 1;
+        self.redeals.push(0);
+        self.deal_cards();
+    }
+
+    /// deal_cards deals the hands of the current deal from a fresh deck, every redeal
+    /// of the deal gets a shuffle of its own
+    fn deal_cards(&mut self) {
+        self.current_round_no = 0;
+        self.current_round = Trick::new();
+        self.last_round = Trick::new();
+        for p in self.players.iter_mut() {
+            p.clear_deal();
+        }
+
+        // every player gets the same number of cards, the low cards left over are taken out
+        self.total_rounds_count = RuleSet::get_hand_size(self.players.len());
+        let seed = match self.shuffle_seeds.get(self.current_deal_no as usize - 1) {
+            Some(seed) => get_redeal_seed(seed, self.get_redeals(self.current_deal_no)),
+            None => new_seed(),
+        };
+
+        // distribute the cards to each player starting from the one next to the dealer
+        let hands = self
+            .rules
+            .deal(seed, self.players.len(), self.next_seat(self.dealer));
+        for (player, hand) in self.players.iter_mut().zip(hands) {
+            for card in hand {
+                player.add_card(card);
             }
         }
 
-        self.total_rounds_count = deck::DECK_LEN / self.players.len() as u8;
+        self.current_turn = self.next_seat(self.dealer);
+        self.state = GameState::Calling;
+        self.restart_turn_clock();
     }
 
+    fn next_seat(&self, seat: usize) -> usize {
+        (seat + 1) % self.players.len()
+    }
 
     /// get_player_index returns the player index with the player name
-    fn get_player_index(&self, player_name: &str) -> usize {
-        let mut idx = self.players.len() - 1;
-        for (i, c) in self.players.iter().enumerate() {
-            if c.get_name() == player_name {
-                idx = i
-            }
+    fn get_player_index(&self, player_name: &str) -> Result<usize, GameError> {
+        self.players
+            .iter()
+            .position(|p| p.get_name() == player_name)
+            .ok_or(GameError::UnknownPlayer)
+    }
+
+    /// check_turn verifies that the game is in the given state and it's the player's turn
+    fn check_turn(&self, player_name: &str, state: GameState) -> Result<usize, GameError> {
+        if self.state != state {
+            return Err(GameError::InvalidState);
+        }
+        let player_idx = self.get_player_index(player_name)?;
+        if player_idx != self.current_turn {
+            return Err(GameError::NotPlayersTurn);
+        }
+        Ok(player_idx)
+    }
+
+    /// place_call sets the call of the player for the current deal, once every
+    /// player has called the game moves on to the throwing of the cards
+    pub fn place_call(&mut self, player_name: &str, call: u8) -> Result<(), GameError> {
+        let player_idx = self.check_turn(player_name, GameState::Calling)?;
+        let bid = Bid::new(call, &self.rules, self.players.len())?;
+        if self.get_forbidden_bids().contains(&bid) {
+            return Err(GameError::ForbiddenCall);
+        }
+        self.players[player_idx].set_call(bid);
+
+        self.current_turn = self.next_seat(player_idx);
+        // the calling is over once the turn goes back to the player next to the dealer
+        if self.current_turn == self.next_seat(self.dealer) {
+            self.state = GameState::Playing;
         }
-        idx
+        self.restart_turn_clock();
+        Ok(())
+    }
+
+    /// can_redeal checks if the player may ask for a redeal instead of calling: their
+    /// hand is weak enough for the rules and the deal has redeals left
+    pub fn can_redeal(&self, player_name: &str) -> bool {
+        let player_idx = match self.check_turn(player_name, GameState::Calling) {
+            Ok(idx) => idx,
+            Err(_) => return false,
+        };
+        self.get_redeals(self.current_deal_no) < self.rules.max_redeals
+            && self
+                .rules
+                .redeal
+                .is_needed(self.players[player_idx].get_cards(), self.rules.trump_suit)
+    }
+
+    /// redeal throws in the hands of the deal and deals them again from a fresh deck,
+    /// the calls made so far are dropped and the calling starts over
+    pub fn redeal(&mut self, player_name: &str) -> Result<(), GameError> {
+        self.check_turn(player_name, GameState::Calling)?;
+        if !self.can_redeal(player_name) {
+            return Err(GameError::NoRedeal);
+        }
+        if let Some(redeals) = self.redeals.last_mut() {
+            *redeals += 1;
+        }
+        self.deal_cards();
+        Ok(())
+    }
+
+    /// get_redeals returns the number of redeals asked for in the deal
+    pub fn get_redeals(&self, deal_no: u8) -> u8 {
+        (deal_no as usize)
+            .checked_sub(1)
+            .and_then(|i| self.redeals.get(i))
+            .copied()
+            .unwrap_or(0)
     }
 
     /// throw takes player name and card the user want to throw
     /// and throw it in a single round in the game
-    pub fn throw(&mut self, player_name: &'a str, card_idx: usize) {
-        // check if this is a new round or an existing round
-        let mut is_new_round = true;
-        if !self.current_round.rounds.is_empty() && !self.current_round.winner.player.is_empty() {
-            is_new_round = false
+    pub fn throw(&mut self, player_name: &str, card_idx: usize) -> Result<(), GameError> {
+        let player_idx = self.check_turn(player_name, GameState::Playing)?;
+        if !self.get_eligible_cards(player_name)?.contains(&card_idx) {
+            return Err(GameError::InvalidCard);
         }
-        // throw the current players card to the
-        let player_idx = self.get_player_index(player_name);
+
+        // check if this is a new round or an existing round
+        let is_new_round = self.current_round.rounds.is_empty();
         // throw the card that the player passed to the round
         let throwable_card = self.players[player_idx].throw(card_idx);
         // get the current winner of the round
         if is_new_round {
             self.current_round_no += 1;
-            self.current_round.winner = Participant {
-                player: player_name,
-                suit: throwable_card.get_suit(),
-                priority: throwable_card.get_priority(),
-                value: throwable_card.get_value().to_string(),
-            };
-            self.current_round.lead_thrower = Participant {
-                player: player_name,
-                suit: throwable_card.get_suit(),
-                priority: throwable_card.get_priority(),
-                value: throwable_card.get_value().to_string(),
-            };
-            let new_round = Round {
-                player: player_name,
-                card: throwable_card,
-            };
-            // add the card to the round
-            self.current_round.rounds.push(new_round);
-        } else {
-            let new_round = Round {
-                player: player_name,
-                card: throwable_card,
-            };
-            // add the card to the round
-            self.current_round.rounds.push(new_round);
+            self.current_round.winner = Participant::new(player_name, &throwable_card);
+            self.current_round.lead_thrower = Participant::new(player_name, &throwable_card);
+        }
+        let new_round = Round {
+            player: player_name.to_string(),
+            card: throwable_card,
+        };
+        // add the card to the round
+        self.current_round.rounds.push(new_round);
 
+        if !is_new_round {
             // get the current winner
             self.get_round_winner();
         }
@@ -181,128 +585,733 @@ impl<'a> Game<'a> {
         if self.current_round.rounds.len() == self.players.len() {
             // add points to the winner
             self.add_points_to_winner();
+            // the winner of the round throws first in the next round
+            self.current_turn = self.get_player_index(&self.current_round.winner.player)?;
             self.clear_round();
-        }
-
 
-        // it's the final round
-        if self.current_round_no == self.total_rounds_count {
-            // Check who won the game
-            self.find_game_winner();
+            // it's the final round
+            if self.current_round_no == self.total_rounds_count {
+                self.finish_deal();
+            }
+        } else {
+            self.current_turn = self.next_seat(player_idx);
         }
+        self.restart_turn_clock();
+        Ok(())
     }
 
-    /// clears current round and adds an empty round
+    /// clears current round and keeps it as the last round
     pub fn clear_round(&mut self) {
-        self.current_round = Trick::new();
+        self.last_round = std::mem::replace(&mut self.current_round, Trick::new());
     }
 
     /// gets the current round winner
     pub fn get_round_winner(&mut self) {
-        // get all the round cards
-        // set rules for winner of the game
-        // check if any spade is there in the card
-        for s in self.current_round.rounds.iter() {
-            if s.card.get_value() == self.current_round.winner.value {
-                continue;
-            }
-            if s.card.get_suit() == TRUMP_SUIT {
-                // get the highest suit if the current winning suit is 'spade'
-                if self.current_round.winner.suit == TRUMP_SUIT {
-                    if s.card.get_priority() > self.current_round.winner.priority {
-                        self.current_round.winner = Participant::new(s.player,
-                                                                     s.card.get_suit(),
-                                                                     s.card.get_priority(),
-                                                                     s.card.get_value().to_string());
-                    }
-                } else {
-                    // if the current winner is not spade then by default spade wins
-                    self.current_round.winner = Participant::new(s.player,
-                                                                 s.card.get_suit(),
-                                                                 s.card.get_priority(),
-                                                                 s.card.get_value().to_string());
-                }
-            } else if self.current_round.winner.suit != TRUMP_SUIT {
-                // if the current winner is not spade then we need to see if the lead thrower and the current thrower
-                // has the same suit, then we need to check for the priority and decide the winner
-                // check for a higher priority card
-                if s.card.get_priority() > self.current_round.winner.priority && s.card.get_suit() == self.current_round.lead_thrower.suit {
-                    self.current_round.winner = Participant::new(s.player,
-                                                                 s.card.get_suit(),
-                                                                 s.card.get_priority(),
-                                                                 s.card.get_value().to_string());
-                }
-            }
+        let s = match self.current_round.rounds.last() {
+            Some(s) => s,
+            None => return,
+        };
+        let winner = &self.current_round.winner;
+        let winning_card = Card::new(winner.suit, winner.value.to_string());
+
+        if s.card.beats(&winning_card, self.rules.trump_suit) {
+            self.current_round.winner = Participant::new(&s.player, &s.card);
+        }
+    }
+
+    /// get_eligible_cards returns the index of the cards that the player can throw
+    pub fn get_eligible_cards(&self, name: &str) -> Result<Vec<usize>, GameError> {
+        let idx = self.get_player_index(name)?;
+        let lead_suit = self.current_round.rounds.first().map(|r| r.card.get_suit());
+        let winning_card = self.get_winning_card();
+        Ok(self.players[idx].get_eligible_cards(lead_suit, winning_card.as_ref(), &self.rules))
+    }
+
+    /// get_winning_card returns the card winning the ongoing round
+    fn get_winning_card(&self) -> Option<Card> {
+        if self.current_round.rounds.is_empty() {
+            return None;
         }
+        let winner = &self.current_round.winner;
+        Some(Card::new(winner.suit, winner.value.to_string()))
     }
 
     pub fn get_player_eligible_cards(&self, name: &str) {
-        let idx = self.get_player_index(name);
-        let lead_card = Card::new(self.current_round.lead_thrower.suit, self.current_round.lead_thrower.value.to_string());
-        self.players[idx].show_eligible_cards(&lead_card);
+        if let Ok(idx) = self.get_player_index(name) {
+            let lead_card = Card::new(
+                self.current_round.lead_thrower.suit,
+                self.current_round.lead_thrower.value.to_string(),
+            );
+            let winning_card = self.get_winning_card().unwrap_or_else(|| lead_card.clone());
+            self.players[idx].show_eligible_cards(&lead_card, &winning_card, &self.rules);
+        }
     }
 
     // add_points_to_winner adds the points to the current winner
     fn add_points_to_winner(&mut self) {
-        let lead_winner = &self.current_round.lead_thrower;
+        let round_winner = &self.current_round.winner;
 
         // find the winner index
         for p in self.players.iter_mut() {
-            if p.get_name() == lead_winner.player {
+            if p.get_name() == round_winner.player {
                 p.add_points(1);
                 break;
             }
         }
     }
 
-    fn find_game_winner(&self) {
-        let mut max_points = 0;
-        let mut player = "";
-        for p in self.players.iter() {
-            if max_points < p.get_points() {
-                max_points = p.get_points();
-                player = p.get_name();
+    /// finish_deal records the scores of the deal and moves on to the next deal
+    /// or finishes the game if all the deals are played
+    fn finish_deal(&mut self) {
+        // the partners add up their calls into the contract of the team and their tricks
+        let calls_and_tricks: Vec<(Bid, u8)> = self
+            .get_teams()
+            .iter()
+            .map(|team| {
+                let players = || team.iter().map(|seat| &self.players[*seat]);
+                (
+                    players().map(|p| p.get_call()).sum(),
+                    players().map(|p| p.get_points()).sum(),
+                )
+            })
+            .collect();
+        self.score_board
+            .record_deal(self.current_deal_no, &calls_and_tricks);
+
+        if self.current_deal_no >= self.rules.total_deals {
+            self.state = GameState::Finished;
+            return;
+        }
+        self.dealer = self.next_seat(self.dealer);
+        self.start_deal();
+    }
+
+    pub fn get_players(&self) -> &[Player] {
+        &self.players
+    }
+
+    pub fn get_state(&self) -> GameState {
+        self.state
+    }
+
+    /// get_current_player returns the player whose turn it is
+    pub fn get_current_player(&self) -> Option<&Player> {
+        match self.state {
+            GameState::Calling | GameState::Playing => self.players.get(self.current_turn),
+            _ => None,
+        }
+    }
+
+    /// verify_pin checks the pin of the player before their hand is shown
+    pub fn verify_pin(&mut self, name: &str, pin: &str) -> Result<(), GameError> {
+        let idx = self.get_player_index(name)?;
+        self.players[idx].verify_pin(pin).map_err(GameError::Pin)
+    }
+
+    /// get_current_round returns the cards thrown in the ongoing round
+    pub fn get_current_round(&self) -> &[Round] {
+        &self.current_round.rounds
+    }
+
+    /// get_last_round returns the cards thrown in the previous round
+    pub fn get_last_round(&self) -> &[Round] {
+        &self.last_round.rounds
+    }
+
+    /// get_last_round_winner returns the winner of the previous round
+    pub fn get_last_round_winner(&self) -> Option<&str> {
+        if self.last_round.rounds.is_empty() {
+            return None;
+        }
+        Some(self.last_round.winner.player.as_str())
+    }
+
+    /// has_called checks if the player has made the call for the current deal
+    pub fn has_called(&self, player_name: &str) -> bool {
+        let player_idx = match self.get_player_index(player_name) {
+            Ok(idx) => idx,
+            Err(_) => return false,
+        };
+        match self.state {
+            GameState::Waiting => false,
+            // the players from the one next to the dealer up to the current turn have called
+            GameState::Calling => {
+                let first_caller = self.next_seat(self.dealer);
+                let total = self.players.len();
+                (player_idx + total - first_caller) % total
+                    < (self.current_turn + total - first_caller) % total
             }
+            GameState::Playing | GameState::Finished => true,
         }
+    }
+
+    /// get_round_info returns the current round number and the total rounds of the deal
+    pub fn get_round_info(&self) -> (u8, u8) {
+        (self.current_round_no, self.total_rounds_count)
+    }
+
+    /// get_deal_info returns the current deal number and the total deals of the game
+    pub fn get_deal_info(&self) -> (u8, u8) {
+        (self.current_deal_no, self.rules.total_deals)
+    }
 
-        if !player.is_empty() {
-            println!("{} Won the game", player)
+    pub fn get_score_board(&self) -> &ScoreBoard {
+        &self.score_board
+    }
+
+    /// get_teams returns the seats of every team of the game
+    pub fn get_teams(&self) -> Vec<Vec<usize>> {
+        self.rules.get_teams(self.players.len())
+    }
+
+    /// get_team_name joins the names of the players of the team, e.g. "a & c"
+    pub fn get_team_name(&self, team: usize) -> String {
+        self.get_teams()
+            .get(team)
+            .map(|seats| {
+                seats
+                    .iter()
+                    .map(|seat| self.players[*seat].get_name())
+                    .collect::<Vec<&str>>()
+                    .join(" & ")
+            })
+            .unwrap_or_default()
+    }
+
+    /// get_game_winner returns the winner once all the deals are played, the name of
+    /// the team in a partnership
+    pub fn get_game_winner(&self) -> Option<String> {
+        if self.state != GameState::Finished {
+            return None;
         }
+        self.score_board.winner()
     }
 }
 
-impl<'a> Trick<'a> {
-    pub fn new() -> Trick<'a> {
+impl Trick {
+    pub fn new() -> Trick {
         Trick {
             rounds: vec![],
-            winner: Participant {
-                player: "",
-                suit: TRUMP_SUIT,
-                priority: 0,
-                value: "".to_string(),
-            },
-            lead_thrower: Participant {
-                player: "",
-                suit: TRUMP_SUIT,
-                priority: 0,
-                value: "".to_string(),
-            },
+            winner: Participant::empty(),
+            lead_thrower: Participant::empty(),
         }
     }
 }
 
-impl<'a> Participant<'a> {
-    pub fn new(player_name: &str, suit: Suit, priority: i32, value: String) -> Participant {
+impl Participant {
+    pub fn new(player_name: &str, card: &Card) -> Participant {
         Participant {
-            player: player_name,
-            suit,
-            priority,
-            value,
+            player: player_name.to_string(),
+            suit: card.get_suit(),
+            value: card.get_value().to_string(),
+        }
+    }
+
+    fn empty() -> Participant {
+        Participant {
+            player: "".to_string(),
+            suit: DEFAULT_TRUMP_SUIT,
+            value: "".to_string(),
         }
     }
 }
 
+impl Round {
+    pub fn new(player_name: &str, card: Card) -> Round {
+        Round {
+            player: player_name.to_string(),
+            card,
+        }
+    }
+
+    pub fn get_player(&self) -> &str {
+        self.player.as_str()
+    }
+
+    pub fn get_card(&self) -> &Card {
+        &self.card
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::Reshuffle;
+
+    fn new_started_game(players: &[&str]) -> Game {
+        let mut game = Game::default();
+        for p in players {
+            game.add_players(p, "1234").unwrap();
+        }
+        game.start().unwrap();
+        game
+    }
+
+    fn current_player_name(game: &Game) -> String {
+        game.get_current_player().unwrap().get_name().to_string()
+    }
+
+    /// play_round throws the first eligible card of every player for one round
+    fn play_round(game: &mut Game) {
+        for _ in 0..game.get_players().len() {
+            let name = current_player_name(game);
+            let card_idx = game.get_eligible_cards(&name).unwrap()[0];
+            game.throw(&name, card_idx).unwrap();
+        }
+    }
 
+    fn call_all(game: &mut Game, call: u8) {
+        for _ in 0..game.get_players().len() {
+            let name = current_player_name(game);
+            game.place_call(&name, call).unwrap();
+        }
+    }
 
+    #[test]
+    fn test_add_players() {
+        let mut game = Game::default();
+        assert_eq!(game.add_players("a", "1234"), Ok(()));
+        assert_eq!(game.add_players("a", "1234"), Err(GameError::PlayerExists));
+        assert_eq!(game.start(), Err(GameError::NotEnoughPlayers));
 
+        game.add_players("b", "1234").unwrap();
+        game.add_players("c", "1234").unwrap();
+        game.add_players("d", "1234").unwrap();
+        game.add_players("e", "1234").unwrap();
+        assert_eq!(
+            game.add_players("f", "1234"),
+            Err(GameError::TooManyPlayers)
+        );
 
+        assert_eq!(game.remove_player("f"), Err(GameError::UnknownPlayer));
+        game.remove_player("b").unwrap();
+        let names: Vec<&str> = game.get_players().iter().map(|p| p.get_name()).collect();
+        assert_eq!(names, vec!["a", "c", "d", "e"]);
+        game.start().unwrap();
+        assert_eq!(game.remove_player("a"), Err(GameError::InvalidState));
+    }
+
+    #[test]
+    fn test_set_call_range() {
+        let mut game = Game::default();
+        assert_eq!(game.get_call_range(), (2, 8));
+        assert_eq!(game.set_call_range(4, 3), Err(GameError::InvalidCall));
+        assert_eq!(game.set_call_range(1, 5), Err(GameError::InvalidCall));
+
+        game.set_call_range(3, 6).unwrap();
+        assert_eq!(game.get_call_range(), (3, 6));
+
+        game.set_total_deals(2).unwrap();
+        game.add_players("a", "1234").unwrap();
+        game.add_players("b", "1234").unwrap();
+        game.start().unwrap();
+        assert_eq!(game.get_deal_info(), (1, 2));
+        assert_eq!(game.place_call("b", 2), Err(GameError::InvalidCall));
+        assert_eq!(game.place_call("b", 3), Ok(()));
+        assert_eq!(game.set_total_deals(3), Err(GameError::InvalidState));
+    }
+
+    #[test]
+    fn test_rules() {
+        let rules = RuleSet {
+            trump_suit: Suit::Hearts,
+            min_call: 3,
+            total_deals: 2,
+            ..RuleSet::indian()
+        };
+        let mut game = Game::new_game(rules.clone()).unwrap();
+        assert_eq!(game.get_rules(), &rules);
+        assert_eq!(game.get_call_range(), (3, 8));
+        assert_eq!(game.get_deal_info(), (0, 2));
+
+        // the call range is kept in the rules
+        game.set_call_range(4, 5).unwrap();
+        assert_eq!(
+            (game.get_rules().min_call, game.get_rules().max_call),
+            (4, 5)
+        );
+        let invalid = RuleSet {
+            max_call: 9,
+            ..RuleSet::default()
+        };
+        assert_eq!(game.set_rules(invalid.clone()), Err(GameError::InvalidCall));
+        assert_eq!(Game::new_game(invalid).err(), Some(GameError::InvalidCall));
+        let invalid = RuleSet {
+            max_redeals: 11,
+            ..RuleSet::default()
+        };
+        assert_eq!(
+            Game::new_game(invalid).err(),
+            Some(GameError::InvalidRedeals)
+        );
+
+        // two players can't reach a minimum total the largest table can
+        let mut two_players = Game::new_game(RuleSet {
+            min_total_call: 20,
+            ..RuleSet::default()
+        })
+        .unwrap();
+        two_players.add_players("a", "1234").unwrap();
+        two_players.add_players("b", "1234").unwrap();
+        assert_eq!(two_players.start(), Err(GameError::InvalidMinTotal));
+
+        game.add_players("a", "1234").unwrap();
+        game.add_players("b", "1234").unwrap();
+        game.start().unwrap();
+        assert_eq!(game.set_rules(rules), Err(GameError::InvalidState));
+        // every hand has a trump and a face card under the indian rules
+        for p in game.get_players() {
+            assert!(!Reshuffle::NoTrumpOrFaceCard.is_needed(p.get_cards(), Suit::Hearts));
+        }
+    }
+
+    #[test]
+    fn test_time_limits() {
+        let mut game = Game::default();
+        game.add_players("a", "1234").unwrap();
+        game.add_players("b", "1234").unwrap();
+        let limits = TimeLimits {
+            call: Some(Duration::ZERO),
+            throw: Some(Duration::from_secs(60)),
+        };
+        game.set_time_limits(limits).unwrap();
+        assert_eq!(game.get_time_left(), None);
+        game.start().unwrap();
+        assert_eq!(game.set_time_limits(limits), Err(GameError::InvalidState));
+
+        // the lowest call is made for the player out of time
+        assert!(game.is_out_of_time());
+        assert_eq!(game.get_timeout_move(), Some(Move::Call(2)));
+        for _ in 0..2 {
+            let name = current_player_name(&game);
+            let timeout_move = game.get_timeout_move().unwrap();
+            game.play_move(&name, timeout_move).unwrap();
+        }
+
+        // the clock starts again for the throw, which is the lowest card saving the trumps
+        assert_eq!(game.get_state(), GameState::Playing);
+        assert!(!game.is_out_of_time());
+        assert!(game.get_time_left().unwrap() > Duration::from_secs(59));
+        let name = current_player_name(&game);
+        let cards = game.get_players()[game.get_player_index(&name).unwrap()].get_cards();
+        let lowest = match game.get_timeout_move() {
+            Some(Move::Throw(card_idx)) => &cards[card_idx],
+            other => panic!("unexpected move {:?}", other),
+        };
+        assert!(cards.iter().all(
+            |c| (lowest.get_suit() == Suit::Spade, lowest.get_priority())
+                <= (c.get_suit() == Suit::Spade, c.get_priority())
+        ));
+    }
+
+    #[test]
+    fn test_pause_turn_clock() {
+        let mut game = Game::default();
+        game.add_players("a", "1234").unwrap();
+        game.add_players("b", "1234").unwrap();
+        game.set_time_limits(TimeLimits {
+            call: Some(Duration::from_secs(60)),
+            throw: None,
+        })
+        .unwrap();
+        game.start().unwrap();
+        // half of the time was used before the pause
+        game.turn_started -= Duration::from_secs(30);
+
+        game.pause_turn_clock();
+        let left = game.get_time_left().unwrap();
+        std::thread::sleep(Duration::from_millis(10));
+        assert_eq!(game.get_time_left(), Some(left));
+
+        // the player gets back the time they had left, not the full time
+        game.resume_turn_clock();
+        let resumed = game.get_time_left().unwrap();
+        assert!(resumed <= left && resumed > Duration::from_secs(29));
+    }
+
+    #[test]
+    fn test_start_deals_cards() {
+        let game = new_started_game(&["a", "b", "c", "d"]);
+
+        assert_eq!(game.get_state(), GameState::Calling);
+        assert_eq!(game.get_round_info(), (0, 13));
+        assert_eq!(game.get_deal_info(), (1, DEFAULT_TOTAL_DEALS));
+        for p in game.get_players() {
+            assert_eq!(p.get_cards().len(), 13);
+        }
+        // the player next to the dealer calls first
+        assert_eq!(current_player_name(&game), "b");
+    }
+
+    #[test]
+    fn test_place_call() {
+        let mut game = new_started_game(&["a", "b", "c"]);
+
+        assert_eq!(game.place_call("a", 3), Err(GameError::NotPlayersTurn));
+        assert_eq!(game.place_call("b", 9), Err(GameError::InvalidCall));
+        assert_eq!(game.throw("b", 0), Err(GameError::InvalidState));
+
+        call_all(&mut game, 3);
+        assert_eq!(game.get_state(), GameState::Playing);
+        assert_eq!(game.get_players()[0].get_call().get_value(), 3);
+        assert_eq!(current_player_name(&game), "b");
+    }
+
+    #[test]
+    fn test_throw_follows_suit() {
+        let mut game = new_started_game(&["a", "b"]);
+        call_all(&mut game, 2);
+
+        let lead = current_player_name(&game);
+        game.throw(&lead, 0).unwrap();
+
+        let next = current_player_name(&game);
+        let eligible = game.get_eligible_cards(&next).unwrap();
+        let hand_len = game.get_players()[game.get_player_index(&next).unwrap()]
+            .get_cards()
+            .len();
+        if let Some(invalid) = (0..hand_len).find(|i| !eligible.contains(i)) {
+            assert_eq!(game.throw(&next, invalid), Err(GameError::InvalidCard));
+        }
+        game.throw(&next, eligible[0]).unwrap();
+
+        assert_eq!(game.get_current_round().len(), 0);
+        assert_eq!(game.get_last_round().len(), 2);
+        let winner_points: u8 = game.get_players().iter().map(|p| p.get_points()).sum();
+        assert_eq!(winner_points, 1);
+    }
+
+    #[test]
+    fn test_round_winner() {
+        let mut game = new_started_game(&["a", "b", "c"]);
+        game.current_round.rounds.push(Round {
+            player: "a".to_string(),
+            card: Card::new(Suit::Hearts, "10".to_string()),
+        });
+        game.current_round.winner = Participant::new("a", &game.current_round.rounds[0].card);
+
+        let throws = [
+            ("b", Suit::Hearts, "K", "b"),
+            ("c", Suit::Club, "A", "b"),
+            ("a", Suit::Spade, "2", "a"),
+            ("b", Suit::Spade, "J", "b"),
+            ("c", Suit::Hearts, "A", "b"),
+        ];
+        for (player, suit, value, winner) in throws {
+            game.current_round.rounds.push(Round {
+                player: player.to_string(),
+                card: Card::new(suit, value.to_string()),
+            });
+            game.get_round_winner();
+            assert_eq!(game.current_round.winner.player, winner);
+        }
+    }
+
+    #[test]
+    fn test_table_sizes() {
+        for (players, hand_size) in [
+            (&["a", "b"][..], 26),
+            (&["a", "b", "c"][..], 17),
+            (&["a", "b", "c", "d", "e"][..], 10),
+        ] {
+            let mut game = new_started_game(players);
+            assert_eq!(game.get_round_info(), (0, hand_size));
+            assert!(game
+                .get_players()
+                .iter()
+                .all(|p| p.get_cards().len() == hand_size as usize));
+            call_all(&mut game, 2);
+            for _ in 0..hand_size {
+                play_round(&mut game);
+            }
+            // every round is played and the next deal is dealt in full
+            assert_eq!(game.get_deal_info().0, 2);
+            let tricks: u8 = game.get_score_board().get_deals()[0]
+                .entries
+                .iter()
+                .map(|e| e.tricks)
+                .sum();
+            assert_eq!(tricks, hand_size);
+        }
+    }
+
+    #[test]
+    fn test_partnership() {
+        let rules = RuleSet {
+            partnership: true,
+            total_deals: 1,
+            ..RuleSet::default()
+        };
+        let mut game = Game::new_game(rules.clone()).unwrap();
+        for p in ["a", "b", "c"] {
+            game.add_players(p, "1234").unwrap();
+        }
+        assert_eq!(game.start(), Err(GameError::InvalidTeams));
+        game.add_players("d", "1234").unwrap();
+        game.start().unwrap();
+        assert_eq!(game.get_score_board().get_players(), ["a & c", "b & d"]);
+
+        call_all(&mut game, 3);
+        for _ in 0..13 {
+            play_round(&mut game);
+        }
+        assert_eq!(game.get_state(), GameState::Finished);
+        let deal = &game.get_score_board().get_deals()[0];
+        // the contract of every team is the sum of the calls of the partners
+        assert!(deal.entries.iter().all(|e| e.call.get_value() == 6));
+        assert_eq!(deal.entries[0].tricks + deal.entries[1].tricks, 13);
+        assert!(game.get_game_winner().is_some());
+    }
+
+    #[test]
+    fn test_redeal() {
+        let rules = RuleSet {
+            redeal: Reshuffle::NoTrump,
+            max_redeals: 1,
+            ..RuleSet::default()
+        };
+        let mut game = Game::new_game(rules).unwrap();
+        game.add_players("a", "1234").unwrap();
+        game.add_players("b", "1234").unwrap();
+        game.set_shuffle_seeds(vec![[1; 32]]).unwrap();
+        game.start().unwrap();
+
+        // b calls first, with a hand that has no trump
+        game.players[1].clear_deal();
+        for (suit, value) in [(Suit::Hearts, "2"), (Suit::Club, "3")] {
+            game.players[1].add_card(Card::new(suit, value.to_string()));
+        }
+        assert!(!game.can_redeal("a"));
+        assert!(game.can_redeal("b"));
+        assert_eq!(game.redeal("a"), Err(GameError::NotPlayersTurn));
+
+        game.redeal("b").unwrap();
+        assert_eq!(game.get_redeals(1), 1);
+        assert_eq!(game.get_state(), GameState::Calling);
+        assert_eq!(current_player_name(&game), "b");
+        assert_eq!(game.get_players()[1].get_cards().len(), 26);
+
+        // the deal has no redeals left
+        game.players[1].clear_deal();
+        game.players[1].add_card(Card::new(Suit::Hearts, "2".to_string()));
+        assert!(!game.can_redeal("b"));
+        assert_eq!(game.play_move("b", Move::Redeal), Err(GameError::NoRedeal));
+    }
+
+    #[test]
+    fn test_nil_bid() {
+        let mut game = Game::default();
+        game.add_players("a", "1234").unwrap();
+        game.add_players("b", "1234").unwrap();
+        assert_eq!(game.get_nil_bid(), None);
+        game.start().unwrap();
+        assert_eq!(game.place_call("b", NIL_CALL), Err(GameError::InvalidCall));
+
+        let rules = RuleSet {
+            nil_bid: Some(NilBid::default()),
+            total_deals: 1,
+            ..RuleSet::default()
+        };
+        let mut game = Game::new_game(rules).unwrap();
+        game.add_players("a", "1234").unwrap();
+        game.add_players("b", "1234").unwrap();
+        game.start().unwrap();
+        // the nil call stays out of the call range
+        assert_eq!(game.get_call_range(), (2, 8));
+        assert_eq!(game.get_timeout_move(), Some(Move::Call(2)));
+        call_all(&mut game, NIL_CALL);
+        assert!(game.get_players()[0].get_call().is_nil());
+        for _ in 0..26 {
+            play_round(&mut game);
+        }
+        assert_eq!(game.get_state(), GameState::Finished);
+        // one of the two nil calls failed
+        let scores: Vec<i32> = game.get_score_board().get_deals()[0]
+            .entries
+            .iter()
+            .map(|e| e.score.get_tenths())
+            .collect();
+        assert!(scores.contains(&-50));
+    }
+
+    #[test]
+    fn test_hook() {
+        let rules = RuleSet {
+            hook: true,
+            ..RuleSet::default()
+        };
+        let mut game = Game::new_game(rules).unwrap();
+        for p in ["a", "b", "c", "d"] {
+            game.add_players(p, "1234").unwrap();
+        }
+        game.start().unwrap();
+        assert!(game.get_forbidden_bids().is_empty());
+        for (name, call) in [("b", 4), ("c", 3), ("d", 3)] {
+            game.place_call(name, call).unwrap();
+        }
+        assert_eq!(game.get_call_total(), 10);
+
+        // the dealer calls last and can't make it 13
+        let forbidden = game.get_forbidden_bids();
+        assert_eq!(forbidden.len(), 1);
+        assert_eq!(forbidden[0].get_value(), 3);
+        assert_eq!(game.place_call("a", 3), Err(GameError::ForbiddenCall));
+        assert_eq!(game.get_timeout_move(), Some(Move::Call(2)));
+        game.place_call("a", 4).unwrap();
+        assert_eq!(game.get_state(), GameState::Playing);
+        assert_eq!(game.get_call_total(), 14);
+    }
+
+    #[test]
+    fn test_must_overtake() {
+        let mut game = Game::new_game(RuleSet::nepali()).unwrap();
+        game.add_players("a", "1234").unwrap();
+        game.add_players("b", "1234").unwrap();
+        // a fixed deal where a can overtake the lead of b
+        game.set_shuffle_seeds(vec![[1; 32]]).unwrap();
+        game.start().unwrap();
+        call_all(&mut game, 2);
+
+        // b leads the lowest card of a suit a holds a higher card of
+        let a_cards = game.get_players()[0].get_cards().to_vec();
+        let b_cards = game.get_players()[1].get_cards().to_vec();
+        let lead = b_cards.iter().enumerate().find(|(_, c)| {
+            a_cards
+                .iter()
+                .any(|a| a.get_suit() == c.get_suit() && a.get_priority() > c.get_priority())
+        });
+        let (lead_idx, lead_card) = lead
+            .map(|(i, c)| (i, c.clone()))
+            .expect("a card of b that a can overtake");
+        game.throw("b", lead_idx).unwrap();
+        let eligible = game.get_eligible_cards("a").unwrap();
+        assert!(!eligible.is_empty());
+        assert!(eligible
+            .iter()
+            .all(|i| a_cards[*i].beats(&lead_card, Suit::Spade)));
+    }
+
+    #[test]
+    fn test_full_game() {
+        let mut game = new_started_game(&["a", "b", "c", "d"]);
+
+        for deal in 1..=DEFAULT_TOTAL_DEALS {
+            assert_eq!(game.get_deal_info(), (deal, DEFAULT_TOTAL_DEALS));
+            call_all(&mut game, 2);
+            for _ in 0..13 {
+                play_round(&mut game);
+            }
+            assert_eq!(game.get_score_board().get_deals().len(), deal as usize);
+        }
+
+        assert_eq!(game.get_state(), GameState::Finished);
+        assert!(game.get_current_player().is_none());
+        let tricks: u8 = game.get_score_board().get_deals()[0]
+            .entries
+            .iter()
+            .map(|e| e.tricks)
+            .sum();
+        assert_eq!(tricks, 13);
+        assert_eq!(game.get_game_winner(), game.get_score_board().winner());
+    }
+}
@@ -0,0 +1,18 @@
+pub mod bid;
+pub mod bot;
+pub mod card;
+pub mod cli;
+pub mod deck;
+pub mod duplicate;
+pub mod game;
+pub mod log;
+pub mod net;
+pub mod pin;
+pub mod player;
+pub mod rating;
+pub mod rules;
+pub mod score;
+pub mod shuffle;
+pub mod stats;
+pub mod tournament;
+pub mod view;
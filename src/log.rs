@@ -0,0 +1,288 @@
+use std::fmt::{self, Display};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::bid::Bid;
+use crate::card::{Card, CardStyle};
+use crate::game::{Game, GameError, Move};
+use crate::rules::RuleSet;
+use crate::score::ScoreBoard;
+use crate::shuffle::ShuffleProof;
+
+/// LogEvent is a single thing that happened at the table
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LogEvent {
+    Joined {
+        player: String,
+    },
+    // left the table before the game started
+    Left {
+        player: String,
+    },
+    Started {
+        players: Vec<String>,
+        // the rules the game is played with, to read the log back
+        #[serde(default)]
+        rules: RuleSet,
+    },
+    // the hashes of the seeds of the server, one for every deal
+    ShuffleCommitted {
+        hashes: Vec<String>,
+    },
+    ShuffleRevealed {
+        proof: ShuffleProof,
+    },
+    Call {
+        player: String,
+        call: Bid,
+    },
+    Throw {
+        player: String,
+        card: Card,
+    },
+    // the hands were thrown in and dealt again for the player's weak hand
+    Redeal {
+        player: String,
+    },
+    Disconnected {
+        player: String,
+    },
+    // the player came back and took the seat over from the bot
+    Reconnected {
+        player: String,
+    },
+    // a bot plays the seat until the player comes back
+    BotTookOver {
+        player: String,
+    },
+    // the lowest move was played for the player once their time was up
+    TimedOut {
+        player: String,
+    },
+    Finished {
+        score_board: ScoreBoard,
+        winner: Option<String>,
+    },
+}
+
+impl Display for LogEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LogEvent::Joined { player } => write!(f, "{} joined the game", player),
+            LogEvent::Left { player } => write!(f, "{} left the game", player),
+            LogEvent::Started { players, .. } => {
+                write!(f, "The game has started with {}", players.join(", "))
+            }
+            LogEvent::ShuffleCommitted { hashes } => {
+                write!(f, "The shuffles of {} deals are committed", hashes.len())
+            }
+            LogEvent::ShuffleRevealed { proof } => write!(
+                f,
+                "The seeds of deal {} are revealed: {}",
+                proof.deal, proof.server_seed
+            ),
+            LogEvent::Call { player, call } => write!(f, "{} called {}", player, call),
+            LogEvent::Throw { player, card } => write!(
+                f,
+                "{} threw {}",
+                player,
+                card.get_display_str(CardStyle::Unicode)
+            ),
+            LogEvent::Redeal { player } => write!(f, "{} asked for a redeal", player),
+            LogEvent::Disconnected { player } => write!(f, "{} disconnected", player),
+            LogEvent::Reconnected { player } => write!(f, "{} is back", player),
+            LogEvent::BotTookOver { player } => {
+                write!(f, "A bot plays for {} until they are back", player)
+            }
+            LogEvent::TimedOut { player } => write!(f, "{} ran out of time", player),
+            LogEvent::Finished { winner, .. } => match winner {
+                Some(winner) => write!(f, "The game is over, {} won", winner),
+                None => write!(f, "The game is over, it's a tie"),
+            },
+        }
+    }
+}
+
+impl LogEvent {
+    /// play_move plays the move of the player in the game and returns the event to log
+    pub fn play_move(game: &mut Game, player: &str, player_move: Move) -> Result<Self, GameError> {
+        let seat = game
+            .get_players()
+            .iter()
+            .find(|p| p.get_name() == player)
+            .ok_or(GameError::UnknownPlayer)?;
+        let card = match player_move {
+            Move::Throw(card_idx) => seat.get_cards().get(card_idx).cloned(),
+            _ => None,
+        };
+        game.play_move(player, player_move)?;
+
+        let player = player.to_string();
+        Ok(match (player_move, card) {
+            (Move::Throw(_), Some(card)) => LogEvent::Throw { player, card },
+            (Move::Redeal, _) => LogEvent::Redeal { player },
+            // the call is checked by the game, so the player holds a valid bid now
+            _ => {
+                let call = game
+                    .get_players()
+                    .iter()
+                    .find(|p| p.get_name() == player)
+                    .map(|p| p.get_call())
+                    .unwrap_or_default();
+                LogEvent::Call { player, call }
+            }
+        })
+    }
+}
+
+/// LogEntry is an event along with the time it happened, in seconds since the UNIX epoch
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LogEntry {
+    pub time: u64,
+    #[serde(flatten)]
+    pub event: LogEvent,
+}
+
+/// GameLog is the history of a hosted game, saved as JSON once the game is over
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct GameLog {
+    entries: Vec<LogEntry>,
+}
+
+impl GameLog {
+    pub fn new() -> Self {
+        GameLog::default()
+    }
+
+    pub fn record(&mut self, event: LogEvent) {
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        self.entries.push(LogEntry { time, event });
+    }
+
+    pub fn get_entries(&self) -> &[LogEntry] {
+        &self.entries
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let content = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(path, content)
+    }
+
+    /// save_new saves the log as a new file of the directory, named after the time the game
+    /// started, a game started in the same second gets a numbered name instead of overwriting it
+    pub fn save_new(&self, dir: &Path) -> io::Result<PathBuf> {
+        fs::create_dir_all(dir)?;
+        let started = self.entries.first().map(|e| e.time).unwrap_or_default();
+        let content = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        let mut copy = 0;
+        loop {
+            let name = match copy {
+                0 => format!("game-{}.json", started),
+                _ => format!("game-{}-{}.json", started, copy),
+            };
+            let path = dir.join(name);
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(mut file) => {
+                    file.write_all(content.as_bytes())?;
+                    return Ok(path);
+                }
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => copy += 1,
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let content = fs::read_to_string(path)?;
+        serde_json::from_str(&content).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::Suit;
+
+    #[test]
+    fn test_game_log() {
+        let mut log = GameLog::new();
+        log.record(LogEvent::Joined {
+            player: String::from("a"),
+        });
+        log.record(LogEvent::Throw {
+            player: String::from("a"),
+            card: Card::new(Suit::Hearts, String::from("Q")),
+        });
+        log.record(LogEvent::BotTookOver {
+            player: String::from("a"),
+        });
+        assert_eq!(log.get_entries().len(), 3);
+        assert!(log.get_entries()[0].time > 0);
+        assert_eq!(
+            log.get_entries()[2].event.to_string(),
+            "A bot plays for a until they are back"
+        );
+
+        // the entries are flat JSON objects with the type of the event
+        let json = serde_json::to_string(&log).unwrap();
+        assert!(json.contains("\"type\":\"bot_took_over\",\"player\":\"a\""));
+
+        let path = std::env::temp_dir().join(format!("callbreak-log-{}.json", std::process::id()));
+        log.save(&path).unwrap();
+        let loaded = GameLog::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, log);
+    }
+
+    #[test]
+    fn test_save_new() {
+        let dir = std::env::temp_dir().join(format!("callbreak-logs-{}", std::process::id()));
+        let mut log = GameLog::new();
+        log.record(LogEvent::Joined {
+            player: String::from("a"),
+        });
+        // two games started in the same second are both kept
+        let first = log.save_new(&dir).unwrap();
+        let second = log.save_new(&dir).unwrap();
+        assert_ne!(first, second);
+        assert_eq!(GameLog::load(&first).unwrap(), log);
+        assert_eq!(GameLog::load(&second).unwrap(), log);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_play_move() {
+        let mut game = Game::default();
+        game.add_players("a", "1234").unwrap();
+        game.add_players("b", "1234").unwrap();
+        game.start().unwrap();
+        assert_eq!(
+            LogEvent::play_move(&mut game, "a", Move::Call(3)),
+            Err(GameError::NotPlayersTurn)
+        );
+        let event = LogEvent::play_move(&mut game, "b", Move::Call(3)).unwrap();
+        assert_eq!(event.to_string(), "b called 3");
+        LogEvent::play_move(&mut game, "a", Move::Call(2)).unwrap();
+
+        let card = game.get_players()[1].get_cards()[0].clone();
+        let event = LogEvent::play_move(&mut game, "b", Move::Throw(0)).unwrap();
+        assert_eq!(
+            event,
+            LogEvent::Throw {
+                player: String::from("b"),
+                card
+            }
+        );
+    }
+}
@@ -1,41 +1,127 @@
-use card::{Card, Suit};
-use deck::Deck;
-use player::Player;
-use rand::distributions::uniform::SampleBorrow;
-use std::io;
+use std::path::Path;
+use std::{env, io, thread};
 
-use crate::game::{Call, Game};
+use callbreak::cli;
+use callbreak::duplicate::DuplicateCommand;
+use callbreak::net::server::{Server, ServerOptions};
+use callbreak::rating::Ratings;
+use callbreak::tournament::TournamentCommand;
 
-mod card;
-mod cli;
-mod deck;
-mod game;
-mod player;
+const USAGE: &str = "usage: callbreak [serve [--port N] [--players N] [--deals N] [--rules standard|nepali|indian] [--redeal no-trump|no-face-card|either] [--max-redeals N] [--partnership on] [--nil on|N] [--hook on] [--min-total N] [--web-port N] [--bot-after SECS|off] [--log FILE] [--call-time SECS] [--throw-time SECS] [--spectator-hands all|none] [--spectator-delay SECS] [--ratings DIR] [--deal-seed HEX] | join <host:port> [--name NAME] | watch <host:port> [--name NAME] | ratings <dir> | tournament <command> <file> ... | duplicate <command> <file> ...]";
 
-fn main() -> io::Result<()> {
-    let card = Card::new(Suit::Club, "2".to_string());
-    card.print();
-
-    let mut deck = Deck::new();
-    deck.print_deck();
-    deck.shuffle();
-    deck.print_deck();
-    let mut p1 = Player::new("Ayan", &2234, Call::Two(2));
-    p1.add_card(Card::new(Suit::Diamonds, "2".to_string()));
-    p1.reveal(&2234);
+/// parse_join_args returns the address of the server and the optional player name
+fn parse_join_args(args: &[String]) -> Result<(String, Option<String>), String> {
+    let mut addr = None;
+    let mut name = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--name" => match args.next() {
+                Some(n) => name = Some(n.clone()),
+                None => return Err(String::from("Missing value for --name")),
+            },
+            _ if addr.is_none() && !arg.starts_with("--") => addr = Some(arg.clone()),
+            _ => return Err(format!("Unknown argument {}, {}", arg, USAGE)),
+        }
+    }
+    match addr {
+        Some(addr) => Ok((addr, name)),
+        None => Err(format!("Missing the address of the server, {}", USAGE)),
+    }
+}
 
-    let mut new_game = Game::new_game();
-    new_game.add_players("as", 1234.borrow(), 2);
-    new_game.add_players("bb", 1234.borrow(), 3);
-    new_game.add_players("b1b", 1234.borrow(), 4);
-    new_game.add_players("b2b", 1234.borrow(), 2);
-    new_game.start();
+/// print_ratings rates the players from the game logs saved in the directory and prints
+/// them from the highest rating down
+fn print_ratings(dir: &Path) -> io::Result<()> {
+    let ratings = Ratings::from_logs(dir)?;
+    let ranking = ratings.get_ranking();
+    if ranking.is_empty() {
+        println!("No finished games found in {}", dir.display());
+    }
+    for (place, (name, rating)) in ranking.iter().enumerate() {
+        println!(
+            "{:>3}. {:<20} {:>6.0}  ({} games)",
+            place + 1,
+            name,
+            rating.value,
+            rating.games
+        );
+    }
+    Ok(())
+}
 
-    new_game.throw("as", 0);
-    new_game.throw("bb", 1);
-    new_game.get_player_eligible_cards("b1b");
+/// serve hosts a game on all the network interfaces and prints its events for the host
+fn serve(options: ServerOptions) -> io::Result<()> {
+    let mut server = Server::bind(("0.0.0.0", options.port), options.clone())?;
+    println!(
+        "Hosting a game for {} players on {}",
+        options.total_players,
+        server.local_addr()?
+    );
+    #[cfg(feature = "websocket")]
+    if let Some(web_port) = options.web_port {
+        let game_addr = std::net::SocketAddr::from(([127, 0, 0, 1], server.local_addr()?.port()));
+        let gateway = callbreak::net::gateway::Gateway::bind(("0.0.0.0", web_port), game_addr)?;
+        println!("Browsers can join on http://{}", gateway.local_addr()?);
+        thread::spawn(move || gateway.run());
+    }
 
-    println!("Hello, world!, {}", deck.total_len());
+    let events = server.subscribe();
+    let host = thread::spawn(move || server.run());
+    for event in events {
+        println!("{}", event);
+    }
+    host.join()
+        .map_err(|_| io::Error::other("the server stopped unexpectedly"))?
+}
 
-    cli::CLI::new_cli()
+fn main() -> io::Result<()> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    match args.first().map(|a| a.as_str()) {
+        // hosts a game on the local network, e.g. `callbreak serve --port 7777 --players 4`
+        Some("serve") => {
+            let options = ServerOptions::from_args(&args[1..])
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+            serve(options)
+        }
+        // joins a hosted game, e.g. `callbreak join 192.168.1.5:7777 --name Ayan`
+        Some("join") => {
+            let (addr, name) = parse_join_args(&args[1..])
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+            cli::CLI::join_cli(&addr, name)
+        }
+        // watches a hosted game without a seat, e.g. `callbreak watch 192.168.1.5:7777`
+        Some("watch") => {
+            let (addr, name) = parse_join_args(&args[1..])
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+            cli::CLI::watch_cli(&addr, name)
+        }
+        // rates the players offline from the saved game logs, e.g. `callbreak ratings league/`
+        Some("ratings") => match args.get(1) {
+            Some(dir) => print_ratings(Path::new(dir)),
+            None => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Missing the directory of the game logs, {}", USAGE),
+            )),
+        },
+        // runs an office tournament kept in a file, e.g. `callbreak tournament round office.json`
+        Some("tournament") => {
+            let command = TournamentCommand::from_args(&args[1..])
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+            print!("{}", command.run()?);
+            Ok(())
+        }
+        // runs a duplicate match kept in a file, e.g. `callbreak duplicate results match.json`
+        Some("duplicate") => {
+            let command = DuplicateCommand::from_args(&args[1..])
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+            print!("{}", command.run()?);
+            Ok(())
+        }
+        Some(command) => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Unknown command {}, {}", command, USAGE),
+        )),
+        None => cli::CLI::new_cli(),
+    }
 }
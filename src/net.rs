@@ -0,0 +1,5 @@
+pub mod client;
+#[cfg(feature = "websocket")]
+pub mod gateway;
+pub mod protocol;
+pub mod server;
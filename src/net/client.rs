@@ -0,0 +1,61 @@
+use std::io::{self, BufReader};
+use std::net::TcpStream;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+
+use super::protocol::{read_message, write_message, ClientMessage, ServerMessage};
+
+/// ClientEvent is what the connection has received from the server
+#[derive(Debug, PartialEq)]
+pub enum ClientEvent {
+    Message(ServerMessage),
+    Disconnected,
+}
+
+/// Connection is a connection to a hosted game, the messages of the server are
+/// read on a separate thread so that the front end never blocks on the network
+pub struct Connection {
+    writer: TcpStream,
+    events: Receiver<ClientEvent>,
+}
+
+impl Connection {
+    pub fn connect(addr: &str) -> io::Result<Self> {
+        let stream = TcpStream::connect(addr)?;
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let (sender, events) = mpsc::channel();
+        thread::spawn(move || {
+            // a message that can't be read is skipped, the connection itself is still fine
+            loop {
+                match read_message(&mut reader) {
+                    Ok(Some(message)) => {
+                        if sender.send(ClientEvent::Message(message)).is_err() {
+                            return;
+                        }
+                    }
+                    Err(e) if e.kind() == io::ErrorKind::InvalidData => {}
+                    Ok(None) | Err(_) => break,
+                }
+            }
+            let _ = sender.send(ClientEvent::Disconnected);
+        });
+
+        Ok(Connection {
+            writer: stream,
+            events,
+        })
+    }
+
+    pub fn send(&mut self, message: &ClientMessage) -> io::Result<()> {
+        write_message(&mut self.writer, message)
+    }
+
+    /// try_recv returns the next event without waiting, None if there is nothing new
+    pub fn try_recv(&self) -> Option<ClientEvent> {
+        match self.events.try_recv() {
+            Ok(event) => Some(event),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(ClientEvent::Disconnected),
+        }
+    }
+}
@@ -0,0 +1,263 @@
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{self, TryRecvError};
+use std::thread;
+use std::time::Duration;
+
+use tungstenite::{Message, WebSocket};
+
+use super::protocol::{read_message, write_message, ClientMessage, ServerMessage};
+
+// the browser client, served as a single page without any other files
+const WEB_CLIENT: &str = include_str!("web/index.html");
+// the path the web client opens its WebSocket on
+pub const WEBSOCKET_PATH: &str = "/ws";
+// how long a read of the socket waits before the messages of the server are passed on
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Gateway lets browsers play on a hosted game: it serves the web client and
+/// relays the messages of every WebSocket to its own connection to the game
+/// server, so the browsers speak exactly the same messages as the terminal clients
+pub struct Gateway {
+    listener: TcpListener,
+    game_addr: SocketAddr,
+}
+
+impl Gateway {
+    pub fn bind<A: ToSocketAddrs>(addr: A, game_addr: SocketAddr) -> io::Result<Self> {
+        Ok(Gateway {
+            listener: TcpListener::bind(addr)?,
+            game_addr,
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// run serves the browsers until the listener fails, one thread per connection
+    pub fn run(self) -> io::Result<()> {
+        for stream in self.listener.incoming() {
+            let stream = stream?;
+            let game_addr = self.game_addr;
+            thread::spawn(move || handle_connection(stream, game_addr));
+        }
+        Ok(())
+    }
+}
+
+fn handle_connection(stream: TcpStream, game_addr: SocketAddr) -> io::Result<()> {
+    if get_request_path(&stream)? == WEBSOCKET_PATH {
+        relay(stream, game_addr)
+    } else {
+        serve_web_client(stream)
+    }
+}
+
+/// get_request_path peeks at the request line without reading it, the WebSocket
+/// handshake still needs the whole request
+fn get_request_path(stream: &TcpStream) -> io::Result<String> {
+    let mut buf = [0; 1024];
+    loop {
+        let n = stream.peek(&mut buf)?;
+        if n == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        if let Some(end) = buf[..n].windows(2).position(|w| w == b"\r\n") {
+            // e.g. "GET /ws HTTP/1.1"
+            let line = String::from_utf8_lossy(&buf[..end]);
+            return line
+                .split(' ')
+                .nth(1)
+                .map(String::from)
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "invalid request"));
+        }
+        if n == buf.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "request too long",
+            ));
+        }
+        // only a part of the line has arrived so far
+        thread::sleep(Duration::from_millis(5));
+    }
+}
+
+/// serve_web_client answers a plain HTTP request with the web client
+fn serve_web_client(stream: TcpStream) -> io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // the headers are read and ignored
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        header.clear();
+    }
+
+    let path = request_line.split(' ').nth(1).unwrap_or("/");
+    let (status, body) = match path {
+        "/" | "/index.html" => ("200 OK", WEB_CLIENT),
+        _ => ("404 Not Found", "Not Found"),
+    };
+    let content_type = if status == "200 OK" {
+        "text/html; charset=utf-8"
+    } else {
+        "text/plain"
+    };
+    let mut stream = stream;
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+/// relay passes the messages between the WebSocket and a new connection to the game server
+/// until either side closes
+fn relay(stream: TcpStream, game_addr: SocketAddr) -> io::Result<()> {
+    let mut socket = tungstenite::accept(stream.try_clone()?).map_err(io::Error::other)?;
+    let game = TcpStream::connect(game_addr)?;
+    let mut game_writer = game.try_clone()?;
+
+    // the game server is read on its own thread, the socket is polled for the browser
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let mut reader = BufReader::new(game);
+        while let Ok(Some(message)) = read_message::<_, ServerMessage>(&mut reader) {
+            if sender.send(message).is_err() {
+                break;
+            }
+        }
+    });
+    stream.set_read_timeout(Some(POLL_INTERVAL))?;
+
+    let result = relay_messages(&mut socket, &mut game_writer, &receiver);
+    let _ = socket.close(None);
+    let _ = socket.flush();
+    let _ = game_writer.shutdown(Shutdown::Both);
+    result
+}
+
+fn relay_messages(
+    socket: &mut WebSocket<TcpStream>,
+    game_writer: &mut TcpStream,
+    receiver: &mpsc::Receiver<ServerMessage>,
+) -> io::Result<()> {
+    loop {
+        loop {
+            match receiver.try_recv() {
+                Ok(message) => send_to_browser(socket, &message)?,
+                Err(TryRecvError::Empty) => break,
+                // the game server has closed the connection
+                Err(TryRecvError::Disconnected) => return Ok(()),
+            }
+        }
+
+        match socket.read() {
+            Ok(Message::Text(text)) => match serde_json::from_str::<ClientMessage>(&text) {
+                Ok(message) => write_message(game_writer, &message)?,
+                Err(e) => send_to_browser(
+                    socket,
+                    &ServerMessage::Error {
+                        message: format!("Invalid message: {}", e),
+                    },
+                )?,
+            },
+            Ok(Message::Close(_)) => return Ok(()),
+            // pings are answered by tungstenite
+            Ok(_) => {}
+            Err(tungstenite::Error::Io(e))
+                if matches!(
+                    e.kind(),
+                    io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                ) => {}
+            Err(tungstenite::Error::ConnectionClosed) => return Ok(()),
+            Err(e) => return Err(io::Error::other(e)),
+        }
+    }
+}
+
+fn send_to_browser(socket: &mut WebSocket<TcpStream>, message: &ServerMessage) -> io::Result<()> {
+    let text = serde_json::to_string(message).map_err(io::Error::other)?;
+    socket.send(Message::Text(text)).map_err(io::Error::other)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use super::*;
+    use crate::net::protocol::PROTOCOL_VERSION;
+    use crate::net::server::{Server, ServerOptions};
+
+    fn start_gateway() -> SocketAddr {
+        let server = Server::bind("127.0.0.1:0", ServerOptions::default()).unwrap();
+        let game_addr = server.local_addr().unwrap();
+        thread::spawn(move || server.run());
+        let gateway = Gateway::bind("127.0.0.1:0", game_addr).unwrap();
+        let addr = gateway.local_addr().unwrap();
+        thread::spawn(move || gateway.run());
+        addr
+    }
+
+    fn receive<S: io::Read + io::Write>(socket: &mut WebSocket<S>) -> ServerMessage {
+        match socket.read().unwrap() {
+            Message::Text(text) => serde_json::from_str(&text).unwrap(),
+            message => panic!("unexpected message {:?}", message),
+        }
+    }
+
+    #[test]
+    fn test_web_client() {
+        let addr = start_gateway();
+        let get = |path: &str| {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            write!(stream, "GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        };
+
+        let response = get("/");
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.contains(WEBSOCKET_PATH));
+        assert!(get("/missing").starts_with("HTTP/1.1 404"));
+    }
+
+    #[test]
+    fn test_websocket_relay() {
+        let addr = start_gateway();
+        let url = format!("ws://{}{}", addr, WEBSOCKET_PATH);
+        let (mut socket, _) = tungstenite::connect(url).unwrap();
+
+        // the messages are the same JSON objects as on the TCP protocol
+        let join = ClientMessage::Join {
+            version: PROTOCOL_VERSION,
+            name: String::from("browser"),
+            token: None,
+        };
+        let text = serde_json::to_string(&join).unwrap();
+        socket.send(Message::Text(text)).unwrap();
+        assert!(matches!(
+            receive(&mut socket),
+            ServerMessage::Welcome { seat: 0, .. }
+        ));
+        assert!(matches!(
+            receive(&mut socket),
+            ServerMessage::ShuffleCommitment { hashes, .. } if hashes.len() == 5
+        ));
+        assert!(matches!(
+            receive(&mut socket),
+            ServerMessage::Lobby { seats, .. } if seats[0].name == "browser"
+        ));
+
+        socket
+            .send(Message::Text(String::from("{\"type\":\"cheat\"}")))
+            .unwrap();
+        assert!(matches!(receive(&mut socket), ServerMessage::Error { .. }));
+    }
+}
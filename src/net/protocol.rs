@@ -0,0 +1,170 @@
+use std::io::{self, BufRead, Write};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::card::Card;
+use crate::shuffle::ShuffleProof;
+use crate::view::GameView;
+
+/// PROTOCOL_VERSION is bumped on every incompatible change of the messages,
+/// the server turns away the clients with a different version
+pub const PROTOCOL_VERSION: u32 = 12;
+
+/// ClientMessage is sent by a client to the server, one JSON object per line
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    // takes a seat in the game under the given name, the token of a previous
+    // connection takes back the same seat
+    Join {
+        version: u32,
+        name: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        token: Option<String>,
+    },
+    // watches the game without a seat, spectators can't chat or move
+    Watch {
+        version: u32,
+        name: String,
+    },
+    // the game starts once every seat is taken and ready, the entropy of the
+    // player goes into the shuffle of every deal
+    Ready {
+        ready: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        entropy: Option<String>,
+    },
+    Chat {
+        text: String,
+    },
+    // places the call for the current deal
+    Bid {
+        call: u8,
+    },
+    // throws a card from the hand
+    Play {
+        card: Card,
+    },
+    // asks for a redeal of a weak hand instead of calling
+    Redeal,
+}
+
+/// ServerMessage is sent by the server to a client, one JSON object per line
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    // the join is accepted, `seat` is the position at the table and `token`
+    // is used to take the seat back after a disconnect
+    Welcome {
+        version: u32,
+        seat: usize,
+        token: String,
+    },
+    // the client watches the game, the states reach it `delay_ms` milliseconds late
+    Watching {
+        version: u32,
+        delay_ms: u64,
+    },
+    // the hash of the seed of the server for every deal, sent before any entropy is asked
+    ShuffleCommitment {
+        hashes: Vec<String>,
+        // the deals are fixed by the seed of a duplicate match, the entropy of the players isn't used
+        #[serde(default)]
+        fixed: bool,
+    },
+    // the seeds of a deal that is over, to check the shuffle against the commitment
+    ShuffleReveal {
+        proof: ShuffleProof,
+    },
+    // the players seated so far while waiting for the table to fill up
+    Lobby {
+        seats: Vec<SeatView>,
+        total_seats: usize,
+    },
+    Chat {
+        from: String,
+        text: String,
+    },
+    // players leaving and coming back
+    Notice {
+        text: String,
+    },
+    // the game as seen from the seat of the client, or by the spectators, sent after every change
+    State {
+        view: Box<GameView>,
+    },
+    // the last message of the client was rejected
+    Error {
+        message: String,
+    },
+}
+
+/// SeatView is a taken seat as shown in the lobby
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SeatView {
+    pub name: String,
+    pub ready: bool,
+    // the rating of the player from the rated games of the server, if it rates them
+    #[serde(default)]
+    pub rating: Option<u32>,
+}
+
+/// write_message sends the message as a single line of JSON
+pub fn write_message<W: Write, T: Serialize>(writer: &mut W, message: &T) -> io::Result<()> {
+    let mut line = serde_json::to_string(message).map_err(io::Error::other)?;
+    line.push('\n');
+    writer.write_all(line.as_bytes())?;
+    writer.flush()
+}
+
+/// read_message reads the next message, None once the other side has closed the connection
+pub fn read_message<R: BufRead, T: DeserializeOwned>(reader: &mut R) -> io::Result<Option<T>> {
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        // blank lines are allowed between the messages
+        if !line.trim().is_empty() {
+            break;
+        }
+    }
+    serde_json::from_str(&line)
+        .map(Some)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::Suit;
+
+    #[test]
+    fn test_message_format() {
+        let message = ClientMessage::Play {
+            card: Card::new(Suit::Hearts, String::from("Q")),
+        };
+        let mut buf = vec![];
+        write_message(&mut buf, &message).unwrap();
+        let line = String::from_utf8(buf.clone()).unwrap();
+        assert!(line.starts_with("{\"type\":\"play\""));
+        assert!(line.contains("\"suit\":\"hearts\""));
+        assert!(line.ends_with('\n'));
+
+        let mut reader = io::Cursor::new(buf);
+        let read: Option<ClientMessage> = read_message(&mut reader).unwrap();
+        assert_eq!(read, Some(message));
+        let read: Option<ClientMessage> = read_message(&mut reader).unwrap();
+        assert_eq!(read, None);
+    }
+
+    #[test]
+    fn test_read_invalid_message() {
+        let mut reader = io::Cursor::new("\n{\"type\":\"bid\",\"call\":3}\n{\"type\":\"cheat\"}\n");
+        let read: Option<ClientMessage> = read_message(&mut reader).unwrap();
+        assert_eq!(read, Some(ClientMessage::Bid { call: 3 }));
+        let err = read_message::<_, ClientMessage>(&mut reader).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,1629 @@
+use std::collections::{HashMap, VecDeque};
+use std::io::{self, BufReader};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use rand::Rng;
+
+use super::protocol::{
+    read_message, write_message, ClientMessage, SeatView, ServerMessage, PROTOCOL_VERSION,
+};
+use crate::bot::{Bot, Difficulty};
+use crate::game::{
+    Game, GameError, GameState, Move, TimeLimits, DEFAULT_TOTAL_DEALS, MAX_PLAYERS, MIN_PLAYERS,
+};
+use crate::log::{GameLog, LogEvent};
+use crate::rating::Ratings;
+use crate::rules::{Reshuffle, RulePreset, RuleSet, DEFAULT_MAX_REDEALS, PARTNERSHIP_PLAYERS};
+use crate::score::NilBid;
+use crate::shuffle::{self, Seed, ShuffleProof};
+use crate::view::GameView;
+
+pub const DEFAULT_PORT: u16 = 7777;
+pub const DEFAULT_PLAYERS: usize = 4;
+// how long a disconnected player has to come back before a bot plays for them
+pub const DEFAULT_BOT_GRACE: Duration = Duration::from_secs(30);
+
+/// ServerOptions are the settings of the hosted game
+#[derive(Clone, Debug, PartialEq)]
+pub struct ServerOptions {
+    pub port: u16,
+    // the game starts once this many players have joined
+    pub total_players: usize,
+    pub total_deals: u8,
+    // the conventions the game is played with
+    pub rules: RulePreset,
+    // the weak hands whose player may ask for a redeal, and how often in a deal
+    pub redeal: Reshuffle,
+    pub max_redeals: u8,
+    // four players play in two teams of the opposite seats
+    pub partnership: bool,
+    // the scoring of the nil call, None if it can't be called
+    pub nil_bid: Option<NilBid>,
+    // the last caller can't make the calls add up to the rounds of the deal
+    pub hook: bool,
+    // the calls of a deal have to add up to at least this many, 0 for no minimum
+    pub min_total_call: u8,
+    // the port of the WebSocket gateway for the browsers, None to only host over TCP
+    pub web_port: Option<u16>,
+    // None keeps the seat of a disconnected player waiting for them
+    pub bot_grace: Option<Duration>,
+    // the file the game log is saved to once the game is over
+    pub log_path: Option<PathBuf>,
+    // the directory of the saved logs the players are rated from, the log of the game goes there too
+    pub ratings_dir: Option<PathBuf>,
+    // the seed of a duplicate match, every table hosted with it is dealt the same cards
+    pub deal_seed: Option<Seed>,
+    // the time a player has for a call or a throw before the lowest move is played for them
+    pub time_limits: TimeLimits,
+    // shows the hands of all the players to the spectators, e.g. for coaching
+    pub spectator_hands: bool,
+    // the spectators see every change this much later, so they can't pass on the hands
+    pub spectator_delay: Duration,
+}
+
+impl Default for ServerOptions {
+    fn default() -> Self {
+        ServerOptions {
+            port: DEFAULT_PORT,
+            total_players: DEFAULT_PLAYERS,
+            total_deals: DEFAULT_TOTAL_DEALS,
+            rules: RulePreset::default(),
+            redeal: Reshuffle::Never,
+            max_redeals: DEFAULT_MAX_REDEALS,
+            partnership: false,
+            nil_bid: None,
+            hook: false,
+            min_total_call: 0,
+            web_port: None,
+            bot_grace: Some(DEFAULT_BOT_GRACE),
+            log_path: None,
+            ratings_dir: None,
+            deal_seed: None,
+            time_limits: TimeLimits::default(),
+            spectator_hands: false,
+            spectator_delay: Duration::ZERO,
+        }
+    }
+}
+
+impl ServerOptions {
+    /// from_args parses the options of `callbreak serve`, e.g. `--port 7777 --players 4 --deals 5`,
+    /// `--rules standard|nepali|indian` picks the conventions of the game,
+    /// `--redeal no-trump|no-face-card|either` lets a weak hand ask for a redeal up to
+    /// `--max-redeals 3` times a deal, `--partnership on` plays 4 players in two teams,
+    /// `--nil on` allows a nil call worth the default points, `--nil 8` worth 8 points,
+    /// `--hook on` keeps the last caller from making the calls add up to the rounds,
+    /// `--min-total 10` makes the calls of a deal add up to at least 10,
+    /// `--web-port 8080` also serves the game to browsers when built with the websocket feature,
+    /// `--bot-after 30` gives the seat of a disconnected player to a bot after 30 seconds
+    /// (`off` to wait for them forever), `--call-time 30` and `--throw-time 15` give every
+    /// turn a time limit in seconds, `--spectator-hands all|none` shows the hands to the
+    /// spectators or not, `--spectator-delay 10` shows them the game 10 seconds late
+    /// and `--log game.json` saves the game log, `--ratings league/` rates the players from
+    /// the logs saved in the directory and saves the log of this game there as well,
+    /// `--deal-seed <hex>` deals the cards of a duplicate match instead of a random shuffle
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        let mut options = ServerOptions::default();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let value = args
+                .next()
+                .ok_or_else(|| format!("Missing value for {}", arg))?;
+            let invalid = |_| format!("Invalid value for {}: {}", arg, value);
+            match arg.as_str() {
+                "--port" => options.port = value.parse().map_err(invalid)?,
+                "--players" => options.total_players = value.parse().map_err(invalid)?,
+                // the shuffle of every deal is committed to up front, so there is at least one
+                "--deals" => {
+                    options.total_deals = value
+                        .parse()
+                        .ok()
+                        .filter(|deals| *deals > 0)
+                        .ok_or_else(|| format!("Invalid value for {}: {}", arg, value))?
+                }
+                "--rules" => {
+                    options.rules = *RulePreset::ALL
+                        .iter()
+                        .find(|p| p.get_name().eq_ignore_ascii_case(value))
+                        .ok_or_else(|| format!("Invalid value for {}: {}", arg, value))?
+                }
+                "--redeal" => {
+                    options.redeal = match value.as_str() {
+                        "off" => Reshuffle::Never,
+                        "no-trump" => Reshuffle::NoTrump,
+                        "no-face-card" => Reshuffle::NoFaceCard,
+                        "either" => Reshuffle::NoTrumpOrFaceCard,
+                        _ => return Err(format!("Invalid value for {}: {}", arg, value)),
+                    }
+                }
+                "--max-redeals" => options.max_redeals = value.parse().map_err(invalid)?,
+                "--partnership" => {
+                    options.partnership = match value.as_str() {
+                        "on" => true,
+                        "off" => false,
+                        _ => return Err(format!("Invalid value for {}: {}", arg, value)),
+                    }
+                }
+                "--nil" => {
+                    options.nil_bid = match value.as_str() {
+                        "on" => Some(NilBid::default()),
+                        "off" => None,
+                        points => {
+                            let points = points.parse().map_err(invalid)?;
+                            Some(NilBid {
+                                bonus: points,
+                                penalty: points,
+                            })
+                        }
+                    }
+                }
+                "--hook" => {
+                    options.hook = match value.as_str() {
+                        "on" => true,
+                        "off" => false,
+                        _ => return Err(format!("Invalid value for {}: {}", arg, value)),
+                    }
+                }
+                "--min-total" => options.min_total_call = value.parse().map_err(invalid)?,
+                "--web-port" if cfg!(feature = "websocket") => {
+                    options.web_port = Some(value.parse().map_err(invalid)?)
+                }
+                "--bot-after" if value == "off" => options.bot_grace = None,
+                "--bot-after" => {
+                    options.bot_grace = Some(Duration::from_secs(value.parse().map_err(invalid)?))
+                }
+                "--log" => options.log_path = Some(PathBuf::from(value)),
+                "--ratings" => options.ratings_dir = Some(PathBuf::from(value)),
+                "--deal-seed" => {
+                    options.deal_seed = Some(
+                        shuffle::parse_seed(value)
+                            .ok_or_else(|| format!("Invalid value for {}: {}", arg, value))?,
+                    )
+                }
+                "--call-time" => {
+                    options.time_limits.call =
+                        Some(Duration::from_secs(value.parse().map_err(invalid)?))
+                }
+                "--spectator-hands" => {
+                    options.spectator_hands = match value.as_str() {
+                        "all" => true,
+                        "none" => false,
+                        _ => return Err(format!("Invalid value for {}: {}", arg, value)),
+                    }
+                }
+                "--spectator-delay" => {
+                    options.spectator_delay = Duration::from_secs(value.parse().map_err(invalid)?)
+                }
+                "--throw-time" => {
+                    options.time_limits.throw =
+                        Some(Duration::from_secs(value.parse().map_err(invalid)?))
+                }
+                "--web-port" => {
+                    return Err(String::from(
+                        "--web-port needs callbreak to be built with the websocket feature",
+                    ))
+                }
+                _ => return Err(format!("Unknown option {}", arg)),
+            }
+        }
+
+        if !(MIN_PLAYERS..=MAX_PLAYERS).contains(&options.total_players) {
+            return Err(format!(
+                "Enter between {} and {} players",
+                MIN_PLAYERS, MAX_PLAYERS
+            ));
+        }
+        if options.partnership && options.total_players != PARTNERSHIP_PLAYERS {
+            return Err(GameError::InvalidTeams.to_string());
+        }
+        let rules = options.get_rules();
+        rules
+            .validate()
+            .and_then(|_| rules.validate_players(options.total_players))
+            .map_err(|e| e.to_string())?;
+        Ok(options)
+    }
+
+    /// get_rules returns the rules the game is played with, the preset changed by the other options
+    pub fn get_rules(&self) -> RuleSet {
+        RuleSet {
+            total_deals: self.total_deals,
+            redeal: self.redeal,
+            max_redeals: self.max_redeals,
+            partnership: self.partnership,
+            nil_bid: self.nil_bid,
+            hook: self.hook,
+            min_total_call: self.min_total_call,
+            ..self.rules.get_rules()
+        }
+    }
+}
+
+// longer chat messages are cut off
+const MAX_CHAT_LEN: usize = 200;
+// the entropy of a player is cut off after this many characters
+const MAX_ENTROPY_LEN: usize = 64;
+// how often the game loop checks the seats without a player when no message comes in
+const TICK: Duration = Duration::from_millis(100);
+// the pause between two moves of a bot, so the players can follow them
+const BOT_DELAY: Duration = Duration::from_millis(800);
+
+/// ServerEvent is passed from the connection threads to the game loop
+enum ServerEvent {
+    Connected(u64, TcpStream),
+    Message(u64, ClientMessage),
+    // a line that couldn't be read as a message
+    Invalid(u64, String),
+    Disconnected(u64),
+}
+
+/// Client is a connection along with the player it has joined as, or the
+/// name it watches the game under
+struct Client {
+    stream: TcpStream,
+    player: Option<String>,
+    spectator: Option<String>,
+}
+
+/// Seat is a player of the game, it outlives the connection so that the
+/// player can come back to it with the token
+struct Seat {
+    name: String,
+    token: String,
+    ready: bool,
+    client: Option<u64>,
+    disconnected_at: Option<Instant>,
+    // a bot plays for the player until they come back
+    is_bot: bool,
+    // sent along with ready, it goes into the shuffle of every deal
+    entropy: String,
+}
+
+/// Server hosts a single game, it holds the only copy of the game and checks
+/// every call and throw of the clients against it
+pub struct Server {
+    listener: TcpListener,
+    options: ServerOptions,
+    game: Game,
+    clients: HashMap<u64, Client>,
+    // in the same order as the players of the game
+    seats: Vec<Seat>,
+    log: GameLog,
+    // the ratings from the logs of the ratings directory, shown in the lobby
+    ratings: Ratings,
+    bot: Bot,
+    bot_delay: Duration,
+    last_move: Instant,
+    // the messages waiting for the spectator delay to pass, with the time they are due
+    spectator_messages: VecDeque<(Instant, ServerMessage)>,
+    // the latest state sent to the spectators, for the ones joining later
+    spectator_view: Option<GameView>,
+    // the seeds of the server for every deal, only their hashes are known until the deal is over
+    shuffle_seeds: Vec<Seed>,
+    revealed_deals: u8,
+    // the events shown to the host, None if nobody is following them
+    host_events: Option<Sender<String>>,
+}
+
+impl Server {
+    pub fn bind<A: ToSocketAddrs>(addr: A, options: ServerOptions) -> io::Result<Self> {
+        let game = Game::new_game(options.get_rules())
+            .and_then(|mut game| {
+                game.set_total_deals(options.total_deals)?;
+                game.set_time_limits(options.time_limits)?;
+                Ok(game)
+            })
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))?;
+        // a new directory has no rated games yet
+        let ratings = match &options.ratings_dir {
+            Some(dir) if dir.exists() => Ratings::from_logs(dir)?,
+            _ => Ratings::default(),
+        };
+        // the game plays at least one deal whatever the options asked for
+        let total_deals = game.get_rules().total_deals;
+        let shuffle_seeds = match &options.deal_seed {
+            Some(seed) => shuffle::get_duplicate_seeds(seed, total_deals),
+            None => (0..total_deals).map(|_| shuffle::new_seed()).collect(),
+        };
+        Ok(Server {
+            listener: TcpListener::bind(addr)?,
+            options,
+            game,
+            clients: HashMap::new(),
+            seats: vec![],
+            log: GameLog::new(),
+            ratings,
+            bot: Bot::new(Difficulty::Normal),
+            bot_delay: BOT_DELAY,
+            last_move: Instant::now(),
+            spectator_messages: VecDeque::new(),
+            spectator_view: None,
+            shuffle_seeds,
+            revealed_deals: 0,
+            host_events: None,
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// subscribe returns the events of the game for the host, e.g. the moves and the spectators
+    /// joining, the receiver is closed once the server stops
+    pub fn subscribe(&mut self) -> Receiver<String> {
+        let (sender, receiver) = mpsc::channel();
+        self.host_events = Some(sender);
+        receiver
+    }
+
+    fn notify_host(&self, text: String) {
+        if let Some(sender) = &self.host_events {
+            let _ = sender.send(text);
+        }
+    }
+
+    /// run accepts the players and hosts the game until all the deals are played,
+    /// the game log is saved at the end if a path is given
+    pub fn run(mut self) -> io::Result<()> {
+        let (sender, receiver) = mpsc::channel();
+        let listener = self.listener.try_clone()?;
+        thread::spawn(move || accept_clients(listener, sender));
+
+        // the events are handled one by one, so the game never sees two moves at once
+        while self.game.get_state() != GameState::Finished {
+            match receiver.recv_timeout(TICK) {
+                Ok(event) => self.handle_event(event),
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+            self.hand_over_to_bots();
+            self.play_timed_out_turn();
+            self.play_bot_turn();
+            self.send_spectator_messages();
+        }
+        // the spectators still get the end of the game after the delay
+        while let Some((due, _)) = self.spectator_messages.front() {
+            if !self.clients.values().any(|c| c.spectator.is_some()) {
+                break;
+            }
+            thread::sleep(due.saturating_duration_since(Instant::now()));
+            self.send_spectator_messages();
+        }
+
+        if self.game.get_state() == GameState::Finished {
+            self.record(LogEvent::Finished {
+                score_board: self.game.get_score_board().clone(),
+                winner: self.game.get_game_winner(),
+            });
+        }
+        if let Some(path) = &self.options.log_path {
+            self.log.save(path)?;
+        }
+        // only the finished games are rated
+        if let Some(dir) = &self.options.ratings_dir {
+            if self.game.get_state() == GameState::Finished {
+                self.log.save_new(dir)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// record adds the event to the game log and passes it on to the host
+    fn record(&mut self, event: LogEvent) {
+        self.notify_host(event.to_string());
+        self.log.record(event);
+    }
+
+    fn handle_event(&mut self, event: ServerEvent) {
+        match event {
+            ServerEvent::Connected(id, stream) => {
+                self.clients.insert(
+                    id,
+                    Client {
+                        stream,
+                        player: None,
+                        spectator: None,
+                    },
+                );
+            }
+            ServerEvent::Message(id, message) => {
+                if let Err(message) = self.handle_message(id, message) {
+                    self.send(id, &ServerMessage::Error { message });
+                }
+            }
+            ServerEvent::Invalid(id, error) => {
+                let message = format!("Invalid message: {}", error);
+                self.send(id, &ServerMessage::Error { message });
+            }
+            ServerEvent::Disconnected(id) => self.disconnect(id),
+        }
+    }
+
+    /// handle_message applies the message of the client to the game, the error is sent back to the client
+    fn handle_message(&mut self, id: u64, message: ClientMessage) -> Result<(), String> {
+        match message {
+            ClientMessage::Join {
+                version,
+                name,
+                token,
+            } => self.join(id, version, name, token),
+            ClientMessage::Watch { version, name } => self.watch(id, version, name),
+            ClientMessage::Ready { ready, entropy } => {
+                let player = self.get_player(id)?;
+                if self.game.get_state() != GameState::Waiting {
+                    return Err(String::from("The game has already started"));
+                }
+                if let Some(seat) = self.seats.iter_mut().find(|s| s.name == player) {
+                    seat.ready = ready;
+                    // the deals of a duplicate match are fixed, no one adds to the shuffle
+                    let entropy = entropy.filter(|_| self.options.deal_seed.is_none());
+                    seat.entropy = entropy
+                        .unwrap_or_default()
+                        .chars()
+                        .take(MAX_ENTROPY_LEN)
+                        .collect();
+                }
+                self.broadcast_lobby();
+                self.start_if_ready()
+            }
+            ClientMessage::Chat { text } => {
+                let from = self.get_player(id)?;
+                let text: String = text.trim().chars().take(MAX_CHAT_LEN).collect();
+                if !text.is_empty() {
+                    self.broadcast(&ServerMessage::Chat { from, text });
+                }
+                Ok(())
+            }
+            ClientMessage::Bid { call } => {
+                let player = self.get_player(id)?;
+                self.play_move(&player, Move::Call(call))
+            }
+            ClientMessage::Play { card } => {
+                let player = self.get_player(id)?;
+                let card_idx = self
+                    .game
+                    .get_players()
+                    .iter()
+                    .find(|p| p.get_name() == player)
+                    .and_then(|p| p.get_cards().iter().position(|c| *c == card))
+                    .ok_or_else(|| String::from("The card is not in the hand"))?;
+                self.play_move(&player, Move::Throw(card_idx))
+            }
+            ClientMessage::Redeal => {
+                let player = self.get_player(id)?;
+                self.play_move(&player, Move::Redeal)
+            }
+        }
+    }
+
+    /// play_move applies the call or the throw of the player to the game and logs it
+    fn play_move(&mut self, player: &str, player_move: Move) -> Result<(), String> {
+        let event =
+            LogEvent::play_move(&mut self.game, player, player_move).map_err(|e| e.to_string())?;
+        if player_move == Move::Redeal {
+            self.broadcast(&ServerMessage::Notice {
+                text: event.to_string(),
+            });
+        }
+        self.record(event);
+        self.last_move = Instant::now();
+        self.broadcast_state();
+        self.reveal_shuffles();
+        Ok(())
+    }
+
+    fn get_shuffle_commitment(&self) -> Vec<String> {
+        self.shuffle_seeds.iter().map(shuffle::commit).collect()
+    }
+
+    /// reveal_shuffles sends the seeds of the deals that are over, so the players can
+    /// check that their hands were dealt from the committed seeds
+    fn reveal_shuffles(&mut self) {
+        let (deal_no, _) = self.game.get_deal_info();
+        let dealt = match self.game.get_state() {
+            GameState::Finished => deal_no,
+            _ => deal_no.saturating_sub(1),
+        };
+        while self.revealed_deals < dealt {
+            self.revealed_deals += 1;
+            let deal = self.revealed_deals;
+            let Some(seed) = self.shuffle_seeds.get(deal as usize - 1) else {
+                continue;
+            };
+            let proof = ShuffleProof::new(
+                deal,
+                seed,
+                self.seats.iter().map(|s| s.entropy.clone()).collect(),
+                self.game.get_redeals(deal),
+            );
+            self.broadcast(&ServerMessage::ShuffleReveal {
+                proof: proof.clone(),
+            });
+            self.record(LogEvent::ShuffleRevealed { proof });
+        }
+    }
+
+    /// hand_over_to_bots gives the seats of the players who didn't come back in time to a bot
+    fn hand_over_to_bots(&mut self) {
+        let grace = match self.options.bot_grace {
+            Some(grace) => grace,
+            None => return,
+        };
+        let players: Vec<String> = self
+            .seats
+            .iter_mut()
+            .filter(|s| !s.is_bot && s.disconnected_at.is_some_and(|t| t.elapsed() >= grace))
+            .map(|s| {
+                s.is_bot = true;
+                s.name.clone()
+            })
+            .collect();
+        for player in players {
+            let event = LogEvent::BotTookOver { player };
+            self.broadcast(&ServerMessage::Notice {
+                text: event.to_string(),
+            });
+            self.record(event);
+        }
+    }
+
+    /// play_timed_out_turn plays the lowest move for the current player once their time is up
+    fn play_timed_out_turn(&mut self) {
+        if !self.game.is_out_of_time() {
+            return;
+        }
+        let player = match self.game.get_current_player() {
+            Some(p) => p.get_name().to_string(),
+            None => return,
+        };
+        if let Some(timeout_move) = self.game.get_timeout_move() {
+            let event = LogEvent::TimedOut {
+                player: player.clone(),
+            };
+            self.broadcast(&ServerMessage::Notice {
+                text: event.to_string(),
+            });
+            self.record(event);
+            let _ = self.play_move(&player, timeout_move);
+        }
+    }
+
+    /// play_bot_turn moves for the current player if a bot has their seat
+    fn play_bot_turn(&mut self) {
+        let player = match self.game.get_current_player() {
+            Some(p) => p.get_name().to_string(),
+            None => return,
+        };
+        let is_bot = self.seats.iter().any(|s| s.name == player && s.is_bot);
+        if !is_bot || self.last_move.elapsed() < self.bot_delay {
+            return;
+        }
+        if let Some(bot_move) = self.bot.choose_move(&self.game) {
+            // the bot only picks from the eligible cards, so the move is always accepted
+            let _ = self.play_move(&player, bot_move);
+        }
+    }
+
+    /// join seats the client in the game, or gives back the seat of a disconnected player
+    fn join(
+        &mut self,
+        id: u64,
+        version: u32,
+        name: String,
+        token: Option<String>,
+    ) -> Result<(), String> {
+        if version != PROTOCOL_VERSION {
+            return Err(format!(
+                "Protocol version {} is not supported, the server uses version {}",
+                version, PROTOCOL_VERSION
+            ));
+        }
+        if self.get_player(id).is_ok() {
+            return Err(String::from("Already joined the game"));
+        }
+        let name = name.trim().to_string();
+        if name.is_empty() {
+            return Err(String::from("Player name can't be empty"));
+        }
+
+        if let Some(seat_idx) = self.seats.iter().position(|s| s.name == name) {
+            let seat = &mut self.seats[seat_idx];
+            if seat.client.is_some() || token.as_deref() != Some(seat.token.as_str()) {
+                return Err(GameError::PlayerExists.to_string());
+            }
+            seat.client = Some(id);
+            seat.disconnected_at = None;
+            seat.is_bot = false;
+            self.take_seat(id, seat_idx);
+            let event = LogEvent::Reconnected { player: name };
+            self.broadcast(&ServerMessage::Notice {
+                text: event.to_string(),
+            });
+            self.record(event);
+            self.broadcast_state();
+            return Ok(());
+        }
+
+        if self.game.get_state() != GameState::Waiting {
+            return Err(String::from("The game has already started"));
+        }
+        // the pin is not needed as every player only gets their own hand
+        self.game
+            .add_players(&name, "")
+            .map_err(|e| e.to_string())?;
+        self.seats.push(Seat {
+            name: name.clone(),
+            token: format!("{:016x}", rand::thread_rng().gen::<u64>()),
+            ready: false,
+            client: Some(id),
+            disconnected_at: None,
+            is_bot: false,
+            entropy: String::new(),
+        });
+        self.take_seat(id, self.seats.len() - 1);
+        self.record(LogEvent::Joined { player: name });
+        self.broadcast_lobby();
+        Ok(())
+    }
+
+    /// watch lets the client follow the game without a seat, at any time of the game
+    fn watch(&mut self, id: u64, version: u32, name: String) -> Result<(), String> {
+        if version != PROTOCOL_VERSION {
+            return Err(format!(
+                "Protocol version {} is not supported, the server uses version {}",
+                version, PROTOCOL_VERSION
+            ));
+        }
+        if self
+            .clients
+            .get(&id)
+            .is_some_and(|c| c.player.is_some() || c.spectator.is_some())
+        {
+            return Err(String::from("Already joined the game"));
+        }
+        let name = name.trim().to_string();
+        if name.is_empty() {
+            return Err(String::from("Player name can't be empty"));
+        }
+
+        if let Some(client) = self.clients.get_mut(&id) {
+            client.spectator = Some(name.clone());
+        }
+        self.send(
+            id,
+            &ServerMessage::Watching {
+                version: PROTOCOL_VERSION,
+                delay_ms: self.options.spectator_delay.as_millis() as u64,
+            },
+        );
+        let hashes = self.get_shuffle_commitment();
+        self.send(
+            id,
+            &ServerMessage::ShuffleCommitment {
+                hashes,
+                fixed: self.options.deal_seed.is_some(),
+            },
+        );
+        self.send(id, &self.get_lobby_message());
+        if let Some(view) = self.spectator_view.clone() {
+            self.send(
+                id,
+                &ServerMessage::State {
+                    view: Box::new(view),
+                },
+            );
+        }
+        let text = format!("{} is watching the game", name);
+        self.notify_host(text.clone());
+        self.broadcast(&ServerMessage::Notice { text });
+        Ok(())
+    }
+
+    /// take_seat links the client to the seat and welcomes it
+    fn take_seat(&mut self, id: u64, seat_idx: usize) {
+        let seat = &self.seats[seat_idx];
+        let welcome = ServerMessage::Welcome {
+            version: PROTOCOL_VERSION,
+            seat: seat_idx,
+            token: seat.token.clone(),
+        };
+        if let Some(client) = self.clients.get_mut(&id) {
+            client.player = Some(seat.name.clone());
+        }
+        self.send(id, &welcome);
+        let hashes = self.get_shuffle_commitment();
+        self.send(
+            id,
+            &ServerMessage::ShuffleCommitment {
+                hashes,
+                fixed: self.options.deal_seed.is_some(),
+            },
+        );
+    }
+
+    /// disconnect frees the seat while the game is yet to start, a running game
+    /// keeps the seat for the player to come back
+    fn disconnect(&mut self, id: u64) {
+        let player = match self.clients.remove(&id).and_then(|c| c.player) {
+            Some(player) => player,
+            None => return,
+        };
+        if self.game.remove_player(&player).is_ok() {
+            self.seats.retain(|s| s.name != player);
+            self.record(LogEvent::Left { player });
+            self.broadcast_lobby();
+            return;
+        }
+        if let Some(seat) = self.seats.iter_mut().find(|s| s.name == player) {
+            seat.client = None;
+            seat.disconnected_at = Some(Instant::now());
+        }
+        let event = LogEvent::Disconnected { player };
+        self.broadcast(&ServerMessage::Notice {
+            text: event.to_string(),
+        });
+        self.record(event);
+    }
+
+    /// start_if_ready starts the game once every seat is taken and ready
+    fn start_if_ready(&mut self) -> Result<(), String> {
+        if self.seats.len() < self.options.total_players || self.seats.iter().any(|s| !s.ready) {
+            return Ok(());
+        }
+        // the entropy of the players is mixed into the seeds committed before they sent it
+        let entropies: Vec<String> = self.seats.iter().map(|s| s.entropy.clone()).collect();
+        let seeds = (1..)
+            .zip(&self.shuffle_seeds)
+            .map(|(deal, seed)| shuffle::get_deal_seed(seed, &entropies, deal))
+            .collect();
+        self.game
+            .set_shuffle_seeds(seeds)
+            .and_then(|_| self.game.start())
+            .map_err(|e| e.to_string())?;
+        self.record(LogEvent::Started {
+            players: self.seats.iter().map(|s| s.name.clone()).collect(),
+            rules: self.game.get_rules().clone(),
+        });
+        self.record(LogEvent::ShuffleCommitted {
+            hashes: self.get_shuffle_commitment(),
+        });
+        self.last_move = Instant::now();
+        self.broadcast_state();
+        Ok(())
+    }
+
+    fn get_player(&self, id: u64) -> Result<String, String> {
+        match self.clients.get(&id) {
+            Some(Client {
+                player: Some(player),
+                ..
+            }) => Ok(player.clone()),
+            Some(Client {
+                spectator: Some(_), ..
+            }) => Err(String::from("Spectators can only watch the game")),
+            _ => Err(String::from("Join the game first")),
+        }
+    }
+
+    fn send(&mut self, id: u64, message: &ServerMessage) {
+        if let Some(client) = self.clients.get_mut(&id) {
+            // a failed write shows up as a disconnect from the reading thread
+            let _ = write_message(&mut client.stream, message);
+        }
+    }
+
+    /// broadcast sends the message to every seated client, the spectators get it after
+    /// the spectator delay
+    fn broadcast(&mut self, message: &ServerMessage) {
+        for client in self.clients.values_mut().filter(|c| c.player.is_some()) {
+            let _ = write_message(&mut client.stream, message);
+        }
+        self.send_spectators(message.clone());
+    }
+
+    fn broadcast_lobby(&mut self) {
+        let message = self.get_lobby_message();
+        self.broadcast(&message);
+    }
+
+    fn get_lobby_message(&self) -> ServerMessage {
+        ServerMessage::Lobby {
+            seats: self
+                .seats
+                .iter()
+                .map(|s| SeatView {
+                    name: s.name.clone(),
+                    ready: s.ready,
+                    rating: self
+                        .options
+                        .ratings_dir
+                        .as_ref()
+                        .map(|_| self.ratings.get_rating(&s.name).value.round() as u32),
+                })
+                .collect(),
+            total_seats: self.options.total_players,
+        }
+    }
+
+    /// broadcast_state sends every seated client the game as seen from its own seat,
+    /// the view of the spectators waits for the spectator delay
+    fn broadcast_state(&mut self) {
+        for client in self.clients.values_mut() {
+            if let Some(player) = &client.player {
+                let view = Box::new(GameView::new(&self.game, Some(player)));
+                let _ = write_message(&mut client.stream, &ServerMessage::State { view });
+            }
+        }
+        let view = GameView::new_spectator(&self.game, self.options.spectator_hands);
+        self.send_spectators(ServerMessage::State {
+            view: Box::new(view),
+        });
+    }
+
+    /// send_spectators queues the message for the spectators until the spectator delay has passed
+    fn send_spectators(&mut self, message: ServerMessage) {
+        self.spectator_messages
+            .push_back((Instant::now() + self.options.spectator_delay, message));
+        self.send_spectator_messages();
+    }
+
+    /// send_spectator_messages sends the spectators the messages whose delay has passed
+    fn send_spectator_messages(&mut self) {
+        while let Some((due, _)) = self.spectator_messages.front() {
+            if *due > Instant::now() {
+                break;
+            }
+            let (_, message) = self.spectator_messages.pop_front().expect("due message");
+            for client in self.clients.values_mut().filter(|c| c.spectator.is_some()) {
+                let _ = write_message(&mut client.stream, &message);
+            }
+            if let ServerMessage::State { view } = message {
+                self.spectator_view = Some(*view);
+            }
+        }
+    }
+}
+
+fn accept_clients(listener: TcpListener, sender: Sender<ServerEvent>) {
+    for (id, stream) in (0..).zip(listener.incoming()) {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(_) => continue,
+        };
+        let writer = match stream.try_clone() {
+            Ok(writer) => writer,
+            Err(_) => continue,
+        };
+        if sender.send(ServerEvent::Connected(id, writer)).is_err() {
+            // the game is over
+            return;
+        }
+        let sender = sender.clone();
+        thread::spawn(move || read_client(id, stream, sender));
+    }
+}
+
+/// read_client passes the messages of a single connection on to the game loop
+fn read_client(id: u64, stream: TcpStream, sender: Sender<ServerEvent>) {
+    let mut reader = BufReader::new(stream);
+    loop {
+        let event = match read_message(&mut reader) {
+            Ok(Some(message)) => ServerEvent::Message(id, message),
+            Err(e) if e.kind() == io::ErrorKind::InvalidData => {
+                ServerEvent::Invalid(id, e.to_string())
+            }
+            Ok(None) | Err(_) => break,
+        };
+        if sender.send(event).is_err() {
+            return;
+        }
+    }
+    let _ = sender.send(ServerEvent::Disconnected(id));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    struct TestClient {
+        reader: BufReader<TcpStream>,
+        writer: TcpStream,
+    }
+
+    impl TestClient {
+        fn connect(addr: SocketAddr) -> Self {
+            let stream = TcpStream::connect(addr).unwrap();
+            TestClient {
+                reader: BufReader::new(stream.try_clone().unwrap()),
+                writer: stream,
+            }
+        }
+
+        fn send(&mut self, message: &ClientMessage) {
+            write_message(&mut self.writer, message).unwrap();
+        }
+
+        fn receive(&mut self) -> ServerMessage {
+            read_message(&mut self.reader).unwrap().unwrap()
+        }
+
+        /// receive_until skips the messages till the one matching the check
+        fn receive_until(&mut self, check: fn(&ServerMessage) -> bool) -> ServerMessage {
+            loop {
+                let message = self.receive();
+                if check(&message) {
+                    return message;
+                }
+            }
+        }
+
+        fn join_with_token(&mut self, name: &str, token: Option<String>) -> ServerMessage {
+            self.send(&ClientMessage::Join {
+                version: PROTOCOL_VERSION,
+                name: name.to_string(),
+                token,
+            });
+            self.receive()
+        }
+
+        /// join takes a seat and returns the seat number with the token
+        fn join(&mut self, name: &str) -> (usize, String) {
+            match self.join_with_token(name, None) {
+                ServerMessage::Welcome { seat, token, .. } => (seat, token),
+                message => panic!("unexpected message {:?}", message),
+            }
+        }
+    }
+
+    fn start_server(options: ServerOptions) -> SocketAddr {
+        let server = Server::bind("127.0.0.1:0", options).unwrap();
+        let addr = server.local_addr().unwrap();
+        thread::spawn(move || server.run());
+        addr
+    }
+
+    fn two_player_server() -> SocketAddr {
+        start_server(ServerOptions {
+            total_players: 2,
+            total_deals: 1,
+            ..ServerOptions::default()
+        })
+    }
+
+    /// play plays every turn of the client with the first eligible card until the game is over
+    fn play(client: &mut TestClient) -> GameView {
+        let mut last_view = None;
+        loop {
+            let view = match client.receive() {
+                ServerMessage::State { view } => view,
+                ServerMessage::Error { message } => panic!("{}", message),
+                _ => continue,
+            };
+            // the same state is sent again when a player comes back
+            if last_view.as_ref() == Some(&view) {
+                continue;
+            }
+            last_view = Some(view.clone());
+            // only the own hand is ever sent
+            let me = view.seat.clone().unwrap();
+            let my_cards = view
+                .players
+                .iter()
+                .find(|p| p.name == me)
+                .unwrap()
+                .cards_left;
+            assert_eq!(view.hand.len(), my_cards);
+
+            if view.state == GameState::Finished {
+                return *view;
+            }
+            if !view.is_my_turn() {
+                continue;
+            }
+            match view.state {
+                GameState::Calling => client.send(&ClientMessage::Bid {
+                    call: view.call_range.0,
+                }),
+                _ => client.send(&ClientMessage::Play {
+                    card: view.hand[view.eligible_cards[0]].clone(),
+                }),
+            }
+        }
+    }
+
+    #[test]
+    fn test_options_from_args() {
+        let args: Vec<String> = ["--port", "9000", "--players", "3"]
+            .iter()
+            .map(|a| a.to_string())
+            .collect();
+        let options = ServerOptions::from_args(&args).unwrap();
+        assert_eq!(options.port, 9000);
+        assert_eq!(options.total_players, 3);
+        assert_eq!(options.total_deals, DEFAULT_TOTAL_DEALS);
+        assert_eq!(options.rules, RulePreset::Standard);
+
+        assert!(ServerOptions::from_args(&["--players".to_string(), "6".to_string()]).is_err());
+        assert!(ServerOptions::from_args(&["--port".to_string()]).is_err());
+        assert!(ServerOptions::from_args(&["--deals".to_string(), "0".to_string()]).is_err());
+        assert!(ServerOptions::from_args(&["--colour".to_string(), "red".to_string()]).is_err());
+        let args: Vec<String> = ["--max-redeals", "11"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert!(ServerOptions::from_args(&args).is_err());
+        let args: Vec<String> = ["--players", "2", "--min-total", "20"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert!(ServerOptions::from_args(&args).is_err());
+
+        let args: Vec<String> = ["--bot-after", "10", "--log", "game.json"]
+            .iter()
+            .map(|a| a.to_string())
+            .collect();
+        let options = ServerOptions::from_args(&args).unwrap();
+        assert_eq!(options.bot_grace, Some(Duration::from_secs(10)));
+        assert_eq!(options.log_path, Some(PathBuf::from("game.json")));
+        assert_eq!(options.ratings_dir, None);
+        assert_eq!(options.deal_seed, None);
+        let seed = "ab".repeat(32);
+        let options = ServerOptions::from_args(&["--deal-seed".to_string(), seed.clone()]).unwrap();
+        assert_eq!(options.deal_seed, shuffle::parse_seed(&seed));
+        assert!(ServerOptions::from_args(&["--deal-seed".to_string(), "xyz".to_string()]).is_err());
+        let args: Vec<String> = ["--call-time", "30", "--throw-time", "15"]
+            .iter()
+            .map(|a| a.to_string())
+            .collect();
+        let options = ServerOptions::from_args(&args).unwrap();
+        assert!(!options.spectator_hands);
+        assert_eq!(
+            options.time_limits,
+            TimeLimits {
+                call: Some(Duration::from_secs(30)),
+                throw: Some(Duration::from_secs(15)),
+            }
+        );
+        let args: Vec<String> = ["--spectator-hands", "all", "--spectator-delay", "10"]
+            .iter()
+            .map(|a| a.to_string())
+            .collect();
+        let options = ServerOptions::from_args(&args).unwrap();
+        assert!(options.spectator_hands);
+        assert_eq!(options.spectator_delay, Duration::from_secs(10));
+        assert!(
+            ServerOptions::from_args(&["--spectator-hands".to_string(), "some".to_string()])
+                .is_err()
+        );
+        let options =
+            ServerOptions::from_args(&["--bot-after".to_string(), "off".to_string()]).unwrap();
+        assert_eq!(options.bot_grace, None);
+        let options =
+            ServerOptions::from_args(&["--rules".to_string(), "nepali".to_string()]).unwrap();
+        assert_eq!(options.rules, RulePreset::Nepali);
+        let args: Vec<String> = ["--redeal", "either", "--max-redeals", "1"]
+            .iter()
+            .map(|a| a.to_string())
+            .collect();
+        let options = ServerOptions::from_args(&args).unwrap();
+        assert_eq!(options.redeal, Reshuffle::NoTrumpOrFaceCard);
+        assert_eq!(options.max_redeals, 1);
+        let args: Vec<String> = ["--partnership", "on", "--players", "3"]
+            .iter()
+            .map(|a| a.to_string())
+            .collect();
+        assert!(ServerOptions::from_args(&args).is_err());
+        let options =
+            ServerOptions::from_args(&["--partnership".to_string(), "on".to_string()]).unwrap();
+        assert!(options.partnership);
+        let options = ServerOptions::from_args(&["--nil".to_string(), "8".to_string()]).unwrap();
+        assert_eq!(options.nil_bid.map(|n| n.penalty), Some(8));
+        assert!(ServerOptions::from_args(&["--nil".to_string(), "yes".to_string()]).is_err());
+        let args: Vec<String> = ["--hook", "on", "--min-total", "10"]
+            .iter()
+            .map(|a| a.to_string())
+            .collect();
+        let options = ServerOptions::from_args(&args).unwrap();
+        assert!(options.hook);
+        assert_eq!(options.min_total_call, 10);
+        assert!(ServerOptions::from_args(&["--rules".to_string(), "house".to_string()]).is_err());
+
+        // the gateway is only there with the websocket feature
+        let web_port = ServerOptions::from_args(&["--web-port".to_string(), "8080".to_string()]);
+        if cfg!(feature = "websocket") {
+            assert_eq!(web_port.unwrap().web_port, Some(8080));
+        } else {
+            assert!(web_port.is_err());
+        }
+    }
+
+    #[test]
+    fn test_join_validation() {
+        let addr = two_player_server();
+
+        let mut client = TestClient::connect(addr);
+        client.send(&ClientMessage::Bid { call: 3 });
+        assert!(matches!(client.receive(), ServerMessage::Error { .. }));
+        client.send(&ClientMessage::Join {
+            version: PROTOCOL_VERSION - 1,
+            name: String::from("a"),
+            token: None,
+        });
+        assert!(matches!(client.receive(), ServerMessage::Error { .. }));
+        writeln!(client.writer, "not json").unwrap();
+        assert!(matches!(client.receive(), ServerMessage::Error { .. }));
+
+        assert_eq!(client.join("a").0, 0);
+        let mut other = TestClient::connect(addr);
+        assert!(matches!(
+            other.join_with_token("a", None),
+            ServerMessage::Error { .. }
+        ));
+    }
+
+    #[test]
+    fn test_lobby() {
+        let addr = two_player_server();
+        let mut first = TestClient::connect(addr);
+        first.join("a");
+        let mut second = TestClient::connect(addr);
+        second.join("b");
+
+        let lobby = first
+            .receive_until(|m| matches!(m, ServerMessage::Lobby { seats, .. } if seats.len() == 2));
+        assert_eq!(
+            lobby,
+            ServerMessage::Lobby {
+                seats: vec![
+                    SeatView {
+                        name: String::from("a"),
+                        ready: false,
+                        rating: None
+                    },
+                    SeatView {
+                        name: String::from("b"),
+                        ready: false,
+                        rating: None
+                    },
+                ],
+                total_seats: 2,
+            }
+        );
+
+        second.send(&ClientMessage::Chat {
+            text: String::from(" hello "),
+        });
+        let chat = first.receive_until(|m| matches!(m, ServerMessage::Chat { .. }));
+        assert_eq!(
+            chat,
+            ServerMessage::Chat {
+                from: String::from("b"),
+                text: String::from("hello")
+            }
+        );
+
+        // the seat of a player leaving the lobby is freed up
+        drop(second);
+        first
+            .receive_until(|m| matches!(m, ServerMessage::Lobby { seats, .. } if seats.len() == 1));
+        let mut third = TestClient::connect(addr);
+        assert_eq!(third.join("c").0, 1);
+
+        // the game only starts once everyone is ready
+        first.send(&ClientMessage::Ready {
+            ready: true,
+            entropy: None,
+        });
+        first.receive_until(|m| matches!(m, ServerMessage::Lobby { seats, .. } if seats[0].ready));
+        third.send(&ClientMessage::Ready {
+            ready: true,
+            entropy: None,
+        });
+        let state = first.receive_until(|m| matches!(m, ServerMessage::State { .. }));
+        assert!(matches!(state, ServerMessage::State { view } if view.state == GameState::Calling));
+    }
+
+    #[test]
+    fn test_full_game() {
+        let mut server = Server::bind(
+            "127.0.0.1:0",
+            ServerOptions {
+                total_players: 2,
+                total_deals: 1,
+                ..ServerOptions::default()
+            },
+        )
+        .unwrap();
+        let addr = server.local_addr().unwrap();
+        let events = server.subscribe();
+        thread::spawn(move || server.run());
+        let mut first = TestClient::connect(addr);
+        first.join("a");
+        let mut second = TestClient::connect(addr);
+        second.join("b");
+        first.send(&ClientMessage::Ready {
+            ready: true,
+            entropy: None,
+        });
+        second.send(&ClientMessage::Ready {
+            ready: true,
+            entropy: None,
+        });
+
+        // a move out of turn is rejected by the server, the player next to the dealer calls first
+        first.receive_until(|m| matches!(m, ServerMessage::State { .. }));
+        first.send(&ClientMessage::Bid { call: 3 });
+        first.receive_until(|m| matches!(m, ServerMessage::Error { .. }));
+
+        let handle = thread::spawn(move || play(&mut second));
+        let view = play(&mut first);
+        assert_eq!(view.state, GameState::Finished);
+        assert_eq!(view.score_board.get_deals().len(), 1);
+        assert_eq!(handle.join().unwrap().score_board, view.score_board);
+
+        // the host follows the game from the events, until the server stops
+        let events: Vec<String> = events.iter().collect();
+        assert_eq!(events[0], "a joined the game");
+        assert!(events.iter().any(|e| e.starts_with("The game has started")));
+        assert!(events.iter().any(|e| e.starts_with("a threw")));
+    }
+
+    #[test]
+    fn test_zero_deals() {
+        // a library caller can still ask for no deals, the game is played with one
+        let addr = start_server(ServerOptions {
+            total_players: 2,
+            total_deals: 0,
+            ..ServerOptions::default()
+        });
+        let mut first = TestClient::connect(addr);
+        first.join("a");
+        let mut second = TestClient::connect(addr);
+        second.join("b");
+        for client in [&mut first, &mut second] {
+            client.send(&ClientMessage::Ready {
+                ready: true,
+                entropy: None,
+            });
+        }
+        let handle = thread::spawn(move || play(&mut second));
+        let view = play(&mut first);
+        assert_eq!(view.state, GameState::Finished);
+        assert_eq!(view.score_board.get_deals().len(), 1);
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn test_reconnect() {
+        let addr = two_player_server();
+        let mut first = TestClient::connect(addr);
+        first.join("a");
+        let mut second = TestClient::connect(addr);
+        let (seat, token) = second.join("b");
+        first.send(&ClientMessage::Ready {
+            ready: true,
+            entropy: None,
+        });
+        second.send(&ClientMessage::Ready {
+            ready: true,
+            entropy: None,
+        });
+        first.receive_until(|m| matches!(m, ServerMessage::State { .. }));
+
+        drop(second);
+        first.receive_until(|m| matches!(m, ServerMessage::Notice { .. }));
+
+        // the seat needs the token of the player
+        let mut other = TestClient::connect(addr);
+        assert!(matches!(
+            other.join_with_token("b", None),
+            ServerMessage::Error { .. }
+        ));
+        assert!(matches!(
+            other.join_with_token("b", Some(token.clone())),
+            ServerMessage::Welcome { seat: s, .. } if s == seat
+        ));
+        let state = other.receive_until(|m| matches!(m, ServerMessage::State { .. }));
+        match state {
+            ServerMessage::State { view } => {
+                assert_eq!(view.seat.as_deref(), Some("b"));
+                assert_eq!(view.hand.len(), 26);
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_bot_takes_over() {
+        let log_path =
+            std::env::temp_dir().join(format!("callbreak-server-log-{}.json", std::process::id()));
+        let mut server = Server::bind(
+            "127.0.0.1:0",
+            ServerOptions {
+                total_players: 2,
+                total_deals: 1,
+                bot_grace: Some(Duration::from_millis(50)),
+                log_path: Some(log_path.clone()),
+                ..ServerOptions::default()
+            },
+        )
+        .unwrap();
+        server.bot_delay = Duration::ZERO;
+        let addr = server.local_addr().unwrap();
+        let handle = thread::spawn(move || server.run());
+
+        let mut clients = vec![TestClient::connect(addr), TestClient::connect(addr)];
+        let mut tokens = vec![];
+        for (client, name) in clients.iter_mut().zip(["a", "b"]) {
+            tokens.push(client.join(name).1);
+        }
+        for client in clients.iter_mut() {
+            client.send(&ClientMessage::Ready {
+                ready: true,
+                entropy: None,
+            });
+        }
+        let first_player =
+            match clients[0].receive_until(|m| matches!(m, ServerMessage::State { .. })) {
+                ServerMessage::State { view } => view.current_player.unwrap(),
+                _ => unreachable!(),
+            };
+
+        // the player who has to call leaves, the bot calls for them after the grace period
+        let (away, name) = if first_player == "a" {
+            (0, "a")
+        } else {
+            (1, "b")
+        };
+        let mut staying = clients.remove(1 - away);
+        drop(clients);
+        staying
+            .receive_until(|m| matches!(m, ServerMessage::Notice { text } if text.contains("bot")));
+        match staying.receive_until(|m| matches!(m, ServerMessage::State { .. })) {
+            ServerMessage::State { view } => {
+                assert!(view
+                    .players
+                    .iter()
+                    .any(|p| p.name == name && p.call.is_some()))
+            }
+            _ => unreachable!(),
+        }
+
+        // the player takes the seat back and plays till the end
+        let mut back = TestClient::connect(addr);
+        assert!(matches!(
+            back.join_with_token(name, Some(tokens[away].clone())),
+            ServerMessage::Welcome { seat, .. } if seat == away
+        ));
+        let back_thread = thread::spawn(move || play(&mut back));
+        play(&mut staying);
+        back_thread.join().unwrap();
+        handle.join().unwrap().unwrap();
+
+        // the takeover is in the saved game log
+        let log = GameLog::load(&log_path).unwrap();
+        std::fs::remove_file(&log_path).unwrap();
+        let events: Vec<&LogEvent> = log.get_entries().iter().map(|e| &e.event).collect();
+        let position = |event: LogEvent| events.iter().position(|e| **e == event).unwrap();
+        let player = name.to_string();
+        let took_over = position(LogEvent::BotTookOver {
+            player: player.clone(),
+        });
+        assert!(
+            position(LogEvent::Disconnected {
+                player: player.clone()
+            }) < took_over
+        );
+        assert!(took_over < position(LogEvent::Reconnected { player }));
+        assert!(matches!(events.last(), Some(LogEvent::Finished { .. })));
+    }
+
+    #[test]
+    fn test_rated_games() {
+        let dir =
+            std::env::temp_dir().join(format!("callbreak-server-ratings-{}", std::process::id()));
+        let options = ServerOptions {
+            total_players: 2,
+            total_deals: 1,
+            ratings_dir: Some(dir.clone()),
+            ..ServerOptions::default()
+        };
+        let addr = start_server(options.clone());
+        let mut clients = vec![TestClient::connect(addr), TestClient::connect(addr)];
+        for (client, name) in clients.iter_mut().zip(["a", "b"]) {
+            client.join(name);
+        }
+        for client in clients.iter_mut() {
+            client.send(&ClientMessage::Ready {
+                ready: true,
+                entropy: None,
+            });
+        }
+        let mut second = clients.pop().unwrap();
+        let second_thread = thread::spawn(move || play(&mut second));
+        play(&mut clients[0]);
+        second_thread.join().unwrap();
+
+        // the next game of the league shows the ratings from the saved log
+        let ratings = (0..50)
+            .find_map(|_| {
+                thread::sleep(Duration::from_millis(20));
+                Ratings::from_logs(&dir)
+                    .ok()
+                    .filter(|r| r.get_rating("a").games == 1)
+            })
+            .unwrap();
+        let mut client = TestClient::connect(start_server(options));
+        client.join("a");
+        let lobby = client.receive_until(|m| matches!(m, ServerMessage::Lobby { .. }));
+        std::fs::remove_dir_all(&dir).unwrap();
+        match lobby {
+            ServerMessage::Lobby { seats, .. } => assert_eq!(
+                seats[0].rating,
+                Some(ratings.get_rating("a").value.round() as u32)
+            ),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_timed_out_call() {
+        let addr = start_server(ServerOptions {
+            total_players: 2,
+            total_deals: 1,
+            time_limits: TimeLimits {
+                call: Some(Duration::from_millis(50)),
+                throw: None,
+            },
+            ..ServerOptions::default()
+        });
+        let mut a = TestClient::connect(addr);
+        let mut b = TestClient::connect(addr);
+        a.join("a");
+        b.join("b");
+        a.send(&ClientMessage::Ready {
+            ready: true,
+            entropy: None,
+        });
+        b.send(&ClientMessage::Ready {
+            ready: true,
+            entropy: None,
+        });
+        let view = match a.receive_until(|m| matches!(m, ServerMessage::State { .. })) {
+            ServerMessage::State { view } => view,
+            _ => unreachable!(),
+        };
+        assert!(view.time_left_ms.is_some());
+        let first_player = view.current_player.clone().unwrap();
+
+        // nobody calls, so the lowest call is made for the first player
+        a.receive_until(
+            |m| matches!(m, ServerMessage::Notice { text } if text.contains("ran out of time")),
+        );
+        match a.receive_until(|m| matches!(m, ServerMessage::State { .. })) {
+            ServerMessage::State { view } => {
+                let player = view.players.iter().find(|p| p.name == first_player);
+                assert_eq!(
+                    player.unwrap().call.map(|c| c.get_value()),
+                    Some(view.call_range.0)
+                );
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_redeal_refused() {
+        let addr = start_server(ServerOptions {
+            total_players: 2,
+            total_deals: 1,
+            ..ServerOptions::default()
+        });
+        let mut a = TestClient::connect(addr);
+        let mut b = TestClient::connect(addr);
+        a.join("a");
+        b.join("b");
+        for client in [&mut a, &mut b] {
+            client.send(&ClientMessage::Ready {
+                ready: true,
+                entropy: None,
+            });
+        }
+        let view = match a.receive_until(|m| matches!(m, ServerMessage::State { .. })) {
+            ServerMessage::State { view } => view,
+            _ => unreachable!(),
+        };
+        assert!(!view.can_redeal);
+
+        // the game is played without the redeal rule
+        let first = if view.is_my_turn() { &mut a } else { &mut b };
+        first.send(&ClientMessage::Redeal);
+        assert_eq!(
+            first.receive_until(|m| matches!(m, ServerMessage::Error { .. })),
+            ServerMessage::Error {
+                message: GameError::NoRedeal.to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_spectators() {
+        let delay = Duration::from_millis(300);
+        let addr = start_server(ServerOptions {
+            total_players: 2,
+            total_deals: 1,
+            spectator_hands: true,
+            spectator_delay: delay,
+            ..ServerOptions::default()
+        });
+        let mut spectator = TestClient::connect(addr);
+        let watched = Instant::now();
+        spectator.send(&ClientMessage::Watch {
+            version: PROTOCOL_VERSION,
+            name: String::from("coach"),
+        });
+        assert_eq!(
+            spectator.receive(),
+            ServerMessage::Watching {
+                version: PROTOCOL_VERSION,
+                delay_ms: 300
+            }
+        );
+        assert!(matches!(
+            spectator.receive(),
+            ServerMessage::ShuffleCommitment { .. }
+        ));
+        assert!(matches!(spectator.receive(), ServerMessage::Lobby { .. }));
+        // the notices wait for the delay just like the states
+        assert_eq!(
+            spectator.receive(),
+            ServerMessage::Notice {
+                text: String::from("coach is watching the game")
+            }
+        );
+        assert!(watched.elapsed() >= delay - TICK);
+        // the spectators can't talk to the players
+        spectator.send(&ClientMessage::Chat {
+            text: String::from("play the ace"),
+        });
+        spectator.receive_until(|m| matches!(m, ServerMessage::Error { .. }));
+
+        let mut a = TestClient::connect(addr);
+        let mut b = TestClient::connect(addr);
+        a.join("a");
+        b.join("b");
+        a.send(&ClientMessage::Ready {
+            ready: true,
+            entropy: None,
+        });
+        b.send(&ClientMessage::Ready {
+            ready: true,
+            entropy: None,
+        });
+        a.receive_until(|m| matches!(m, ServerMessage::State { .. }));
+        let started = Instant::now();
+
+        // the spectator sees all the hands, but only after the delay
+        let view = match spectator.receive_until(|m| matches!(m, ServerMessage::State { .. })) {
+            ServerMessage::State { view } => view,
+            _ => unreachable!(),
+        };
+        assert!(started.elapsed() >= delay - TICK);
+        assert!(view.seat.is_none());
+        assert!(view.hand.is_empty());
+        assert!(view.players.iter().all(|p| p.hand.len() == 26));
+
+        // a spectator coming in late gets the latest state right away
+        let mut late = TestClient::connect(addr);
+        late.send(&ClientMessage::Watch {
+            version: PROTOCOL_VERSION,
+            name: String::from("late"),
+        });
+        late.receive_until(|m| matches!(m, ServerMessage::State { .. }));
+    }
+}
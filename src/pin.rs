@@ -0,0 +1,217 @@
+use std::fmt;
+use std::time::{Duration, SystemTime};
+
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::{Argon2, Params};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+// a pin is at least 4 characters, any longer text works as a passphrase
+pub const MIN_PIN_LENGTH: usize = 4;
+pub const MAX_PIN_LENGTH: usize = 64;
+// the wrong pins allowed before the player is locked out
+pub const MAX_ATTEMPTS: u32 = 3;
+// the first lockout, doubled for every lockout after it up to the maximum
+const LOCKOUT_TIME: Duration = Duration::from_secs(30);
+const MAX_LOCKOUT_TIME: Duration = Duration::from_secs(15 * 60);
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PinError {
+    TooShort,
+    TooLong,
+    Incorrect { attempts_left: u32 },
+    // the time left before the pin can be entered again
+    LockedOut(Duration),
+}
+
+impl fmt::Display for PinError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PinError::TooShort => write!(f, "Pin should be at least {} characters", MIN_PIN_LENGTH),
+            PinError::TooLong => write!(f, "Pin should be at most {} characters", MAX_PIN_LENGTH),
+            PinError::Incorrect { attempts_left: 1 } => {
+                write!(f, "Incorrect Pin Entered, 1 attempt left")
+            }
+            PinError::Incorrect { attempts_left } => {
+                write!(f, "Incorrect Pin Entered, {} attempts left", attempts_left)
+            }
+            PinError::LockedOut(time) => write!(
+                f,
+                "Too many incorrect pins, try again in {} seconds",
+                time.as_secs().max(1)
+            ),
+        }
+    }
+}
+
+/// validate checks the length of a new pin or passphrase
+pub fn validate(pin: &str) -> Result<(), PinError> {
+    match pin.chars().count() {
+        n if n < MIN_PIN_LENGTH => Err(PinError::TooShort),
+        n if n > MAX_PIN_LENGTH => Err(PinError::TooLong),
+        _ => Ok(()),
+    }
+}
+
+/// PinHash is the salted Argon2 hash of a pin, kept as a PHC string so it can be persisted
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct PinHash(String);
+
+/// get_hasher returns Argon2 with the recommended params, the tests use the smallest
+/// ones so adding a player doesn't take a second in a debug build
+fn get_hasher() -> Argon2<'static> {
+    #[cfg(not(test))]
+    let params = Params::default();
+    #[cfg(test)]
+    let params = Params::new(
+        Params::MIN_M_COST,
+        Params::MIN_T_COST,
+        Params::MIN_P_COST,
+        None,
+    )
+    .expect("the smallest params are valid");
+    Argon2::new(Default::default(), Default::default(), params)
+}
+
+impl PinHash {
+    pub fn new(pin: &str) -> Self {
+        let salt: [u8; 16] = rand::thread_rng().gen();
+        let salt = SaltString::encode_b64(&salt).expect("salt of a valid length");
+        let hash = get_hasher()
+            .hash_password(pin.as_bytes(), &salt)
+            .expect("pin hashed with valid params");
+        PinHash(hash.to_string())
+    }
+
+    /// verify checks the entered pin against the hash with the params saved in it,
+    /// a hash that can't be read matches nothing
+    pub fn verify(&self, pin_entered: &str) -> bool {
+        PasswordHash::new(&self.0).is_ok_and(|hash| {
+            Argon2::default()
+                .verify_password(pin_entered.as_bytes(), &hash)
+                .is_ok()
+        })
+    }
+}
+
+/// Lockout counts the wrong pins of a player and locks them out once there are too many,
+/// it's persisted with the pin so closing the app doesn't end a lockout
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct Lockout {
+    failed_attempts: u32,
+    // the lockouts since the last correct pin, each one longer than the one before
+    lockouts: u32,
+    // the wall clock time, as it has to mean the same after a restart
+    locked_until: Option<SystemTime>,
+}
+
+impl Lockout {
+    /// verify checks the entered pin against the hash, unless the player is locked out
+    pub fn verify(&mut self, hash: &PinHash, pin_entered: &str) -> Result<(), PinError> {
+        let now = SystemTime::now();
+        if let Some(until) = self.locked_until {
+            if let Ok(time_left) = until.duration_since(now) {
+                return Err(PinError::LockedOut(time_left));
+            }
+            self.locked_until = None;
+        }
+
+        if hash.verify(pin_entered) {
+            *self = Lockout::default();
+            return Ok(());
+        }
+        self.failed_attempts += 1;
+        if self.failed_attempts < MAX_ATTEMPTS {
+            return Err(PinError::Incorrect {
+                attempts_left: MAX_ATTEMPTS - self.failed_attempts,
+            });
+        }
+        let time = LOCKOUT_TIME
+            .saturating_mul(2u32.saturating_pow(self.lockouts))
+            .min(MAX_LOCKOUT_TIME);
+        self.failed_attempts = 0;
+        self.lockouts += 1;
+        self.locked_until = Some(now + time);
+        Err(PinError::LockedOut(time))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pin_hash() {
+        let hash = PinHash::new("1234");
+        assert!(hash.verify("1234"));
+        assert!(!hash.verify("4321"));
+        assert!(hash.0.starts_with("$argon2id$"));
+        assert!(!hash.0.contains("1234"));
+        // every hash has its own salt
+        assert_ne!(hash, PinHash::new("1234"));
+
+        let passphrase = PinHash::new("correct horse battery staple");
+        assert!(passphrase.verify("correct horse battery staple"));
+        assert!(!PinHash(String::from("not a hash")).verify(""));
+
+        let json = serde_json::to_string(&hash).unwrap();
+        assert!(serde_json::from_str::<PinHash>(&json)
+            .unwrap()
+            .verify("1234"));
+    }
+
+    #[test]
+    fn test_validate() {
+        assert_eq!(validate("123"), Err(PinError::TooShort));
+        assert_eq!(validate("1234"), Ok(()));
+        assert_eq!(validate("ключ"), Ok(()));
+        assert_eq!(
+            validate(&"a".repeat(MAX_PIN_LENGTH + 1)),
+            Err(PinError::TooLong)
+        );
+    }
+
+    #[test]
+    fn test_lockout() {
+        let hash = PinHash::new("1234");
+        let mut lockout = Lockout::default();
+        assert_eq!(
+            lockout.verify(&hash, "0000"),
+            Err(PinError::Incorrect { attempts_left: 2 })
+        );
+        // a correct pin starts the count again
+        assert_eq!(lockout.verify(&hash, "1234"), Ok(()));
+        lockout.verify(&hash, "0000").unwrap_err();
+        lockout.verify(&hash, "0000").unwrap_err();
+        assert_eq!(
+            lockout.verify(&hash, "0000"),
+            Err(PinError::LockedOut(LOCKOUT_TIME))
+        );
+        // even the correct pin is refused while locked out
+        assert!(matches!(
+            lockout.verify(&hash, "1234"),
+            Err(PinError::LockedOut(_))
+        ));
+
+        // the next lockout is twice as long
+        lockout.locked_until = Some(SystemTime::now());
+        for _ in 1..MAX_ATTEMPTS {
+            lockout.verify(&hash, "0000").unwrap_err();
+        }
+        assert_eq!(
+            lockout.verify(&hash, "0000"),
+            Err(PinError::LockedOut(LOCKOUT_TIME * 2))
+        );
+        // the lockout goes on after it's saved and loaded again
+        let json = serde_json::to_string(&lockout).unwrap();
+        let mut loaded: Lockout = serde_json::from_str(&json).unwrap();
+        assert!(matches!(
+            loaded.verify(&hash, "1234"),
+            Err(PinError::LockedOut(_))
+        ));
+        loaded.locked_until = Some(SystemTime::now());
+        assert_eq!(loaded.verify(&hash, "1234"), Ok(()));
+        assert_eq!(loaded, Lockout::default());
+    }
+}
@@ -1,19 +1,25 @@
-use crate::card::{Card, TRUMP_SUIT};
-use crate::game::Call;
+use crate::bid::Bid;
+use crate::card::{Card, Suit};
+use crate::pin::{Lockout, PinError, PinHash};
+use crate::rules::RuleSet;
 
 pub struct Player {
     name: String,
-    pin: u16,
+    // None for the players whose hand is never shown on a shared screen, e.g. the bots
+    pin: Option<PinHash>,
+    lockout: Lockout,
     cards: Vec<Card>,
-    call: Call,
+    call: Bid,
     points: u8,
 }
 
 impl Player {
-    pub fn new(name: &str, pin: &u16, call: Call) -> Self {
+    /// new creates the player with the hash of the pin, an empty pin leaves the hand unprotected
+    pub fn new(name: &str, pin: &str, call: Bid) -> Self {
         Player {
             name: name.to_string(),
-            pin: *pin,
+            pin: (!pin.is_empty()).then(|| PinHash::new(pin)),
+            lockout: Lockout::default(),
             cards: vec![],
             call,
             points: 0,
@@ -28,11 +34,23 @@ impl Player {
         self.cards.push(card)
     }
 
+    pub fn get_cards(&self) -> &[Card] {
+        &self.cards
+    }
 
-    pub fn reveal(&self, pin_entered: &u16) {
+    /// verify_pin checks the entered pin against the hash of the player's pin, the player
+    /// is locked out for a while after too many wrong pins
+    pub fn verify_pin(&mut self, pin_entered: &str) -> Result<(), PinError> {
+        match &self.pin {
+            Some(hash) => self.lockout.verify(hash, pin_entered),
+            None => Ok(()),
+        }
+    }
+
+    pub fn reveal(&mut self, pin_entered: &str) {
         // check if pin is added or not
-        if *pin_entered != self.pin {
-            println!("Incorrect Pin Entered");
+        if let Err(e) = self.verify_pin(pin_entered) {
+            println!("{}", e);
             return;
         }
 
@@ -59,38 +77,81 @@ impl Player {
         self.cards.swap_remove(card_idx)
     }
 
-    /// points to the eligible list of cards that the user needs to throw
-    pub fn show_eligible_cards(&self, lead_thrower: &Card) {
-        // check the lead thrower first
-        let mut eligible_cards: Vec<(&Card, usize)> = vec![];
-
-        for (i, c) in self.cards.iter().enumerate() {
-            if c.get_suit() == lead_thrower.get_suit() {
-                eligible_cards.push((&c, i))
+    /// get_eligible_cards returns the index of the cards the player is allowed to throw
+    /// - the lead suit has to be followed if the player has it
+    /// - otherwise a trump has to be thrown if the player has it
+    /// - otherwise any card can be thrown
+    ///
+    /// with the must overtake rule the winning card has to be beaten whenever the
+    /// player can, a player who can't beat a winning trump may throw any card
+    pub fn get_eligible_cards(
+        &self,
+        lead_suit: Option<Suit>,
+        winning_card: Option<&Card>,
+        rules: &RuleSet,
+    ) -> Vec<usize> {
+        let lead_suit = match lead_suit {
+            Some(s) => s,
+            None => return (0..self.cards.len()).collect(),
+        };
+
+        for suit in [lead_suit, rules.trump_suit] {
+            let eligible_cards: Vec<usize> = self
+                .cards
+                .iter()
+                .enumerate()
+                .filter(|(_, c)| c.get_suit() == suit)
+                .map(|(i, _)| i)
+                .collect();
+            if eligible_cards.is_empty() {
+                continue;
+            }
+            let winning_card = match winning_card {
+                Some(c) if rules.must_overtake => c,
+                _ => return eligible_cards,
+            };
+            let overtaking_cards: Vec<usize> = eligible_cards
+                .iter()
+                .copied()
+                .filter(|i| self.cards[*i].beats(winning_card, rules.trump_suit))
+                .collect();
+            if !overtaking_cards.is_empty() {
+                return overtaking_cards;
             }
-            if c.get_suit() == TRUMP_SUIT {
-                eligible_cards.push((&c, i))
+            if suit == lead_suit {
+                return eligible_cards;
             }
+            break;
         }
 
         // if empty then entire deck can be used for
-        if eligible_cards.is_empty() {
-            for (i, c) in self.cards.iter().enumerate() {
-                eligible_cards.push((&c, i))
-            }
-        }
+        (0..self.cards.len()).collect()
+    }
+
+    /// points to the eligible list of cards that the user needs to throw
+    pub fn show_eligible_cards(&self, lead_thrower: &Card, winning_card: &Card, rules: &RuleSet) {
+        let eligible_cards =
+            self.get_eligible_cards(Some(lead_thrower.get_suit()), Some(winning_card), rules);
 
         println!();
-        for t in eligible_cards.iter() {
-            print!("{}", t.0.get_print_str())
+        for i in eligible_cards.iter() {
+            print!("{}", self.cards[*i].get_print_str())
         }
         println!();
-        for t in eligible_cards.iter() {
-            print!(" {}    ", t.1)
+        for i in eligible_cards.iter() {
+            print!(" {}    ", i)
         }
         println!();
     }
 
+    pub fn set_call(&mut self, call: Bid) {
+        self.call = call
+    }
+
+    pub fn get_call(&self) -> Bid {
+        self.call
+    }
+
     pub fn add_points(&mut self, points: u8) {
         self.points += points
     }
@@ -98,8 +159,13 @@ impl Player {
     pub fn get_points(&self) -> u8 {
         self.points
     }
-}
 
+    /// clear_deal removes the cards and the points of the previous deal
+    pub fn clear_deal(&mut self) {
+        self.cards.clear();
+        self.points = 0;
+    }
+}
 
 #[cfg(test)]
 mod tests {
@@ -109,17 +175,17 @@ mod tests {
 
     #[test]
     fn test_new_deck() {
-        let player = Player::new("test", &1234, Call::Two(2));
+        let player = Player::new("test", "1234", Bid::default());
 
         assert_eq!(player.cards.len(), 0);
         assert_eq!(player.get_name(), "test");
-        assert_eq!(player.call, Call::Two(2));
-        assert_eq!(player.pin, 1234);
+        assert_eq!(player.call, Bid::default());
+        assert!(player.pin.as_ref().is_some_and(|p| p.verify("1234")));
     }
 
     #[test]
     fn test_add_cards() {
-        let mut player = Player::new("test", &1234, Call::Two(2));
+        let mut player = Player::new("test", "1234", Bid::default());
 
         player.add_card(Card::new(Suit::Diamonds, "J".to_string()));
         player.add_card(Card::new(Suit::Diamonds, "K".to_string()));
@@ -133,7 +199,7 @@ mod tests {
 
     #[test]
     fn test_get_card_idx() {
-        let mut player = Player::new("test", &1234, Call::Two(2));
+        let mut player = Player::new("test", "1234", Bid::default());
 
         player.add_card(Card::new(Suit::Diamonds, "J".to_string()));
         player.add_card(Card::new(Suit::Diamonds, "K".to_string()));
@@ -146,7 +212,7 @@ mod tests {
 
     #[test]
     fn test_throw() {
-        let mut player = Player::new("test", &1234, Call::Two(2));
+        let mut player = Player::new("test", "1234", Bid::default());
 
         player.add_card(Card::new(Suit::Diamonds, "J".to_string()));
         player.add_card(Card::new(Suit::Diamonds, "K".to_string()));
@@ -170,7 +236,7 @@ mod tests {
 
     #[test]
     fn test_add_points() {
-        let mut player = Player::new("test", &1234, Call::Two(2));
+        let mut player = Player::new("test", "1234", Bid::default());
         player.add_points(1);
         assert_eq!(player.points, 1);
 
@@ -180,4 +246,93 @@ mod tests {
         player.add_points(1);
         assert_eq!(player.points, 3);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_get_eligible_cards() {
+        let mut player = Player::new("test", "1234", Bid::default());
+
+        player.add_card(Card::new(Suit::Diamonds, "J".to_string()));
+        player.add_card(Card::new(Suit::Spade, "3".to_string()));
+        player.add_card(Card::new(Suit::Hearts, "A".to_string()));
+        player.add_card(Card::new(Suit::Diamonds, "4".to_string()));
+
+        let rules = RuleSet::default();
+        assert_eq!(
+            player.get_eligible_cards(None, None, &rules),
+            vec![0, 1, 2, 3]
+        );
+        assert_eq!(
+            player.get_eligible_cards(Some(Suit::Diamonds), None, &rules),
+            vec![0, 3]
+        );
+        // no clubs, so the trump has to be thrown
+        assert_eq!(
+            player.get_eligible_cards(Some(Suit::Club), None, &rules),
+            vec![1]
+        );
+
+        player.throw(1);
+        // no clubs and no trumps, any card can be thrown
+        assert_eq!(
+            player.get_eligible_cards(Some(Suit::Club), None, &rules),
+            vec![0, 1, 2]
+        );
+    }
+
+    #[test]
+    fn test_get_eligible_cards_must_overtake() {
+        let mut player = Player::new("test", "1234", Bid::default());
+        player.add_card(Card::new(Suit::Diamonds, "J".to_string()));
+        player.add_card(Card::new(Suit::Spade, "3".to_string()));
+        player.add_card(Card::new(Suit::Diamonds, "4".to_string()));
+        player.add_card(Card::new(Suit::Spade, "Q".to_string()));
+
+        let rules = RuleSet::nepali();
+        let winning = Card::new(Suit::Diamonds, "10".to_string());
+        // only the jack beats the ten
+        assert_eq!(
+            player.get_eligible_cards(Some(Suit::Diamonds), Some(&winning), &rules),
+            vec![0]
+        );
+        let winning = Card::new(Suit::Diamonds, "K".to_string());
+        // nothing beats the king, any diamond can follow
+        assert_eq!(
+            player.get_eligible_cards(Some(Suit::Diamonds), Some(&winning), &rules),
+            vec![0, 2]
+        );
+        let winning = Card::new(Suit::Spade, "5".to_string());
+        // void in clubs, the trump has to beat the winning trump
+        assert_eq!(
+            player.get_eligible_cards(Some(Suit::Club), Some(&winning), &rules),
+            vec![3]
+        );
+        let winning = Card::new(Suit::Spade, "K".to_string());
+        // the winning trump can't be beaten, so any card can be thrown
+        assert_eq!(
+            player.get_eligible_cards(Some(Suit::Club), Some(&winning), &rules),
+            vec![0, 1, 2, 3]
+        );
+        // without the rule any trump can be thrown
+        assert_eq!(
+            player.get_eligible_cards(Some(Suit::Club), Some(&winning), &RuleSet::default()),
+            vec![1, 3]
+        );
+    }
+
+    #[test]
+    fn test_clear_deal() {
+        let mut player = Player::new("test", "1234", Bid::default());
+        player.add_card(Card::new(Suit::Diamonds, "J".to_string()));
+        player.add_points(2);
+
+        player.clear_deal();
+        assert_eq!(player.cards.len(), 0);
+        assert_eq!(player.get_points(), 0);
+        assert_eq!(player.verify_pin("1234"), Ok(()));
+        assert!(player.verify_pin("4321").is_err());
+
+        // a player without a pin has nothing to check
+        let mut bot = Player::new("bot", "", Bid::default());
+        assert_eq!(bot.verify_pin("4321"), Ok(()));
+    }
+}
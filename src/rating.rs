@@ -0,0 +1,245 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::log::{GameLog, LogEvent};
+use crate::score::Score;
+
+// the rating of a player who hasn't played a rated game yet
+pub const DEFAULT_RATING: f64 = 1500.0;
+// the most a rating can move in a single game
+const K_FACTOR: f64 = 32.0;
+// a rating this much higher than the opponent's means 10 to 1 odds of finishing above them
+const RATING_SCALE: f64 = 400.0;
+
+/// Rating is the strength of a player, updated after every finished game
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Rating {
+    pub value: f64,
+    pub games: u32,
+}
+
+impl Default for Rating {
+    fn default() -> Self {
+        Rating {
+            value: DEFAULT_RATING,
+            games: 0,
+        }
+    }
+}
+
+/// Ratings are the pairwise ELO ratings of the players and the bots, every game is
+/// rated as a match between each pair of opponents on the final scores
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Ratings {
+    players: BTreeMap<String, Rating>,
+}
+
+impl Ratings {
+    /// from_logs rates the finished games of the saved logs in the directory, oldest first,
+    /// the files that aren't game logs are skipped
+    pub fn from_logs(dir: &Path) -> io::Result<Self> {
+        let mut logs = vec![];
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|e| e == "json") {
+                if let Ok(log) = GameLog::load(&path) {
+                    logs.push(log);
+                }
+            }
+        }
+        logs.sort_by_key(|log| log.get_entries().first().map(|e| e.time));
+
+        let mut ratings = Ratings::default();
+        for log in &logs {
+            ratings.record_game(log);
+        }
+        Ok(ratings)
+    }
+
+    /// get_rating returns the rating of the player, the default one for a new player
+    pub fn get_rating(&self, name: &str) -> Rating {
+        self.players.get(name).copied().unwrap_or_default()
+    }
+
+    /// get_ranking returns the rated players, the highest rating first
+    pub fn get_ranking(&self) -> Vec<(&str, Rating)> {
+        let mut ranking: Vec<(&str, Rating)> =
+            self.players.iter().map(|(n, r)| (n.as_str(), *r)).collect();
+        ranking.sort_by(|a, b| b.1.value.total_cmp(&a.1.value));
+        ranking
+    }
+
+    /// record_game rates every player of a finished game against each of their opponents,
+    /// false if the log isn't of a finished game
+    pub fn record_game(&mut self, log: &GameLog) -> bool {
+        let standings = match get_standings(log) {
+            Some(standings) => standings,
+            None => return false,
+        };
+        // every rating moves by the results against the ratings from before the game
+        let changes: Vec<f64> = standings
+            .iter()
+            .map(|player| {
+                let opponents: Vec<&Standing> =
+                    standings.iter().filter(|o| o.team != player.team).collect();
+                if opponents.is_empty() {
+                    return 0.0;
+                }
+                let rating = self.get_rating(&player.name).value;
+                let change: f64 = opponents
+                    .iter()
+                    .map(|o| {
+                        let result = match player.score.cmp(&o.score) {
+                            std::cmp::Ordering::Greater => 1.0,
+                            std::cmp::Ordering::Equal => 0.5,
+                            std::cmp::Ordering::Less => 0.0,
+                        };
+                        result - get_expected_result(rating, self.get_rating(&o.name).value)
+                    })
+                    .sum();
+                K_FACTOR * change / opponents.len() as f64
+            })
+            .collect();
+
+        for (player, change) in standings.iter().zip(changes) {
+            let rating = self.players.entry(player.name.clone()).or_default();
+            rating.value += change;
+            rating.games += 1;
+        }
+        true
+    }
+}
+
+/// get_expected_result returns the chance of the player finishing above the opponent,
+/// counting a tie as half
+fn get_expected_result(rating: f64, opponent_rating: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf((opponent_rating - rating) / RATING_SCALE))
+}
+
+/// Standing is the final score of a player, the score of the team in a partnership
+struct Standing {
+    name: String,
+    team: usize,
+    score: Score,
+}
+
+/// get_standings reads the players and their final scores from the log of a finished game
+fn get_standings(log: &GameLog) -> Option<Vec<Standing>> {
+    let mut players = vec![];
+    let mut teams = vec![];
+    for entry in log.get_entries() {
+        match &entry.event {
+            LogEvent::Started {
+                players: started,
+                rules,
+            } => {
+                players = started.clone();
+                teams = rules.get_teams(started.len());
+            }
+            LogEvent::Finished { score_board, .. } => {
+                let totals = score_board.totals();
+                return players
+                    .iter()
+                    .enumerate()
+                    .map(|(seat, name)| {
+                        let team = teams.iter().position(|t| t.contains(&seat))?;
+                        Some(Standing {
+                            name: name.clone(),
+                            team,
+                            score: *totals.get(team)?,
+                        })
+                    })
+                    .collect();
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bid::Bid;
+    use crate::rules::RuleSet;
+    use crate::score::ScoreBoard;
+
+    /// finished_game returns the log of a single deal game with the given calls made
+    fn finished_game(players: &[&str], calls: &[u8], rules: RuleSet) -> GameLog {
+        let mut log = GameLog::new();
+        log.record(LogEvent::Started {
+            players: players.iter().map(|p| p.to_string()).collect(),
+            rules,
+        });
+        let mut score_board = ScoreBoard::new((0..calls.len()).map(|i| i.to_string()).collect());
+        let deal: Vec<(Bid, u8)> = calls
+            .iter()
+            .map(|c| (Bid::new(*c, &RuleSet::default(), 4).unwrap(), *c))
+            .collect();
+        score_board.record_deal(1, &deal);
+        log.record(LogEvent::Finished {
+            score_board,
+            winner: None,
+        });
+        log
+    }
+
+    #[test]
+    fn test_record_game() {
+        let mut ratings = Ratings::default();
+        assert!(!ratings.record_game(&GameLog::new()));
+
+        let log = finished_game(&["a", "b", "c"], &[5, 3, 3], RuleSet::default());
+        assert!(ratings.record_game(&log));
+        let (a, b, c) = (
+            ratings.get_rating("a"),
+            ratings.get_rating("b"),
+            ratings.get_rating("c"),
+        );
+        // the winner beat both, the tied players split the game between them
+        assert_eq!(a.games, 1);
+        assert!((a.value - (DEFAULT_RATING + K_FACTOR / 2.0)).abs() < 1e-9);
+        assert!((b.value - (DEFAULT_RATING - K_FACTOR / 4.0)).abs() < 1e-9);
+        assert_eq!(b, c);
+        assert_eq!(ratings.get_ranking()[0].0, "a");
+        assert_eq!(ratings.get_rating("d"), Rating::default());
+
+        // beating a weaker player again is worth less
+        let before = ratings.get_rating("a").value;
+        ratings.record_game(&finished_game(&["a", "b"], &[4, 2], RuleSet::default()));
+        assert!(ratings.get_rating("a").value - before < K_FACTOR / 2.0);
+    }
+
+    #[test]
+    fn test_partners() {
+        let rules = RuleSet {
+            partnership: true,
+            ..RuleSet::default()
+        };
+        let mut ratings = Ratings::default();
+        ratings.record_game(&finished_game(&["a", "b", "c", "d"], &[4, 2], rules));
+        assert_eq!(ratings.get_rating("a"), ratings.get_rating("c"));
+        assert!(ratings.get_rating("a").value > DEFAULT_RATING);
+        assert!(ratings.get_rating("b").value < DEFAULT_RATING);
+    }
+
+    #[test]
+    fn test_from_logs() {
+        let dir = std::env::temp_dir().join(format!("callbreak-ratings-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let log = finished_game(&["a", "b"], &[4, 2], RuleSet::default());
+        log.save(&dir.join("first.json")).unwrap();
+        log.save(&dir.join("second.json")).unwrap();
+        fs::write(dir.join("notes.json"), "not a log").unwrap();
+        fs::write(dir.join("notes.txt"), "not a log").unwrap();
+
+        let ratings = Ratings::from_logs(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(ratings.get_rating("a").games, 2);
+        assert!(ratings.get_rating("a").value > ratings.get_rating("b").value);
+    }
+}
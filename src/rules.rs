@@ -0,0 +1,387 @@
+use rand_chacha::ChaCha20Rng;
+use serde::{Deserialize, Serialize};
+
+use rand::SeedableRng;
+
+use crate::bid::Bid;
+use crate::card::{Card, Suit, DEFAULT_TRUMP_SUIT};
+use crate::deck::{Deck, DECK_LEN};
+use crate::game::{GameError, DEFAULT_TOTAL_DEALS, MAX_CALL, MAX_PLAYERS, MIN_CALL, MIN_PLAYERS};
+use crate::score::{NilBid, Overtricks};
+use crate::shuffle::Seed;
+
+// a deal is shuffled again at most this many times, the last shuffle is kept after that
+const MAX_RESHUFFLES: usize = 10;
+// a partnership is played by two teams of two
+pub const PARTNERSHIP_PLAYERS: usize = 4;
+// the redeals a single deal allows by default
+pub const DEFAULT_MAX_REDEALS: u8 = 3;
+// the most redeals a single deal can allow
+pub const MAX_REDEALS: u8 = 10;
+
+/// Reshuffle is the rule to shuffle the deck again when a hand is too weak to play
+#[derive(Copy, Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Reshuffle {
+    #[default]
+    Never,
+    // a hand without a trump
+    NoTrump,
+    // a hand without a jack, queen or king
+    NoFaceCard,
+    NoTrumpOrFaceCard,
+}
+
+impl Reshuffle {
+    pub const ALL: [Reshuffle; 4] = [
+        Reshuffle::Never,
+        Reshuffle::NoTrump,
+        Reshuffle::NoFaceCard,
+        Reshuffle::NoTrumpOrFaceCard,
+    ];
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Reshuffle::Never => "Off",
+            Reshuffle::NoTrump => "No trump",
+            Reshuffle::NoFaceCard => "No face card",
+            Reshuffle::NoTrumpOrFaceCard => "No trump or face card",
+        }
+    }
+
+    /// is_needed checks if the hand calls for another shuffle
+    pub fn is_needed(&self, hand: &[Card], trump_suit: Suit) -> bool {
+        let no_trump = || !hand.iter().any(|c| c.get_suit() == trump_suit);
+        let no_face_card = || !hand.iter().any(|c| (11..=13).contains(&c.get_priority()));
+        match self {
+            Reshuffle::Never => false,
+            Reshuffle::NoTrump => no_trump(),
+            Reshuffle::NoFaceCard => no_face_card(),
+            Reshuffle::NoTrumpOrFaceCard => no_trump() || no_face_card(),
+        }
+    }
+}
+
+/// RuleSet holds the rules that differ between the conventions of callbreak
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RuleSet {
+    pub trump_suit: Suit,
+    pub min_call: u8,
+    pub max_call: u8,
+    pub overtricks: Overtricks,
+    pub total_deals: u8,
+    // a player has to beat the winning card of the round if they can
+    pub must_overtake: bool,
+    pub reshuffle: Reshuffle,
+    // the hands whose player may ask for a redeal instead of calling
+    #[serde(default)]
+    pub redeal: Reshuffle,
+    // the redeals allowed in a single deal
+    #[serde(default = "default_max_redeals")]
+    pub max_redeals: u8,
+    // four players play in two teams of the opposite seats, calling and scoring together
+    #[serde(default)]
+    pub partnership: bool,
+    // a player may call to take no tricks at all, scored apart from the other calls
+    #[serde(default)]
+    pub nil_bid: Option<NilBid>,
+    // the last caller can't make the calls add up to the rounds of the deal, so that
+    // someone is bound to fail
+    #[serde(default)]
+    pub hook: bool,
+    // the calls of a deal have to add up to at least this many, the last caller makes
+    // up for the rest, 0 for no minimum
+    #[serde(default)]
+    pub min_total_call: u8,
+}
+
+fn default_max_redeals() -> u8 {
+    DEFAULT_MAX_REDEALS
+}
+
+impl Default for RuleSet {
+    fn default() -> Self {
+        RuleSet {
+            trump_suit: DEFAULT_TRUMP_SUIT,
+            min_call: MIN_CALL,
+            max_call: MAX_CALL,
+            overtricks: Overtricks::Tenths,
+            total_deals: DEFAULT_TOTAL_DEALS,
+            must_overtake: false,
+            reshuffle: Reshuffle::Never,
+            redeal: Reshuffle::Never,
+            max_redeals: DEFAULT_MAX_REDEALS,
+            partnership: false,
+            nil_bid: None,
+            hook: false,
+            min_total_call: 0,
+        }
+    }
+}
+
+impl RuleSet {
+    /// nepali follows the rules played in Nepal: the winning card has to be beaten
+    /// if possible and a hand without a trump is shuffled again
+    pub fn nepali() -> Self {
+        RuleSet {
+            must_overtake: true,
+            reshuffle: Reshuffle::NoTrump,
+            ..RuleSet::default()
+        }
+    }
+
+    /// indian follows the rules common in India: the extra rounds don't score and a
+    /// hand without a trump or a face card is shuffled again
+    pub fn indian() -> Self {
+        RuleSet {
+            overtricks: Overtricks::Ignored,
+            reshuffle: Reshuffle::NoTrumpOrFaceCard,
+            ..RuleSet::default()
+        }
+    }
+
+    /// validate checks the call range against the calls the game knows about, the
+    /// redeals of a deal and the minimum total call against the largest table
+    pub fn validate(&self) -> Result<(), GameError> {
+        if self.min_call > self.max_call || self.min_call < MIN_CALL || self.max_call > MAX_CALL {
+            return Err(GameError::InvalidCall);
+        }
+        if self.max_redeals > MAX_REDEALS {
+            return Err(GameError::InvalidRedeals);
+        }
+        if !(MIN_PLAYERS..=MAX_PLAYERS).any(|total| self.validate_players(total).is_ok()) {
+            return Err(GameError::InvalidMinTotal);
+        }
+        Ok(())
+    }
+
+    /// validate_players checks that the calls of a table of the given size can add up
+    /// to the minimum total call
+    pub fn validate_players(&self, total_players: usize) -> Result<(), GameError> {
+        let (_, max_call) = self.get_call_range(total_players);
+        if self.min_total_call as usize > total_players * max_call as usize {
+            return Err(GameError::InvalidMinTotal);
+        }
+        Ok(())
+    }
+
+    /// get_teams returns the seats of every team, the opposite seats are partners in a
+    /// partnership and everyone plays alone otherwise
+    pub fn get_teams(&self, total_players: usize) -> Vec<Vec<usize>> {
+        if self.partnership && total_players == PARTNERSHIP_PLAYERS {
+            return vec![vec![0, 2], vec![1, 3]];
+        }
+        (0..total_players).map(|seat| vec![seat]).collect()
+    }
+
+    /// get_nil_bid returns the scoring of the nil calls, a nil call is made alone so
+    /// there is none in a partnership
+    pub fn get_nil_bid(&self) -> Option<NilBid> {
+        if self.partnership {
+            return None;
+        }
+        self.nil_bid
+    }
+
+    /// get_hand_size returns the cards every player gets, the low cards that can't be
+    /// dealt evenly are left out of the deck
+    pub fn get_hand_size(total_players: usize) -> u8 {
+        DECK_LEN / total_players.max(1) as u8
+    }
+
+    /// get_call_range returns the calls allowed at a table of the given size, no call
+    /// can be higher than the hand
+    pub fn get_call_range(&self, total_players: usize) -> (u8, u8) {
+        let max_call = self.max_call.min(Self::get_hand_size(total_players));
+        (self.min_call.min(max_call), max_call)
+    }
+
+    /// get_forbidden_bids returns the bids the last caller can't make, once the calls of
+    /// the others add up to `total`, the highest bid is always left allowed
+    pub fn get_forbidden_bids(&self, total: u8, total_players: usize) -> Vec<Bid> {
+        let bids = Bid::get_bids(self, total_players);
+        let total_rounds = Self::get_hand_size(total_players);
+        let mut forbidden: Vec<Bid> = bids
+            .iter()
+            .copied()
+            .filter(|b| {
+                let sum = total + b.get_value();
+                (self.hook && sum == total_rounds) || sum < self.min_total_call
+            })
+            .collect();
+        if forbidden.len() == bids.len() {
+            forbidden.pop();
+        }
+        forbidden
+    }
+
+    /// deal shuffles the deck from the seed and deals it starting from the hand at
+    /// `first_seat`, shuffling again while a hand calls for it, the lowest cards are
+    /// left out when the deck doesn't split evenly among the players
+    pub fn deal(&self, seed: Seed, total_players: usize, first_seat: usize) -> Vec<Vec<Card>> {
+        let mut rng = ChaCha20Rng::from_seed(seed);
+        let cards_each = Self::get_hand_size(total_players) as usize;
+        let mut hands = vec![];
+        for _ in 0..MAX_RESHUFFLES {
+            let mut deck = Deck::new();
+            deck.remove_low_cards(total_players, self.trump_suit);
+            deck.shuffle_with_rng(&mut rng);
+            hands = deck.deal(total_players, first_seat, cards_each);
+            if !hands
+                .iter()
+                .any(|h| self.reshuffle.is_needed(h, self.trump_suit))
+            {
+                break;
+            }
+        }
+        hands
+    }
+}
+
+/// RulePreset names the rule sets a game can be set up with
+#[derive(Copy, Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RulePreset {
+    #[default]
+    Standard,
+    Nepali,
+    Indian,
+}
+
+impl RulePreset {
+    pub const ALL: [RulePreset; 3] = [RulePreset::Standard, RulePreset::Nepali, RulePreset::Indian];
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            RulePreset::Standard => "Standard",
+            RulePreset::Nepali => "Nepali",
+            RulePreset::Indian => "Indian",
+        }
+    }
+
+    pub fn get_rules(&self) -> RuleSet {
+        match self {
+            RulePreset::Standard => RuleSet::default(),
+            RulePreset::Nepali => RuleSet::nepali(),
+            RulePreset::Indian => RuleSet::indian(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reshuffle() {
+        let hand = vec![
+            Card::new(Suit::Hearts, String::from("K")),
+            Card::new(Suit::Club, String::from("2")),
+        ];
+        assert!(!Reshuffle::Never.is_needed(&hand, Suit::Spade));
+        assert!(Reshuffle::NoTrump.is_needed(&hand, Suit::Spade));
+        assert!(!Reshuffle::NoTrump.is_needed(&hand, Suit::Club));
+        assert!(!Reshuffle::NoFaceCard.is_needed(&hand, Suit::Spade));
+        assert!(Reshuffle::NoTrumpOrFaceCard.is_needed(&hand, Suit::Spade));
+    }
+
+    #[test]
+    fn test_deal() {
+        let rules = RuleSet::nepali();
+        let hands = rules.deal([3; 32], 4, 1);
+        assert_eq!(hands, rules.deal([3; 32], 4, 1));
+        assert!(hands.iter().all(|h| h.len() == 13));
+        // every hand has a trump under the nepali rules
+        assert!(hands
+            .iter()
+            .all(|h| !rules.reshuffle.is_needed(h, rules.trump_suit)));
+
+        // the 2s that don't split among 3 and 5 players are left out
+        for (total_players, hand_size) in [(2, 26), (3, 17), (4, 13), (5, 10)] {
+            let hands = rules.deal([5; 32], total_players, 0);
+            assert_eq!(RuleSet::get_hand_size(total_players), hand_size);
+            assert!(hands.iter().all(|h| h.len() == hand_size as usize));
+        }
+        assert_eq!(rules.get_call_range(5), (2, 8));
+        let rules = RuleSet {
+            max_call: 8,
+            ..RuleSet::default()
+        };
+        assert_eq!(rules.get_call_range(8), (2, 6));
+    }
+
+    #[test]
+    fn test_teams() {
+        let rules = RuleSet::default();
+        assert_eq!(rules.get_teams(3), vec![vec![0], vec![1], vec![2]]);
+        let rules = RuleSet {
+            partnership: true,
+            ..RuleSet::default()
+        };
+        assert_eq!(rules.get_teams(4), vec![vec![0, 2], vec![1, 3]]);
+        let rules = RuleSet {
+            nil_bid: Some(NilBid::default()),
+            ..rules
+        };
+        assert_eq!(rules.get_nil_bid(), None);
+    }
+
+    #[test]
+    fn test_forbidden_bids() {
+        let rules = RuleSet::default();
+        assert!(rules.get_forbidden_bids(10, 4).is_empty());
+
+        let bid = |value| Bid::new(value, &rules, 4).unwrap();
+        // the hook: 13 rounds, so a call of 3 can't follow 10
+        let rules = RuleSet {
+            hook: true,
+            ..RuleSet::default()
+        };
+        assert_eq!(rules.get_forbidden_bids(10, 4), vec![bid(3)]);
+        assert!(rules.get_forbidden_bids(2, 4).is_empty());
+
+        let rules = RuleSet {
+            min_total_call: 11,
+            ..RuleSet::default()
+        };
+        assert_eq!(rules.get_forbidden_bids(7, 4), vec![bid(2), bid(3)]);
+        // the highest call is allowed even if the total can't be reached
+        assert_eq!(rules.get_forbidden_bids(2, 4).len(), 6);
+        assert!(!rules.get_forbidden_bids(2, 4).contains(&bid(8)));
+    }
+
+    #[test]
+    fn test_presets() {
+        for preset in RulePreset::ALL {
+            assert_eq!(preset.get_rules().validate(), Ok(()));
+        }
+        assert_eq!(
+            RulePreset::Indian.get_rules().overtricks,
+            Overtricks::Ignored
+        );
+        let rules = RuleSet {
+            min_call: 1,
+            ..RuleSet::default()
+        };
+        assert_eq!(rules.validate(), Err(GameError::InvalidCall));
+        let rules = RuleSet {
+            max_redeals: MAX_REDEALS + 1,
+            ..RuleSet::default()
+        };
+        assert_eq!(rules.validate(), Err(GameError::InvalidRedeals));
+
+        // 5 players can call up to 40 together, 2 players only up to 16
+        let rules = RuleSet {
+            min_total_call: 20,
+            ..RuleSet::default()
+        };
+        assert_eq!(rules.validate(), Ok(()));
+        assert_eq!(rules.validate_players(2), Err(GameError::InvalidMinTotal));
+        assert_eq!(rules.validate_players(4), Ok(()));
+        let rules = RuleSet {
+            min_total_call: 41,
+            ..RuleSet::default()
+        };
+        assert_eq!(rules.validate(), Err(GameError::InvalidMinTotal));
+    }
+}
@@ -0,0 +1,338 @@
+use std::cmp::Reverse;
+use std::fmt;
+use std::ops::{Add, AddAssign};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::bid::Bid;
+
+// the bonus and the penalty of a nil call by default
+pub const DEFAULT_NIL_POINTS: u8 = 5;
+
+/// Score keeps the points in tenths, so that the overtricks (0.1 each) can be
+/// added up without running into floating point errors
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Default, Serialize, Deserialize)]
+pub struct Score(i32);
+
+impl Score {
+    /// calculates the score of a single deal from the call and the tricks taken
+    /// - tricks >= call: call + 0.1 for every extra trick
+    /// - tricks < call: -call
+    pub fn from_deal(call: Bid, tricks: u8) -> Self {
+        let value = call.get_value() as i32;
+        if call.is_made(tricks) {
+            Score(value * 10 + call.get_overtricks(tricks) as i32)
+        } else {
+            Score(-value * 10)
+        }
+    }
+
+    /// from_deal_with calculates the score of a deal under the given overtricks rule
+    pub fn from_deal_with(call: Bid, tricks: u8, overtricks: Overtricks) -> Self {
+        match overtricks {
+            Overtricks::Tenths => Score::from_deal(call, tricks),
+            Overtricks::Ignored => Score::from_deal(call, tricks.min(call.get_value())),
+        }
+    }
+
+    /// from_nil calculates the score of a nil call, the bonus if no trick was taken
+    /// and minus the penalty otherwise
+    pub fn from_nil(tricks: u8, nil_bid: NilBid) -> Self {
+        if tricks == 0 {
+            Score(nil_bid.bonus as i32 * 10)
+        } else {
+            Score(-(nil_bid.penalty as i32) * 10)
+        }
+    }
+
+    pub fn from_tenths(tenths: i32) -> Self {
+        Score(tenths)
+    }
+
+    pub fn get_tenths(&self) -> i32 {
+        self.0
+    }
+}
+
+/// Overtricks is how the tricks taken over the call are scored
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Overtricks {
+    // 0.1 for every extra trick
+    #[default]
+    Tenths,
+    // the extra tricks don't score
+    Ignored,
+}
+
+/// NilBid is the worth of a call to take no tricks at all, in whole points
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct NilBid {
+    pub bonus: u8,
+    pub penalty: u8,
+}
+
+impl Default for NilBid {
+    fn default() -> Self {
+        NilBid {
+            bonus: DEFAULT_NIL_POINTS,
+            penalty: DEFAULT_NIL_POINTS,
+        }
+    }
+}
+
+impl fmt::Display for Score {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        f.pad(&format!(
+            "{}{}.{}",
+            sign,
+            self.0.abs() / 10,
+            self.0.abs() % 10
+        ))
+    }
+}
+
+// reads the score as it's shown, e.g. "-3.1", a whole number of points is also accepted
+impl FromStr for Score {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid score {}", s);
+        let (points, tenths) = s.trim().split_once('.').unwrap_or((s.trim(), "0"));
+        if tenths.len() != 1 {
+            return Err(invalid());
+        }
+        let negative = points.starts_with('-');
+        let points: i32 = points.parse().map_err(|_| invalid())?;
+        let tenths: i32 = tenths.parse().map_err(|_| invalid())?;
+        let value = points.abs() * 10 + tenths;
+        Ok(Score(if negative { -value } else { value }))
+    }
+}
+
+impl Add for Score {
+    type Output = Score;
+
+    fn add(self, rhs: Score) -> Score {
+        Score(self.0 + rhs.0)
+    }
+}
+
+impl AddAssign for Score {
+    fn add_assign(&mut self, rhs: Score) {
+        self.0 += rhs.0
+    }
+}
+
+/// DealEntry is the result of a single player in a deal
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DealEntry {
+    pub player: String,
+    pub call: Bid,
+    pub tricks: u8,
+    pub score: Score,
+}
+
+/// DealRecord holds the results of all the players for a single deal
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DealRecord {
+    pub deal_no: u8,
+    pub entries: Vec<DealEntry>,
+}
+
+/// ScoreBoard keeps the history of every deal played in a match
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScoreBoard {
+    players: Vec<String>,
+    deals: Vec<DealRecord>,
+    #[serde(default)]
+    overtricks: Overtricks,
+    // the scoring of the nil calls, None if a nil call is scored like any other
+    #[serde(default)]
+    nil_bid: Option<NilBid>,
+}
+
+impl ScoreBoard {
+    pub fn new(players: Vec<String>) -> Self {
+        ScoreBoard::with_overtricks(players, Overtricks::Tenths)
+    }
+
+    /// with_overtricks creates a scoreboard that scores the extra tricks by the given rule
+    pub fn with_overtricks(players: Vec<String>, overtricks: Overtricks) -> Self {
+        ScoreBoard {
+            players,
+            deals: vec![],
+            overtricks,
+            nil_bid: None,
+        }
+    }
+
+    /// with_nil_bid scores the nil calls with their bonus and penalty
+    pub fn with_nil_bid(mut self, nil_bid: Option<NilBid>) -> Self {
+        self.nil_bid = nil_bid;
+        self
+    }
+
+    /// record_deal adds the result of a finished deal, the entries are expected
+    /// to be in the same order as the players of the scoreboard
+    pub fn record_deal(&mut self, deal_no: u8, calls_and_tricks: &[(Bid, u8)]) {
+        let entries = self
+            .players
+            .iter()
+            .zip(calls_and_tricks.iter())
+            .map(|(player, (call, tricks))| DealEntry {
+                player: player.to_string(),
+                call: *call,
+                tricks: *tricks,
+                score: match self.nil_bid {
+                    Some(nil_bid) if call.is_nil() => Score::from_nil(*tricks, nil_bid),
+                    _ => Score::from_deal_with(*call, *tricks, self.overtricks),
+                },
+            })
+            .collect();
+
+        self.deals.push(DealRecord { deal_no, entries });
+    }
+
+    pub fn get_players(&self) -> &[String] {
+        &self.players
+    }
+
+    pub fn get_deals(&self) -> &[DealRecord] {
+        &self.deals
+    }
+
+    /// running_totals returns the cumulative score of every player after each deal
+    pub fn running_totals(&self) -> Vec<Vec<Score>> {
+        let mut totals = vec![Score::default(); self.players.len()];
+        let mut running = vec![];
+        for deal in self.deals.iter() {
+            for (i, e) in deal.entries.iter().enumerate() {
+                totals[i] += e.score;
+            }
+            running.push(totals.clone());
+        }
+        running
+    }
+
+    /// totals returns the current total score of every player
+    pub fn totals(&self) -> Vec<Score> {
+        self.running_totals()
+            .pop()
+            .unwrap_or_else(|| vec![Score::default(); self.players.len()])
+    }
+
+    /// standings returns the players sorted by their total score, highest first
+    pub fn standings(&self) -> Vec<(String, Score)> {
+        let mut standings: Vec<(String, Score)> =
+            self.players.iter().cloned().zip(self.totals()).collect();
+        // stable sort keeps the seating order for the ties
+        standings.sort_by_key(|s| Reverse(s.1));
+        standings
+    }
+
+    /// winner returns the player with the highest total, None if no deal was played
+    /// or if the top score is shared
+    pub fn winner(&self) -> Option<String> {
+        if self.deals.is_empty() {
+            return None;
+        }
+        let standings = self.standings();
+        if standings.len() > 1 && standings[0].1 == standings[1].1 {
+            return None;
+        }
+        standings.first().map(|s| s.0.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::RuleSet;
+
+    fn bid(value: u8) -> Bid {
+        Bid::new(value, &RuleSet::default(), 4).unwrap()
+    }
+
+    #[test]
+    fn test_score_from_deal() {
+        assert_eq!(Score::from_deal(bid(3), 3), Score(30));
+        assert_eq!(Score::from_deal(bid(3), 5), Score(32));
+        assert_eq!(Score::from_deal(bid(4), 2), Score(-40));
+        assert_eq!(
+            Score::from_deal_with(bid(3), 5, Overtricks::Ignored),
+            Score(30)
+        );
+        assert_eq!(
+            Score::from_deal_with(bid(4), 2, Overtricks::Ignored),
+            Score(-40)
+        );
+    }
+
+    #[test]
+    fn test_nil_bid() {
+        let nil_bid = NilBid {
+            bonus: 5,
+            penalty: 3,
+        };
+        assert_eq!(Score::from_nil(0, nil_bid), Score(50));
+        assert_eq!(Score::from_nil(2, nil_bid), Score(-30));
+
+        let mut board =
+            ScoreBoard::new(vec!["a".to_string(), "b".to_string()]).with_nil_bid(Some(nil_bid));
+        board.record_deal(1, &[(Bid::NIL, 0), (bid(3), 4)]);
+        board.record_deal(2, &[(bid(2), 3), (Bid::NIL, 1)]);
+        assert_eq!(board.totals(), vec![Score(71), Score(1)]);
+    }
+
+    #[test]
+    fn test_score_display() {
+        assert_eq!(Score::from_deal(bid(3), 4).to_string(), "3.1");
+        assert_eq!(Score::from_deal(bid(5), 1).to_string(), "-5.0");
+        assert_eq!(Score::default().to_string(), "0.0");
+        assert_eq!((Score(-40) + Score(32)).to_string(), "-0.8");
+        assert_eq!(format!("{:>5}", Score(31)), "  3.1");
+
+        for score in [Score(31), Score(-50), Score(-8), Score(0)] {
+            assert_eq!(score.to_string().parse(), Ok(score));
+        }
+        assert_eq!("12".parse(), Ok(Score(120)));
+        assert!("1.25".parse::<Score>().is_err());
+        assert!("abc".parse::<Score>().is_err());
+    }
+
+    #[test]
+    fn test_record_deal_and_totals() {
+        let mut board = ScoreBoard::new(vec!["a".to_string(), "b".to_string()]);
+        board.record_deal(1, &[(bid(3), 4), (bid(2), 1)]);
+        board.record_deal(2, &[(bid(2), 2), (bid(4), 5)]);
+
+        assert_eq!(board.get_deals().len(), 2);
+        assert_eq!(board.get_deals()[0].entries[0].score, Score(31));
+        assert_eq!(board.get_deals()[0].entries[1].score, Score(-20));
+
+        let running = board.running_totals();
+        assert_eq!(running[0], vec![Score(31), Score(-20)]);
+        assert_eq!(running[1], vec![Score(51), Score(21)]);
+        assert_eq!(board.totals(), vec![Score(51), Score(21)]);
+    }
+
+    #[test]
+    fn test_standings_and_winner() {
+        let mut board = ScoreBoard::new(vec!["a".to_string(), "b".to_string(), "c".to_string()]);
+        assert_eq!(board.winner(), None);
+
+        board.record_deal(1, &[(bid(2), 1), (bid(3), 3), (bid(2), 2)]);
+        let standings = board.standings();
+        assert_eq!(standings[0].0, "b");
+        assert_eq!(standings[1].0, "c");
+        assert_eq!(standings[2].0, "a");
+        assert_eq!(board.winner(), Some("b".to_string()));
+
+        board.record_deal(2, &[(bid(2), 2), (bid(2), 2), (bid(3), 3)]);
+        // b and c are tied on 5.0
+        assert_eq!(board.winner(), None);
+    }
+}
@@ -0,0 +1,228 @@
+use std::fmt;
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::card::Card;
+use crate::rules::RuleSet;
+
+/// Seed decides the whole shuffle of a deck
+pub type Seed = [u8; 32];
+
+/// new_seed picks a random seed, for the server or as the entropy of a player
+pub fn new_seed() -> Seed {
+    let mut seed = [0; 32];
+    rand::thread_rng().fill(&mut seed);
+    seed
+}
+
+/// commit returns the SHA-256 of the seed in hex, it's published before the game
+/// so the seed can't be changed once the players have sent their entropy
+pub fn commit(seed: &Seed) -> String {
+    hex::encode(Sha256::digest(seed))
+}
+
+/// get_deal_seed mixes the seed of the server with the entropy of every player,
+/// so no one alone decides the shuffle of the deal
+pub fn get_deal_seed(server_seed: &Seed, entropies: &[String], deal: u8) -> Seed {
+    let mut hasher = Sha256::new();
+    hasher.update(server_seed);
+    for entropy in entropies {
+        // the length keeps the entropies of two players from running into each other
+        hasher.update((entropy.len() as u64).to_le_bytes());
+        hasher.update(entropy.as_bytes());
+    }
+    hasher.update([deal]);
+    hasher.finalize().into()
+}
+
+/// get_duplicate_seeds returns the seed of the server for every deal of a duplicate
+/// match, every table playing the match gets the same deals from the seed of the match
+pub fn get_duplicate_seeds(match_seed: &Seed, total_deals: u8) -> Vec<Seed> {
+    (1..=total_deals)
+        .map(|deal| {
+            let mut hasher = Sha256::new();
+            hasher.update(b"duplicate");
+            hasher.update(match_seed);
+            hasher.update([deal]);
+            hasher.finalize().into()
+        })
+        .collect()
+}
+
+/// parse_seed reads a seed written in hex, e.g. the seed of a duplicate match
+pub fn parse_seed(hex_seed: &str) -> Option<Seed> {
+    hex::decode(hex_seed.trim())
+        .ok()
+        .and_then(|seed| seed.try_into().ok())
+}
+
+/// get_redeal_seed returns the seed of a redeal of the deal, the first deal keeps
+/// the seed of the deal
+pub fn get_redeal_seed(deal_seed: &Seed, redeal: u8) -> Seed {
+    if redeal == 0 {
+        return *deal_seed;
+    }
+    let mut hasher = Sha256::new();
+    hasher.update(deal_seed);
+    hasher.update([redeal]);
+    hasher.finalize().into()
+}
+
+#[derive(PartialEq, Debug)]
+pub enum ShuffleError {
+    InvalidSeed,
+    WrongCommitment,
+    MissingEntropy,
+    WrongHand,
+}
+
+impl fmt::Display for ShuffleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = match self {
+            ShuffleError::InvalidSeed => "The revealed seed is not valid",
+            ShuffleError::WrongCommitment => "The revealed seed doesn't match the commitment",
+            ShuffleError::MissingEntropy => "The entropy of the player was left out",
+            ShuffleError::WrongHand => "The hand doesn't match the shuffle",
+        };
+        write!(f, "{}", msg)
+    }
+}
+
+/// ShuffleProof is revealed once a deal is over, with it every player can shuffle
+/// and deal the deck again and compare their hand with the one they got
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ShuffleProof {
+    pub deal: u8,
+    // the seed of the server in hex, its hash was published before the game
+    pub server_seed: String,
+    // the entropy sent by every player, in the order of the seats
+    pub entropies: Vec<String>,
+    // the redeals asked for in the deal, the hands were dealt from the last one
+    #[serde(default)]
+    pub redeals: u8,
+}
+
+impl ShuffleProof {
+    pub fn new(deal: u8, server_seed: &Seed, entropies: Vec<String>, redeals: u8) -> Self {
+        ShuffleProof {
+            deal,
+            server_seed: hex::encode(server_seed),
+            entropies,
+            redeals,
+        }
+    }
+
+    pub fn get_server_seed(&self) -> Result<Seed, ShuffleError> {
+        parse_seed(&self.server_seed).ok_or(ShuffleError::InvalidSeed)
+    }
+
+    /// deal_hands shuffles and deals the deck of the deal again, the same way the game
+    /// does under the rules starting from the seat at `first_seat`
+    pub fn deal_hands(
+        &self,
+        first_seat: usize,
+        rules: &RuleSet,
+    ) -> Result<Vec<Vec<Card>>, ShuffleError> {
+        let total_players = self.entropies.len();
+        if total_players == 0 || first_seat >= total_players {
+            return Err(ShuffleError::MissingEntropy);
+        }
+        let seed = get_deal_seed(&self.get_server_seed()?, &self.entropies, self.deal);
+        let seed = get_redeal_seed(&seed, self.redeals);
+        Ok(rules.deal(seed, total_players, first_seat))
+    }
+
+    /// verify checks the proof against the commitment published before the game,
+    /// the entropy the player sent and the hand they were dealt
+    pub fn verify(
+        &self,
+        commitment: &str,
+        seat: usize,
+        entropy: &str,
+        first_seat: usize,
+        hand: &[Card],
+        rules: &RuleSet,
+    ) -> Result<(), ShuffleError> {
+        if commit(&self.get_server_seed()?) != commitment {
+            return Err(ShuffleError::WrongCommitment);
+        }
+        if self.entropies.get(seat).map(String::as_str) != Some(entropy) {
+            return Err(ShuffleError::MissingEntropy);
+        }
+        let hands = self.deal_hands(first_seat, rules)?;
+        if hands.get(seat).map(Vec::as_slice) != Some(hand) {
+            return Err(ShuffleError::WrongHand);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Game;
+
+    #[test]
+    fn test_verify_shuffle() {
+        let server_seed = new_seed();
+        let commitment = commit(&server_seed);
+        let entropies = vec![String::from("aa"), String::from("bb")];
+
+        // the game deals from the same seed the proof reveals later, shuffling again
+        // under the same rules
+        let rules = RuleSet::indian();
+        let mut game = Game::new_game(rules.clone()).unwrap();
+        game.add_players("a", "").unwrap();
+        game.add_players("b", "").unwrap();
+        game.set_shuffle_seeds(vec![get_deal_seed(&server_seed, &entropies, 1)])
+            .unwrap();
+        game.start().unwrap();
+        let first_seat = game
+            .get_players()
+            .iter()
+            .position(|p| Some(p.get_name()) == game.get_current_player().map(|c| c.get_name()))
+            .unwrap();
+        let hand = game.get_players()[1].get_cards().to_vec();
+
+        let proof = ShuffleProof::new(1, &server_seed, entropies.clone(), 0);
+        assert_eq!(
+            proof.verify(&commitment, 1, "bb", first_seat, &hand, &rules),
+            Ok(())
+        );
+        assert_eq!(
+            proof.verify(&commitment, 1, "aa", first_seat, &hand, &rules),
+            Err(ShuffleError::MissingEntropy)
+        );
+        assert_eq!(
+            proof.verify(&commitment, 0, "aa", first_seat, &hand, &rules),
+            Err(ShuffleError::WrongHand)
+        );
+
+        // the server can't pick another seed after the commitment
+        let other = ShuffleProof::new(1, &new_seed(), entropies.clone(), 0);
+        assert_eq!(
+            other.verify(&commitment, 1, "bb", first_seat, &hand, &rules),
+            Err(ShuffleError::WrongCommitment)
+        );
+
+        // a redeal shuffles from another seed
+        let redealt = ShuffleProof::new(1, &server_seed, entropies, 1);
+        assert_eq!(
+            redealt.verify(&commitment, 1, "bb", first_seat, &hand, &rules),
+            Err(ShuffleError::WrongHand)
+        );
+    }
+
+    #[test]
+    fn test_duplicate_seeds() {
+        let match_seed = new_seed();
+        let seeds = get_duplicate_seeds(&match_seed, 3);
+        assert_eq!(seeds.len(), 3);
+        assert_eq!(seeds, get_duplicate_seeds(&match_seed, 3));
+        assert_ne!(seeds[0], seeds[1]);
+        assert_eq!(parse_seed(&hex::encode(match_seed)), Some(match_seed));
+        assert_eq!(parse_seed("abcd"), None);
+    }
+}
@@ -0,0 +1,389 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::bid::Bid;
+use crate::card::Card;
+use crate::log::{GameLog, LogEvent};
+use crate::rating::Ratings;
+use crate::rules::RuleSet;
+use crate::score::{Score, ScoreBoard};
+
+const STATS_DIR: &str = "callbreak";
+const STATS_FILE: &str = "stats.json";
+
+/// HeadToHead is the record of a player against another one, from the games they
+/// played against each other
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct HeadToHead {
+    pub wins: u32,
+    pub losses: u32,
+    pub ties: u32,
+}
+
+/// PlayerStats are the lifetime statistics of a player
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlayerStats {
+    pub games_played: u32,
+    pub wins: u32,
+    // the final scores of all the games added up
+    pub total_score: Score,
+    pub deals_played: u32,
+    // the deals in which the player won at least the rounds they called
+    pub calls_made: u32,
+    // the rounds won over the call in the deals the call was made
+    pub overtricks: u32,
+    pub cards_thrown: u32,
+    pub trumps_thrown: u32,
+    // the games won in a row up to the last game, and the most ever
+    pub current_streak: u32,
+    pub longest_streak: u32,
+    // the record against every opponent, by their name
+    pub head_to_head: BTreeMap<String, HeadToHead>,
+}
+
+impl PlayerStats {
+    pub fn get_average_score(&self) -> f32 {
+        ratio(
+            self.total_score.get_tenths() as f32 / 10.0,
+            self.games_played,
+        )
+    }
+
+    /// get_call_accuracy returns the share of the deals in which the call was made
+    pub fn get_call_accuracy(&self) -> f32 {
+        ratio(self.calls_made as f32, self.deals_played)
+    }
+
+    /// get_overtrick_rate returns the rounds won over the call in an average deal
+    pub fn get_overtrick_rate(&self) -> f32 {
+        ratio(self.overtricks as f32, self.deals_played)
+    }
+
+    /// get_trump_rate returns the share of the cards thrown that were trumps
+    pub fn get_trump_rate(&self) -> f32 {
+        ratio(self.trumps_thrown as f32, self.cards_thrown)
+    }
+}
+
+fn ratio(value: f32, total: u32) -> f32 {
+    if total == 0 {
+        return 0.0;
+    }
+    value / total as f32
+}
+
+/// Stats holds the statistics of every player, persisted as JSON in the data directory
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Stats {
+    players: BTreeMap<String, PlayerStats>,
+    // the ratings of everyone the players played with, the bots included
+    #[serde(default)]
+    ratings: Ratings,
+}
+
+impl Stats {
+    /// get_path returns the path of the stats file, e.g. ~/.local/share/callbreak/stats.json
+    pub fn get_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join(STATS_DIR).join(STATS_FILE))
+    }
+
+    /// load reads the stats file, empty stats are used if there is no file yet
+    pub fn load() -> io::Result<Self> {
+        match Self::get_path() {
+            Some(path) if path.exists() => Self::load_from(&path),
+            _ => Ok(Stats::default()),
+        }
+    }
+
+    pub fn load_from(path: &Path) -> io::Result<Self> {
+        let content = fs::read_to_string(path)?;
+        serde_json::from_str(&content).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// save writes the stats to the stats file, creating the directory if needed
+    pub fn save(&self) -> io::Result<()> {
+        let path = Self::get_path()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "data directory not found"))?;
+        self.save_to(&path)
+    }
+
+    pub fn save_to(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let content = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(path, content)
+    }
+
+    /// get_players returns the statistics of every player, sorted by name
+    pub fn get_players(&self) -> Vec<(&str, &PlayerStats)> {
+        self.players.iter().map(|(n, s)| (n.as_str(), s)).collect()
+    }
+
+    pub fn get_player(&self, name: &str) -> Option<&PlayerStats> {
+        self.players.get(name)
+    }
+
+    pub fn get_ratings(&self) -> &Ratings {
+        &self.ratings
+    }
+
+    /// record_game adds a finished game to the statistics of the given players, the
+    /// other players of the game (e.g. the bots) only show up in the head-to-head records,
+    /// false if the log isn't of a finished game
+    pub fn record_game(&mut self, log: &GameLog, players: &[String]) -> bool {
+        let game = match GameSummary::from_log(log) {
+            Some(game) => game,
+            None => return false,
+        };
+        self.ratings.record_game(log);
+        let totals = game.score_board.totals();
+        let best = totals.iter().max().copied().unwrap_or_default();
+        let is_tie = totals.iter().filter(|t| **t == best).count() > 1;
+
+        for (seat, name) in game.players.iter().enumerate() {
+            if !players.contains(name) {
+                continue;
+            }
+            let team = game.get_team(seat);
+            let stats = self.players.entry(name.to_string()).or_default();
+            stats.games_played += 1;
+            stats.total_score += totals[team];
+            if totals[team] == best && !is_tie {
+                stats.wins += 1;
+                stats.current_streak += 1;
+                stats.longest_streak = stats.longest_streak.max(stats.current_streak);
+            } else {
+                stats.current_streak = 0;
+            }
+
+            let deals = game.deals.get(name).map(Vec::as_slice).unwrap_or_default();
+            for (call, tricks) in deals {
+                stats.deals_played += 1;
+                if call.is_made(*tricks) {
+                    stats.calls_made += 1;
+                    stats.overtricks += call.get_overtricks(*tricks) as u32;
+                }
+            }
+            let (cards, trumps) = game.throws.get(name).copied().unwrap_or_default();
+            stats.cards_thrown += cards;
+            stats.trumps_thrown += trumps;
+
+            // the partners play together, so only the opponents have a record
+            for (other_seat, other) in game.players.iter().enumerate() {
+                let other_team = game.get_team(other_seat);
+                if other_team == team {
+                    continue;
+                }
+                let record = stats.head_to_head.entry(other.to_string()).or_default();
+                match totals[team].cmp(&totals[other_team]) {
+                    std::cmp::Ordering::Greater => record.wins += 1,
+                    std::cmp::Ordering::Less => record.losses += 1,
+                    std::cmp::Ordering::Equal => record.ties += 1,
+                }
+            }
+        }
+        true
+    }
+}
+
+/// GameSummary is what the statistics need from the log of a finished game
+struct GameSummary {
+    players: Vec<String>,
+    teams: Vec<Vec<usize>>,
+    score_board: ScoreBoard,
+    // the call and the rounds won in every deal, by player
+    deals: HashMap<String, Vec<(Bid, u8)>>,
+    // the cards and the trumps thrown, by player
+    throws: HashMap<String, (u32, u32)>,
+}
+
+impl GameSummary {
+    /// from_log plays the calls and the throws of the log again, None if the game
+    /// in the log isn't over
+    fn from_log(log: &GameLog) -> Option<Self> {
+        let mut summary = GameSummary {
+            players: vec![],
+            teams: vec![],
+            score_board: ScoreBoard::default(),
+            deals: HashMap::new(),
+            throws: HashMap::new(),
+        };
+        let mut rules = RuleSet::default();
+        let mut calls: Vec<(String, Bid)> = vec![];
+        let mut round: Vec<(String, Card)> = vec![];
+        let mut tricks: HashMap<String, u8> = HashMap::new();
+
+        for entry in log.get_entries() {
+            match &entry.event {
+                LogEvent::Started {
+                    players,
+                    rules: game_rules,
+                } => {
+                    summary.players = players.clone();
+                    rules = game_rules.clone();
+                    summary.teams = rules.get_teams(players.len());
+                }
+                // the calls of a new deal start once the cards of the last one are thrown
+                LogEvent::Call { player, call } => {
+                    if !tricks.is_empty() || calls.len() == summary.players.len() {
+                        summary.finish_deal(&mut calls, &mut tricks);
+                    }
+                    calls.push((player.to_string(), *call));
+                }
+                LogEvent::Redeal { .. } => calls.clear(),
+                LogEvent::Throw { player, card } => {
+                    let throws = summary.throws.entry(player.to_string()).or_default();
+                    throws.0 += 1;
+                    if card.get_suit() == rules.trump_suit {
+                        throws.1 += 1;
+                    }
+                    round.push((player.to_string(), card.clone()));
+                    if round.len() == summary.players.len() {
+                        let winner = round
+                            .iter()
+                            .skip(1)
+                            .fold(&round[0], |winner, r| {
+                                if r.1.beats(&winner.1, rules.trump_suit) {
+                                    r
+                                } else {
+                                    winner
+                                }
+                            })
+                            .0
+                            .to_string();
+                        *tricks.entry(winner).or_default() += 1;
+                        round.clear();
+                    }
+                }
+                LogEvent::Finished { score_board, .. } => {
+                    summary.finish_deal(&mut calls, &mut tricks);
+                    summary.score_board = score_board.clone();
+                    return Some(summary);
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+
+    fn finish_deal(&mut self, calls: &mut Vec<(String, Bid)>, tricks: &mut HashMap<String, u8>) {
+        for (player, call) in calls.drain(..) {
+            let won = tricks.get(&player).copied().unwrap_or_default();
+            self.deals.entry(player).or_default().push((call, won));
+        }
+        tricks.clear();
+    }
+
+    /// get_team returns the team of the seat, the column of the scoreboard it scores in
+    fn get_team(&self, seat: usize) -> usize {
+        self.teams
+            .iter()
+            .position(|t| t.contains(&seat))
+            .unwrap_or(seat)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bot::{Bot, Difficulty};
+    use crate::game::{Game, GameState};
+
+    /// play_game plays a whole game between bots and returns its log
+    fn play_game(players: &[&str], rules: RuleSet) -> (Game, GameLog) {
+        let mut game = Game::new_game(rules).unwrap();
+        for p in players {
+            game.add_players(p, "1234").unwrap();
+        }
+        game.start().unwrap();
+        let mut log = GameLog::new();
+        log.record(LogEvent::Started {
+            players: players.iter().map(|p| p.to_string()).collect(),
+            rules: game.get_rules().clone(),
+        });
+        let bot = Bot::new(Difficulty::Normal);
+        while game.get_state() != GameState::Finished {
+            let player = game.get_current_player().unwrap().get_name().to_string();
+            let bot_move = bot.choose_move(&game).unwrap();
+            log.record(LogEvent::play_move(&mut game, &player, bot_move).unwrap());
+        }
+        log.record(LogEvent::Finished {
+            score_board: game.get_score_board().clone(),
+            winner: game.get_game_winner(),
+        });
+        (game, log)
+    }
+
+    #[test]
+    fn test_record_game() {
+        let rules = RuleSet {
+            total_deals: 2,
+            ..RuleSet::default()
+        };
+        let (game, log) = play_game(&["a", "b", "c"], rules);
+        let mut stats = Stats::default();
+        assert!(!stats.record_game(&GameLog::new(), &["a".to_string()]));
+        assert!(stats.record_game(&log, &["a".to_string(), "b".to_string()]));
+        assert!(stats.get_player("c").is_none());
+
+        let a = stats.get_player("a").unwrap();
+        assert_eq!(a.games_played, 1);
+        assert_eq!(a.total_score, game.get_score_board().totals()[0]);
+        assert_eq!(a.deals_played, 2);
+        assert_eq!(a.cards_thrown, 2 * 17);
+        // the calls made match the scoreboard
+        let made = game
+            .get_score_board()
+            .get_deals()
+            .iter()
+            .filter(|d| d.entries[0].call.is_made(d.entries[0].tricks))
+            .count();
+        assert_eq!(a.calls_made as usize, made);
+        assert_eq!(
+            a.wins,
+            u32::from(game.get_game_winner().as_deref() == Some("a"))
+        );
+        let record = a.head_to_head["c"];
+        assert_eq!(record.wins + record.losses + record.ties, 1);
+        assert!(a.get_call_accuracy() <= 1.0);
+        // the bots are rated along with the players
+        assert_eq!(stats.get_ratings().get_rating("c").games, 1);
+    }
+
+    #[test]
+    fn test_streaks_and_partners() {
+        let rules = RuleSet {
+            total_deals: 1,
+            partnership: true,
+            ..RuleSet::default()
+        };
+        let mut stats = Stats::default();
+        let players: Vec<String> = ["a", "b", "c", "d"].map(String::from).to_vec();
+        for _ in 0..3 {
+            let (_, log) = play_game(&["a", "b", "c", "d"], rules.clone());
+            stats.record_game(&log, &players);
+        }
+        let a = stats.get_player("a").unwrap();
+        let c = stats.get_player("c").unwrap();
+        // the partners share their results and have no record against each other
+        assert_eq!(a.wins, c.wins);
+        assert_eq!(a.total_score, c.total_score);
+        assert!(!a.head_to_head.contains_key("c"));
+        assert_eq!(a.head_to_head["b"].wins, a.wins);
+        assert!(a.longest_streak >= a.current_streak);
+        assert!(a.longest_streak <= a.wins);
+
+        let path =
+            std::env::temp_dir().join(format!("callbreak-stats-{}.json", std::process::id()));
+        stats.save_to(&path).unwrap();
+        let loaded = Stats::load_from(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, stats);
+    }
+}
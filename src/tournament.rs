@@ -0,0 +1,991 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::bot::{Bot, Difficulty};
+use crate::game::{Game, GameState, MAX_PLAYERS, MIN_PLAYERS};
+use crate::log::{GameLog, LogEvent};
+use crate::rules::{RulePreset, RuleSet};
+use crate::score::Score;
+
+pub const DEFAULT_TABLE_SIZE: usize = 4;
+// the table assignments tried for every round, the one with the fewest repeat meetings is kept
+const SCHEDULE_ATTEMPTS: usize = 200;
+
+const USAGE: &str = "usage: callbreak tournament [new <file> --players a,b,c [--bots N] [--bot-level easy|normal|hard] [--table-size N] [--deals N] [--rules standard|nepali|indian] | round <file> | record <file> <round> <table> <game.json|score,score,..> | standings <file> [--csv|--markdown]]";
+
+#[derive(Debug, PartialEq)]
+pub enum TournamentError {
+    TooFewPlayers,
+    DuplicatePlayer(String),
+    InvalidTableSize,
+    // the tables are scored one player each
+    Partnership,
+    RoundNotOver,
+    UnknownTable,
+    AlreadyRecorded,
+    // the scores or the game log don't match the players of the table
+    PlayersMismatch,
+    GameNotFinished,
+}
+
+impl fmt::Display for TournamentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TournamentError::TooFewPlayers => {
+                write!(f, "A tournament needs at least {} players", MIN_PLAYERS)
+            }
+            TournamentError::DuplicatePlayer(name) => {
+                write!(f, "{} is registered more than once", name)
+            }
+            TournamentError::InvalidTableSize => write!(
+                f,
+                "The tables seat between {} and {} players",
+                MIN_PLAYERS, MAX_PLAYERS
+            ),
+            TournamentError::Partnership => {
+                write!(f, "The tournament games are played without partners")
+            }
+            TournamentError::RoundNotOver => {
+                write!(f, "Every table of the round has to be recorded first")
+            }
+            TournamentError::UnknownTable => write!(f, "There is no such table"),
+            TournamentError::AlreadyRecorded => write!(f, "The table is already recorded"),
+            TournamentError::PlayersMismatch => {
+                write!(f, "The result doesn't match the players of the table")
+            }
+            TournamentError::GameNotFinished => write!(f, "The game in the log isn't over"),
+        }
+    }
+}
+
+/// Entrant is a registered player, the bots play their tables on their own
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Entrant {
+    pub name: String,
+    #[serde(default)]
+    pub bot: Option<Difficulty>,
+}
+
+/// Table is a match of a round, with the final scores once it's played or recorded
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Table {
+    pub players: Vec<String>,
+    // in the same order as the players
+    #[serde(default)]
+    pub scores: Option<Vec<Score>>,
+}
+
+/// Standing is the place of a player in the tournament
+#[derive(Clone, Debug, PartialEq)]
+pub struct Standing {
+    pub name: String,
+    // a point for every table, split by the share of the other players finished above
+    pub points: f32,
+    // the first tiebreaker, the points of the opponents met so far
+    pub opponent_points: f32,
+    // the second tiebreaker, the final scores of all the tables added up
+    pub total_score: Score,
+    pub games: u32,
+    pub wins: u32,
+}
+
+/// Tournament is a series of rounds, every round seats all the players at tables of
+/// about the same size, it's saved as JSON between the rounds
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Tournament {
+    entrants: Vec<Entrant>,
+    table_size: usize,
+    rules: RuleSet,
+    rounds: Vec<Vec<Table>>,
+}
+
+impl Tournament {
+    pub fn new(
+        entrants: Vec<Entrant>,
+        table_size: usize,
+        rules: RuleSet,
+    ) -> Result<Self, TournamentError> {
+        if entrants.len() < MIN_PLAYERS {
+            return Err(TournamentError::TooFewPlayers);
+        }
+        if !(MIN_PLAYERS..=MAX_PLAYERS).contains(&table_size) {
+            return Err(TournamentError::InvalidTableSize);
+        }
+        if rules.partnership {
+            return Err(TournamentError::Partnership);
+        }
+        for (i, entrant) in entrants.iter().enumerate() {
+            if entrants[..i].iter().any(|e| e.name == entrant.name) {
+                return Err(TournamentError::DuplicatePlayer(entrant.name.clone()));
+            }
+        }
+        Ok(Tournament {
+            entrants,
+            table_size,
+            rules,
+            rounds: vec![],
+        })
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let content = fs::read_to_string(path)?;
+        serde_json::from_str(&content).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let content = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(path, content)
+    }
+
+    pub fn get_entrants(&self) -> &[Entrant] {
+        &self.entrants
+    }
+
+    pub fn get_rounds(&self) -> &[Vec<Table>] {
+        &self.rounds
+    }
+
+    /// is_round_over checks if every table of the last round has its result
+    pub fn is_round_over(&self) -> bool {
+        self.rounds
+            .last()
+            .is_none_or(|tables| tables.iter().all(|t| t.scores.is_some()))
+    }
+
+    /// schedule_round seats the players of a new round, trying a number of seatings and
+    /// keeping the one where the players meet the fewest opponents again and the bots are
+    /// spread evenly over the tables
+    pub fn schedule_round(&mut self, seed: u64) -> Result<&[Table], TournamentError> {
+        if !self.is_round_over() {
+            return Err(TournamentError::RoundNotOver);
+        }
+        let meetings = self.get_meetings();
+        let sizes = get_table_sizes(self.entrants.len(), self.table_size);
+        let total_bots = self.entrants.iter().filter(|e| e.bot.is_some()).count();
+        let mut rng = ChaCha20Rng::seed_from_u64(seed);
+        let mut order: Vec<usize> = (0..self.entrants.len()).collect();
+        let mut best: Option<(usize, Vec<usize>)> = None;
+
+        for _ in 0..SCHEDULE_ATTEMPTS {
+            for i in (1..order.len()).rev() {
+                order.swap(i, rng.gen_range(0..=i));
+            }
+            let mut cost = 0;
+            let mut seats = order.as_slice();
+            for size in &sizes {
+                let (table, rest) = seats.split_at(*size);
+                seats = rest;
+                for (i, a) in table.iter().enumerate() {
+                    for b in &table[i + 1..] {
+                        let met = meetings.get(&pair(*a, *b)).copied().unwrap_or_default();
+                        cost += met * met * self.entrants.len();
+                    }
+                }
+                let bots = table
+                    .iter()
+                    .filter(|p| self.entrants[**p].bot.is_some())
+                    .count();
+                cost += (bots * self.entrants.len()).abs_diff(total_bots * size);
+            }
+            if best.as_ref().is_none_or(|(best_cost, _)| cost < *best_cost) {
+                best = Some((cost, order.clone()));
+            }
+        }
+
+        let order = best.map(|(_, order)| order).unwrap_or(order);
+        let mut seats = order.as_slice();
+        let mut tables = vec![];
+        for size in sizes {
+            let (table, rest) = seats.split_at(size);
+            seats = rest;
+            tables.push(Table {
+                players: table
+                    .iter()
+                    .map(|p| self.entrants[*p].name.clone())
+                    .collect(),
+                scores: None,
+            });
+        }
+        self.rounds.push(tables);
+        Ok(self.rounds.last().map(Vec::as_slice).unwrap_or_default())
+    }
+
+    /// get_meetings counts how often every pair of players has shared a table
+    fn get_meetings(&self) -> HashMap<(usize, usize), usize> {
+        let index: HashMap<&str, usize> = self
+            .entrants
+            .iter()
+            .enumerate()
+            .map(|(i, e)| (e.name.as_str(), i))
+            .collect();
+        let mut meetings = HashMap::new();
+        for table in self.rounds.iter().flatten() {
+            let seats: Vec<usize> = table.players.iter().map(|p| index[p.as_str()]).collect();
+            for (i, a) in seats.iter().enumerate() {
+                for b in &seats[i + 1..] {
+                    *meetings.entry(pair(*a, *b)).or_default() += 1;
+                }
+            }
+        }
+        meetings
+    }
+
+    /// record_scores sets the final scores of a table, in the order of its players,
+    /// the rounds and the tables are counted from 1
+    pub fn record_scores(
+        &mut self,
+        round: usize,
+        table: usize,
+        scores: Vec<Score>,
+    ) -> Result<(), TournamentError> {
+        let table = self
+            .rounds
+            .get_mut(round.wrapping_sub(1))
+            .and_then(|tables| tables.get_mut(table.wrapping_sub(1)))
+            .ok_or(TournamentError::UnknownTable)?;
+        if table.scores.is_some() {
+            return Err(TournamentError::AlreadyRecorded);
+        }
+        if scores.len() != table.players.len() {
+            return Err(TournamentError::PlayersMismatch);
+        }
+        table.scores = Some(scores);
+        Ok(())
+    }
+
+    /// record_log takes the final scores of a table from the log of its game, e.g. a game
+    /// hosted with `callbreak serve --log`, the players may sit in any order
+    pub fn record_log(
+        &mut self,
+        round: usize,
+        table: usize,
+        log: &GameLog,
+    ) -> Result<(), TournamentError> {
+        let mut players = vec![];
+        let mut totals = None;
+        for entry in log.get_entries() {
+            match &entry.event {
+                LogEvent::Started {
+                    players: started, ..
+                } => players = started.clone(),
+                LogEvent::Finished { score_board, .. } => totals = Some(score_board.totals()),
+                _ => {}
+            }
+        }
+        let totals = totals.ok_or(TournamentError::GameNotFinished)?;
+        let table_players = self
+            .rounds
+            .get(round.wrapping_sub(1))
+            .and_then(|tables| tables.get(table.wrapping_sub(1)))
+            .map(|t| t.players.clone())
+            .ok_or(TournamentError::UnknownTable)?;
+        if players.len() != table_players.len() || totals.len() != players.len() {
+            return Err(TournamentError::PlayersMismatch);
+        }
+        let scores = table_players
+            .iter()
+            .map(|name| {
+                let seat = players.iter().position(|p| p == name)?;
+                Some(totals[seat])
+            })
+            .collect::<Option<Vec<Score>>>()
+            .ok_or(TournamentError::PlayersMismatch)?;
+        self.record_scores(round, table, scores)
+    }
+
+    /// play_bot_tables plays the tables of the last round where only bots sit, and
+    /// returns how many were played
+    pub fn play_bot_tables(&mut self) -> usize {
+        let bots: HashMap<String, Difficulty> = self
+            .entrants
+            .iter()
+            .filter_map(|e| e.bot.map(|d| (e.name.clone(), d)))
+            .collect();
+        let rules = self.rules.clone();
+        let mut played = 0;
+        for table in self.rounds.last_mut().into_iter().flatten() {
+            if table.scores.is_some() || !table.players.iter().all(|p| bots.contains_key(p)) {
+                continue;
+            }
+            if let Some(scores) = play_bot_game(&table.players, &bots, &rules) {
+                table.scores = Some(scores);
+                played += 1;
+            }
+        }
+        played
+    }
+
+    /// get_standings ranks the players by their points, then by the points of their
+    /// opponents, then by their total score
+    pub fn get_standings(&self) -> Vec<Standing> {
+        let mut standings: Vec<Standing> = self
+            .entrants
+            .iter()
+            .map(|e| Standing {
+                name: e.name.clone(),
+                points: 0.0,
+                opponent_points: 0.0,
+                total_score: Score::default(),
+                games: 0,
+                wins: 0,
+            })
+            .collect();
+        let index: HashMap<String, usize> = self
+            .entrants
+            .iter()
+            .enumerate()
+            .map(|(i, e)| (e.name.clone(), i))
+            .collect();
+        let played: Vec<(&Table, &Vec<Score>)> = self
+            .rounds
+            .iter()
+            .flatten()
+            .filter_map(|t| t.scores.as_ref().map(|s| (t, s)))
+            .collect();
+
+        for (table, scores) in &played {
+            let others = (table.players.len() - 1).max(1) as f32;
+            let best = scores.iter().max();
+            let is_tie = scores.iter().filter(|s| Some(*s) == best).count() > 1;
+            for (seat, name) in table.players.iter().enumerate() {
+                let beaten: f32 = scores
+                    .iter()
+                    .map(|s| match scores[seat].cmp(s) {
+                        Ordering::Greater => 1.0,
+                        Ordering::Equal => 0.5,
+                        Ordering::Less => 0.0,
+                    })
+                    .sum::<f32>()
+                    // the player ties with their own score
+                    - 0.5;
+                let standing = &mut standings[index[name]];
+                standing.points += beaten / others;
+                standing.total_score += scores[seat];
+                standing.games += 1;
+                if Some(&scores[seat]) == best && !is_tie {
+                    standing.wins += 1;
+                }
+            }
+        }
+
+        let points: Vec<f32> = standings.iter().map(|s| s.points).collect();
+        for (table, _) in &played {
+            for name in &table.players {
+                standings[index[name]].opponent_points += table
+                    .players
+                    .iter()
+                    .filter(|p| *p != name)
+                    .map(|p| points[index[p]])
+                    .sum::<f32>();
+            }
+        }
+
+        standings.sort_by(|a, b| {
+            b.points
+                .total_cmp(&a.points)
+                .then(b.opponent_points.total_cmp(&a.opponent_points))
+                .then(b.total_score.cmp(&a.total_score))
+                .then(a.name.cmp(&b.name))
+        });
+        standings
+    }
+
+    /// to_csv returns the standings as CSV, with a header row
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("place,name,points,opponent_points,total_score,games,wins\n");
+        for (place, s) in self.get_standings().iter().enumerate() {
+            csv.push_str(&format!(
+                "{},{},{:.2},{:.2},{},{},{}\n",
+                place + 1,
+                escape_csv(&s.name),
+                s.points,
+                s.opponent_points,
+                s.total_score,
+                s.games,
+                s.wins
+            ));
+        }
+        csv
+    }
+
+    /// to_markdown returns the standings as a Markdown table
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = String::from(
+            "| # | Player | Points | Opponents | Score | Games | Wins |\n|---|---|---|---|---|---|---|\n",
+        );
+        for (place, s) in self.get_standings().iter().enumerate() {
+            markdown.push_str(&format!(
+                "| {} | {} | {:.2} | {:.2} | {} | {} | {} |\n",
+                place + 1,
+                s.name.replace('|', "\\|"),
+                s.points,
+                s.opponent_points,
+                s.total_score,
+                s.games,
+                s.wins
+            ));
+        }
+        markdown
+    }
+}
+
+/// get_table_sizes splits the players into as few tables as the table size allows,
+/// the bigger tables first and none more than a seat bigger than another
+fn get_table_sizes(total_players: usize, table_size: usize) -> Vec<usize> {
+    let tables = total_players.div_ceil(table_size);
+    (0..tables)
+        .map(|i| total_players / tables + usize::from(i < total_players % tables))
+        .collect()
+}
+
+fn pair(a: usize, b: usize) -> (usize, usize) {
+    (a.min(b), a.max(b))
+}
+
+fn escape_csv(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        return format!("\"{}\"", value.replace('"', "\"\""));
+    }
+    value.to_string()
+}
+
+/// play_bot_game plays a whole game between the bots and returns their final scores
+fn play_bot_game(
+    players: &[String],
+    bots: &HashMap<String, Difficulty>,
+    rules: &RuleSet,
+) -> Option<Vec<Score>> {
+    let mut game = Game::new_game(rules.clone()).ok()?;
+    for player in players {
+        game.add_players(player, "").ok()?;
+    }
+    play_bots(&mut game, bots)?;
+    Some(game.get_score_board().totals())
+}
+
+/// play_bots starts the game and lets the bots play every seat till the end
+pub(crate) fn play_bots(game: &mut Game, bots: &HashMap<String, Difficulty>) -> Option<()> {
+    game.start().ok()?;
+    while game.get_state() != GameState::Finished {
+        let player = game.get_current_player()?.get_name().to_string();
+        let bot = Bot::new(*bots.get(&player)?);
+        game.play_move(&player, bot.choose_move(game)?).ok()?;
+    }
+    Some(())
+}
+
+/// TournamentCommand is a `callbreak tournament` command, the tournament is kept in a
+/// JSON file between the commands
+#[derive(Debug, PartialEq)]
+pub enum TournamentCommand {
+    // registers the players, e.g. `new office.json --players ann,bob,cat --bots 1`
+    New {
+        path: PathBuf,
+        tournament: Tournament,
+    },
+    // seats the next round and plays the tables of the bots
+    Round {
+        path: PathBuf,
+    },
+    // the result of a table, from a game log or the final scores in the order of the table
+    Record {
+        path: PathBuf,
+        round: usize,
+        table: usize,
+        result: TableResult,
+    },
+    Standings {
+        path: PathBuf,
+        format: StandingsFormat,
+    },
+}
+
+#[derive(Debug, PartialEq)]
+pub enum TableResult {
+    Log(PathBuf),
+    Scores(Vec<Score>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StandingsFormat {
+    Text,
+    Csv,
+    Markdown,
+}
+
+impl TournamentCommand {
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        let (command, path) = match args {
+            [command, path, ..] => (command.as_str(), PathBuf::from(path)),
+            _ => return Err(USAGE.to_string()),
+        };
+        let rest = &args[2..];
+        match command {
+            "new" => Self::parse_new(path, rest),
+            "round" if rest.is_empty() => Ok(TournamentCommand::Round { path }),
+            "record" => match rest {
+                [round, table, result] => {
+                    let invalid = |value: &String| format!("Invalid number {}", value);
+                    let result = if result.ends_with(".json") {
+                        TableResult::Log(PathBuf::from(result))
+                    } else {
+                        TableResult::Scores(
+                            result
+                                .split(',')
+                                .map(|s| s.parse())
+                                .collect::<Result<_, _>>()?,
+                        )
+                    };
+                    Ok(TournamentCommand::Record {
+                        path,
+                        round: round.parse().map_err(|_| invalid(round))?,
+                        table: table.parse().map_err(|_| invalid(table))?,
+                        result,
+                    })
+                }
+                _ => Err(USAGE.to_string()),
+            },
+            "standings" => {
+                let format = match rest.first().map(|a| a.as_str()) {
+                    None => StandingsFormat::Text,
+                    Some("--csv") => StandingsFormat::Csv,
+                    Some("--markdown") => StandingsFormat::Markdown,
+                    Some(arg) => return Err(format!("Unknown option {}, {}", arg, USAGE)),
+                };
+                Ok(TournamentCommand::Standings { path, format })
+            }
+            _ => Err(USAGE.to_string()),
+        }
+    }
+
+    fn parse_new(path: PathBuf, args: &[String]) -> Result<Self, String> {
+        let mut setup = SetupArgs::default();
+        let mut table_size = DEFAULT_TABLE_SIZE;
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let value = args
+                .next()
+                .ok_or_else(|| format!("Missing value for {}", arg))?;
+            match arg.as_str() {
+                "--table-size" => {
+                    table_size = value
+                        .parse()
+                        .map_err(|_| format!("Invalid value for {}: {}", arg, value))?
+                }
+                _ => setup.parse(arg, value)?,
+            }
+        }
+        let tournament = Tournament::new(setup.get_entrants(), table_size, setup.get_rules())
+            .map_err(|e| e.to_string())?;
+        Ok(TournamentCommand::New { path, tournament })
+    }
+
+    /// run carries out the command and returns what to print
+    pub fn run(self) -> io::Result<String> {
+        let to_io = |e: TournamentError| io::Error::new(io::ErrorKind::InvalidInput, e.to_string());
+        match self {
+            TournamentCommand::New { path, tournament } => {
+                tournament.save(&path)?;
+                Ok(format!(
+                    "Registered {} players in {}\n",
+                    tournament.get_entrants().len(),
+                    path.display()
+                ))
+            }
+            TournamentCommand::Round { path } => {
+                let mut tournament = Tournament::load(&path)?;
+                tournament
+                    .schedule_round(rand::thread_rng().gen())
+                    .map_err(to_io)?;
+                tournament.play_bot_tables();
+                tournament.save(&path)?;
+                Ok(get_round_text(&tournament))
+            }
+            TournamentCommand::Record {
+                path,
+                round,
+                table,
+                result,
+            } => {
+                let mut tournament = Tournament::load(&path)?;
+                match result {
+                    TableResult::Log(log_path) => {
+                        let log = GameLog::load(&log_path)?;
+                        tournament.record_log(round, table, &log)
+                    }
+                    TableResult::Scores(scores) => tournament.record_scores(round, table, scores),
+                }
+                .map_err(to_io)?;
+                tournament.save(&path)?;
+                Ok(format!("Recorded table {} of round {}\n", table, round))
+            }
+            TournamentCommand::Standings { path, format } => {
+                let tournament = Tournament::load(&path)?;
+                Ok(match format {
+                    StandingsFormat::Csv => tournament.to_csv(),
+                    StandingsFormat::Markdown => tournament.to_markdown(),
+                    StandingsFormat::Text => get_standings_text(&tournament),
+                })
+            }
+        }
+    }
+}
+
+/// SetupArgs are the players and the rules given to a new tournament or duplicate match,
+/// e.g. `--players ann,bob --bots 2 --bot-level hard --deals 3 --rules nepali`
+#[derive(Default)]
+pub(crate) struct SetupArgs {
+    names: Vec<String>,
+    total_bots: usize,
+    bot_level: Difficulty,
+    preset: RulePreset,
+    total_deals: Option<u8>,
+}
+
+impl SetupArgs {
+    /// parse reads one of the options, an error for an option it doesn't know
+    pub(crate) fn parse(&mut self, arg: &str, value: &str) -> Result<(), String> {
+        let invalid = || format!("Invalid value for {}: {}", arg, value);
+        match arg {
+            "--players" => {
+                self.names = value
+                    .split(',')
+                    .map(|n| n.trim().to_string())
+                    .filter(|n| !n.is_empty())
+                    .collect()
+            }
+            "--bots" => self.total_bots = value.parse().map_err(|_| invalid())?,
+            "--bot-level" => {
+                self.bot_level = *Difficulty::ALL
+                    .iter()
+                    .find(|d| d.get_name().eq_ignore_ascii_case(value))
+                    .ok_or_else(invalid)?
+            }
+            "--deals" => self.total_deals = Some(value.parse().map_err(|_| invalid())?),
+            "--rules" => {
+                self.preset = *RulePreset::ALL
+                    .iter()
+                    .find(|p| p.get_name().eq_ignore_ascii_case(value))
+                    .ok_or_else(invalid)?
+            }
+            _ => return Err(format!("Unknown option {}", arg)),
+        }
+        Ok(())
+    }
+
+    /// get_entrants returns the named players followed by the bots
+    pub(crate) fn get_entrants(&self) -> Vec<Entrant> {
+        let bots = (1..=self.total_bots).map(|i| Entrant {
+            name: format!("Bot {}", i),
+            bot: Some(self.bot_level),
+        });
+        self.names
+            .iter()
+            .map(|name| Entrant {
+                name: name.clone(),
+                bot: None,
+            })
+            .chain(bots)
+            .collect()
+    }
+
+    pub(crate) fn get_rules(&self) -> RuleSet {
+        let mut rules = self.preset.get_rules();
+        if let Some(total_deals) = self.total_deals {
+            rules.total_deals = total_deals;
+        }
+        rules
+    }
+}
+
+/// get_round_text lists the tables of the last round with their results so far
+fn get_round_text(tournament: &Tournament) -> String {
+    let rounds = tournament.get_rounds();
+    let mut text = format!("Round {}\n", rounds.len());
+    for (i, table) in rounds.last().into_iter().flatten().enumerate() {
+        let players = match &table.scores {
+            Some(scores) => table
+                .players
+                .iter()
+                .zip(scores)
+                .map(|(p, s)| format!("{} {}", p, s))
+                .collect::<Vec<String>>(),
+            None => table.players.clone(),
+        };
+        let status = if table.scores.is_some() {
+            "played"
+        } else {
+            "to record"
+        };
+        text.push_str(&format!(
+            "  Table {}: {} ({})\n",
+            i + 1,
+            players.join(", "),
+            status
+        ));
+    }
+    text
+}
+
+fn get_standings_text(tournament: &Tournament) -> String {
+    let mut text = String::new();
+    for (place, s) in tournament.get_standings().iter().enumerate() {
+        text.push_str(&format!(
+            "{:>3}. {:<20} {:>5.2}  ({:.2} opp, {} pts, {} of {} won)\n",
+            place + 1,
+            s.name,
+            s.points,
+            s.opponent_points,
+            s.total_score,
+            s.wins,
+            s.games
+        ));
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entrants(names: &[&str], bots: usize) -> Vec<Entrant> {
+        names
+            .iter()
+            .map(|n| Entrant {
+                name: n.to_string(),
+                bot: None,
+            })
+            .chain((1..=bots).map(|i| Entrant {
+                name: format!("Bot {}", i),
+                bot: Some(Difficulty::Easy),
+            }))
+            .collect()
+    }
+
+    fn score(s: &str) -> Score {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_new() {
+        let rules = RuleSet::default();
+        assert_eq!(
+            Tournament::new(entrants(&["a"], 0), 4, rules.clone()),
+            Err(TournamentError::TooFewPlayers)
+        );
+        assert_eq!(
+            Tournament::new(entrants(&["a", "a"], 0), 4, rules.clone()),
+            Err(TournamentError::DuplicatePlayer(String::from("a")))
+        );
+        assert_eq!(
+            Tournament::new(entrants(&["a", "b"], 0), 6, rules.clone()),
+            Err(TournamentError::InvalidTableSize)
+        );
+        let partnership = RuleSet {
+            partnership: true,
+            ..rules
+        };
+        assert_eq!(
+            Tournament::new(entrants(&["a", "b"], 0), 4, partnership),
+            Err(TournamentError::Partnership)
+        );
+    }
+
+    #[test]
+    fn test_table_sizes() {
+        assert_eq!(get_table_sizes(8, 4), vec![4, 4]);
+        assert_eq!(get_table_sizes(9, 4), vec![3, 3, 3]);
+        assert_eq!(get_table_sizes(10, 4), vec![4, 3, 3]);
+        assert_eq!(get_table_sizes(5, 4), vec![3, 2]);
+        assert_eq!(get_table_sizes(3, 5), vec![3]);
+    }
+
+    #[test]
+    fn test_schedule_round() {
+        let mut tournament = Tournament::new(
+            entrants(&["a", "b", "c", "d", "e", "f"], 2),
+            4,
+            RuleSet::default(),
+        )
+        .unwrap();
+        let tables = tournament.schedule_round(1).unwrap().to_vec();
+        assert_eq!(tables.len(), 2);
+        // every player has a seat and the bots sit at different tables
+        let mut seated: Vec<&String> = tables.iter().flat_map(|t| &t.players).collect();
+        seated.sort();
+        seated.dedup();
+        assert_eq!(seated.len(), 8);
+        assert!(tables
+            .iter()
+            .all(|t| t.players.iter().filter(|p| p.starts_with("Bot")).count() == 1));
+
+        assert_eq!(
+            tournament.schedule_round(2).err(),
+            Some(TournamentError::RoundNotOver)
+        );
+        for table in 1..=2 {
+            let scores = vec![score("1.0"), score("2.0"), score("3.0"), score("4.0")];
+            tournament.record_scores(1, table, scores).unwrap();
+        }
+        // the next round mixes the tables of the first one
+        let second = tournament.schedule_round(2).unwrap().to_vec();
+        for table in &second {
+            for first in &tables {
+                let shared = table
+                    .players
+                    .iter()
+                    .filter(|p| first.players.contains(p))
+                    .count();
+                assert_eq!(shared, 2);
+            }
+        }
+    }
+
+    #[test]
+    fn test_record_and_standings() {
+        let mut tournament =
+            Tournament::new(entrants(&["a", "b", "c"], 0), 3, RuleSet::default()).unwrap();
+        tournament.schedule_round(1).unwrap();
+        let players = tournament.get_rounds()[0][0].players.clone();
+        let seat = |name: &str| players.iter().position(|p| p == name).unwrap();
+        let mut scores = vec![Score::default(); 3];
+        scores[seat("a")] = score("12.3");
+        scores[seat("b")] = score("8.0");
+        scores[seat("c")] = score("8.0");
+
+        assert_eq!(
+            tournament.record_scores(1, 2, scores.clone()),
+            Err(TournamentError::UnknownTable)
+        );
+        assert_eq!(
+            tournament.record_scores(1, 1, scores[..2].to_vec()),
+            Err(TournamentError::PlayersMismatch)
+        );
+        tournament.record_scores(1, 1, scores.clone()).unwrap();
+        assert_eq!(
+            tournament.record_scores(1, 1, scores),
+            Err(TournamentError::AlreadyRecorded)
+        );
+
+        let standings = tournament.get_standings();
+        assert_eq!(standings[0].name, "a");
+        assert_eq!(standings[0].points, 1.0);
+        assert_eq!(standings[0].wins, 1);
+        assert_eq!(standings[1].points, 0.25);
+        // the tie on points is broken by the name
+        assert_eq!(standings[1].name, "b");
+        assert_eq!(standings[1].opponent_points, 1.25);
+        assert_eq!(standings[1].total_score, score("8.0"));
+
+        let csv = tournament.to_csv();
+        assert_eq!(csv.lines().count(), 4);
+        assert_eq!(csv.lines().nth(1), Some("1,a,1.00,0.50,12.3,1,1"));
+        let markdown = tournament.to_markdown();
+        assert!(markdown.contains("| 1 | a | 1.00 | 0.50 | 12.3 | 1 | 1 |"));
+        assert_eq!(escape_csv("a, \"b\""), "\"a, \"\"b\"\"\"");
+    }
+
+    #[test]
+    fn test_bot_tables_and_logs() {
+        let rules = RuleSet {
+            total_deals: 1,
+            ..RuleSet::default()
+        };
+        let mut tournament = Tournament::new(entrants(&["a", "b"], 2), 2, rules).unwrap();
+        // a table of two players, the bots only play each other if seated together
+        let tables = tournament.schedule_round(3).unwrap().to_vec();
+        let bot_tables = tables
+            .iter()
+            .filter(|t| t.players.iter().all(|p| p.starts_with("Bot")))
+            .count();
+        assert_eq!(tournament.play_bot_tables(), bot_tables);
+
+        let (number, human_table) = tables
+            .iter()
+            .enumerate()
+            .find(|(_, t)| t.players.iter().any(|p| !p.starts_with("Bot")))
+            .unwrap();
+        let mut log = GameLog::new();
+        log.record(LogEvent::Started {
+            players: human_table.players.iter().rev().cloned().collect(),
+            rules: RuleSet::default(),
+        });
+        assert_eq!(
+            tournament.record_log(1, number + 1, &log),
+            Err(TournamentError::GameNotFinished)
+        );
+        let mut game = Game::new_game(RuleSet {
+            total_deals: 1,
+            ..RuleSet::default()
+        })
+        .unwrap();
+        for player in human_table.players.iter().rev() {
+            game.add_players(player, "").unwrap();
+        }
+        game.start().unwrap();
+        let bot = Bot::new(Difficulty::Easy);
+        while game.get_state() != GameState::Finished {
+            let player = game.get_current_player().unwrap().get_name().to_string();
+            game.play_move(&player, bot.choose_move(&game).unwrap())
+                .unwrap();
+        }
+        let totals = game.get_score_board().totals();
+        log.record(LogEvent::Finished {
+            score_board: game.get_score_board().clone(),
+            winner: None,
+        });
+        tournament.record_log(1, number + 1, &log).unwrap();
+        // the scores follow the seats of the table, not of the game
+        assert_eq!(
+            tournament.get_rounds()[0][number].scores,
+            Some(vec![totals[1], totals[0]])
+        );
+    }
+
+    #[test]
+    fn test_command_from_args() {
+        let args = |line: &str| -> Vec<String> { line.split(' ').map(String::from).collect() };
+        match TournamentCommand::from_args(&args(
+            "new t.json --players ann,bob,cat --bots 3 --bot-level hard --table-size 3 --deals 2",
+        )) {
+            Ok(TournamentCommand::New { path, tournament }) => {
+                assert_eq!(path, PathBuf::from("t.json"));
+                assert_eq!(tournament.get_entrants().len(), 6);
+                assert_eq!(tournament.get_entrants()[5].bot, Some(Difficulty::Hard));
+                assert_eq!(tournament.table_size, 3);
+                assert_eq!(tournament.rules.total_deals, 2);
+            }
+            result => panic!("unexpected {:?}", result),
+        }
+        assert_eq!(
+            TournamentCommand::from_args(&args("record t.json 1 2 3.1,-4.0")),
+            Ok(TournamentCommand::Record {
+                path: PathBuf::from("t.json"),
+                round: 1,
+                table: 2,
+                result: TableResult::Scores(vec![score("3.1"), score("-4.0")]),
+            })
+        );
+        assert_eq!(
+            TournamentCommand::from_args(&args("standings t.json --csv")),
+            Ok(TournamentCommand::Standings {
+                path: PathBuf::from("t.json"),
+                format: StandingsFormat::Csv,
+            })
+        );
+        assert!(TournamentCommand::from_args(&args("new t.json --players ann")).is_err());
+        assert!(TournamentCommand::from_args(&args("record t.json 1 x 3.1")).is_err());
+        assert!(TournamentCommand::from_args(&args("round")).is_err());
+    }
+}
@@ -0,0 +1,204 @@
+use serde::{Deserialize, Serialize};
+
+use crate::bid::Bid;
+use crate::card::Card;
+use crate::game::{Game, GameState, Round};
+use crate::rules::RuleSet;
+use crate::score::{NilBid, ScoreBoard};
+
+/// PlayerView is the public information of a player, the cards stay hidden
+/// unless the view is for spectators who may see all the hands
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PlayerView {
+    pub name: String,
+    // None until the player has called in the current deal
+    pub call: Option<Bid>,
+    pub tricks: u8,
+    pub cards_left: usize,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hand: Vec<Card>,
+    // the team of the player, the column of the scoreboard it scores in
+    #[serde(default)]
+    pub team: usize,
+}
+
+/// GameView is the game as seen from a single seat: the public information of the
+/// game along with the hand of that seat only
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GameView {
+    pub state: GameState,
+    pub players: Vec<PlayerView>,
+    pub current_player: Option<String>,
+    // (current, total)
+    pub deal: (u8, u8),
+    pub round: (u8, u8),
+    pub call_range: (u8, u8),
+    // the scoring of the nil call, None if it can't be called at this table
+    #[serde(default)]
+    pub nil_bid: Option<NilBid>,
+    // the calls made so far in the deal added up
+    #[serde(default)]
+    pub call_total: u8,
+    // the calls the current player can't make, as the last caller of the deal
+    #[serde(default)]
+    pub forbidden_bids: Vec<Bid>,
+    #[serde(default)]
+    pub rules: RuleSet,
+    pub table: Vec<Round>,
+    pub last_round: Vec<Round>,
+    pub last_round_winner: Option<String>,
+    pub score_board: ScoreBoard,
+    pub winner: Option<String>,
+    // the seat this view is made for, None for a view without any hand
+    pub seat: Option<String>,
+    pub hand: Vec<Card>,
+    pub eligible_cards: Vec<usize>,
+    // the seat may ask for a redeal of its weak hand instead of calling
+    #[serde(default)]
+    pub can_redeal: bool,
+    // milliseconds the current player has left for the turn, None without a time limit
+    #[serde(default)]
+    pub time_left_ms: Option<u64>,
+}
+
+impl GameView {
+    /// new creates the view of the game for the given player, the hand is left
+    /// empty if the player is not part of the game
+    pub fn new(game: &Game, player_name: Option<&str>) -> Self {
+        let teams = game.get_teams();
+        let players = game
+            .get_players()
+            .iter()
+            .enumerate()
+            .map(|(seat, p)| PlayerView {
+                name: p.get_name().to_string(),
+                call: if game.has_called(p.get_name()) {
+                    Some(p.get_call())
+                } else {
+                    None
+                },
+                tricks: p.get_points(),
+                cards_left: p.get_cards().len(),
+                hand: vec![],
+                team: teams.iter().position(|t| t.contains(&seat)).unwrap_or(seat),
+            })
+            .collect();
+
+        let seat =
+            player_name.and_then(|name| game.get_players().iter().find(|p| p.get_name() == name));
+        let (hand, eligible_cards, can_redeal) = match seat {
+            Some(p) => (
+                p.get_cards().to_vec(),
+                game.get_eligible_cards(p.get_name()).unwrap_or_default(),
+                game.can_redeal(p.get_name()),
+            ),
+            None => (vec![], vec![], false),
+        };
+
+        GameView {
+            state: game.get_state(),
+            players,
+            current_player: game.get_current_player().map(|p| p.get_name().to_string()),
+            deal: game.get_deal_info(),
+            round: game.get_round_info(),
+            call_range: game.get_call_range(),
+            nil_bid: game.get_nil_bid(),
+            call_total: game.get_call_total(),
+            forbidden_bids: game.get_forbidden_bids(),
+            rules: game.get_rules().clone(),
+            table: game.get_current_round().to_vec(),
+            last_round: game.get_last_round().to_vec(),
+            last_round_winner: game.get_last_round_winner().map(String::from),
+            score_board: game.get_score_board().clone(),
+            winner: game.get_game_winner(),
+            seat: seat.map(|p| p.get_name().to_string()),
+            hand,
+            eligible_cards,
+            can_redeal,
+            time_left_ms: game.get_time_left().map(|t| t.as_millis() as u64),
+        }
+    }
+
+    /// new_spectator creates the view of the game for someone watching it, with the
+    /// hands of all the players if they are shown to the spectators
+    pub fn new_spectator(game: &Game, show_hands: bool) -> Self {
+        let mut view = GameView::new(game, None);
+        if show_hands {
+            for (player, p) in view.players.iter_mut().zip(game.get_players()) {
+                player.hand = p.get_cards().to_vec();
+            }
+        }
+        view
+    }
+
+    /// get_allowed_bids returns the calls the current player can make, nil first if allowed
+    pub fn get_allowed_bids(&self) -> Vec<Bid> {
+        Bid::get_bids(&self.rules, self.players.len())
+            .into_iter()
+            .filter(|b| !self.forbidden_bids.contains(b))
+            .collect()
+    }
+
+    /// get_default_bid returns the call a call picker starts from, the lowest allowed
+    /// call that isn't nil
+    pub fn get_default_bid(&self) -> Bid {
+        let bids = self.get_allowed_bids();
+        bids.iter()
+            .find(|b| !b.is_nil())
+            .or(bids.first())
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// is_my_turn checks if the seat of the view has to call or throw now
+    pub fn is_my_turn(&self) -> bool {
+        self.seat.is_some() && self.seat == self.current_player
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_game_view() {
+        let mut game = Game::default();
+        game.add_players("a", "1111").unwrap();
+        game.add_players("b", "2222").unwrap();
+        game.start().unwrap();
+
+        let view = GameView::new(&game, Some("b"));
+        assert_eq!(view.state, GameState::Calling);
+        assert_eq!(view.seat.as_deref(), Some("b"));
+        assert_eq!(view.hand, game.get_players()[1].get_cards());
+        assert_eq!(view.players[0].cards_left, 26);
+        assert_eq!(view.players[0].call, None);
+        // everyone is a team of their own without a partnership
+        assert_eq!(view.players[1].team, 1);
+        // the player next to the dealer calls first
+        assert!(view.is_my_turn());
+        assert_eq!(view.rules, RuleSet::default());
+        // no redeals without the rule
+        assert!(!view.can_redeal);
+
+        game.place_call("b", 3).unwrap();
+        let view = GameView::new(&game, Some("a"));
+        assert_eq!(view.players[1].call.map(|c| c.get_value()), Some(3));
+        assert_eq!(view.hand, game.get_players()[0].get_cards());
+        assert!(view.is_my_turn());
+
+        // no hand for the players outside the game
+        let view = GameView::new(&game, Some("c"));
+        assert!(view.hand.is_empty());
+        assert!(view.seat.is_none());
+        assert!(!view.is_my_turn());
+        assert!(view.players.iter().all(|p| p.hand.is_empty()));
+
+        // the spectators see either all the hands or none
+        let view = GameView::new_spectator(&game, true);
+        assert!(view.seat.is_none());
+        assert_eq!(view.players[1].hand, game.get_players()[1].get_cards());
+        let view = GameView::new_spectator(&game, false);
+        assert!(view.players.iter().all(|p| p.hand.is_empty()));
+    }
+}